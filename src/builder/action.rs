@@ -0,0 +1,449 @@
+//! Functions for building actions.
+//!
+//! Most functions return a builder with methods for the optional arguments of the action, e.g.
+//! `action::move_to(objects, position).duration(0.5)`. All builders can be converted into an [`Action`]. By default
+//! the script waits for an action to finish before running the next one, use `.wait(false)` to change that.
+
+use super::{
+    expr::{self, Expr},
+    Easing, Music, ObjectSet, Param, Script, Sound, Sprite, Transition, Type, Var,
+};
+use crate::types::{
+    action_type::ActionType,
+    color::Color,
+    function_call::{CallParameter, FunctionCall},
+    novascript::{action::Action, color_channel::ColorChannel, easing},
+    vec2::Vec2,
+};
+
+macro_rules! define_actions {
+    ($(
+        $(#[$meta:meta])*
+        $function:ident -> $name:ident = $variant:ident {
+            $($required:ident: $required_ty:ty),*;
+            $($optional:ident: $optional_ty:ty = $default:expr),*$(,)?
+        }
+    )*) => {
+        $(
+            $(#[$meta])*
+            #[derive(Clone, Debug, PartialEq, Eq)]
+            #[must_use]
+            #[allow(missing_copy_implementations, clippy::struct_field_names)]
+            pub struct $name {
+                $($required: Expr<$required_ty>,)*
+                $($optional: Expr<$optional_ty>,)*
+                wait: bool,
+            }
+
+            $(#[$meta])*
+            #[allow(clippy::missing_const_for_fn)]
+            pub fn $function($($required: impl Into<Expr<$required_ty>>),*) -> $name {
+                $name {
+                    $($required: $required.into(),)*
+                    $($optional: $default.into(),)*
+                    wait: true,
+                }
+            }
+
+            impl $name {
+                $(
+                    #[doc = concat!("Sets the `", stringify!($optional), "` argument.")]
+                    pub fn $optional(mut self, $optional: impl Into<Expr<$optional_ty>>) -> Self {
+                        self.$optional = $optional.into();
+                        self
+                    }
+                )*
+
+                /// Sets whether the script waits for this action to finish before running the next one.
+                pub const fn wait(mut self, wait: bool) -> Self {
+                    self.wait = wait;
+                    self
+                }
+            }
+
+            impl From<$name> for Action {
+                fn from(builder: $name) -> Self {
+                    Self {
+                        closed: false,
+                        wait: builder.wait,
+                        action_type: ActionType::$variant {
+                            $($required: builder.$required.into(),)*
+                            $($optional: builder.$optional.into(),)*
+                        },
+                    }
+                }
+            }
+        )*
+    };
+}
+
+define_actions!(
+    /// Waits for a number of seconds.
+    wait -> Wait = Wait { duration: f32; }
+    /// Waits for a number of frames.
+    wait_frames -> WaitFrames = WaitFrames { frames: i32; }
+    /// Moves objects to a position.
+    move_to -> Move = Move {
+        target_objects: ObjectSet, position: Vec2;
+        global: bool = false, duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Scales objects.
+    scale -> Scale = Scale {
+        target_objects: ObjectSet, scale: Vec2;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Rotates objects to an angle in degrees.
+    rotate -> Rotate = Rotate {
+        target_objects: ObjectSet, rotation: f32;
+        shortest_path: bool = false, global: bool = false, duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Rotates objects around a pivot point.
+    rotate_around -> RotateAround = RotateAround {
+        target_objects: ObjectSet, pivot: Vec2, rotation: f32;
+        rotate_target: bool = true, duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Resets objects to their initial state.
+    reset_object -> ResetObject = ResetObject { target_objects: ObjectSet; }
+    /// Sets the color of objects.
+    set_color -> SetColor = SetColor {
+        target_objects: ObjectSet, color: Color;
+        channel: i32 = ColorChannel::Primary, duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Sets the transparency of objects.
+    set_transparency -> SetTransparency = SetTransparency {
+        target_objects: ObjectSet, transparency: f32;
+        channel: i32 = ColorChannel::Primary, duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Sets the secondary color of objects.
+    set_secondary_color -> SetSecondaryColor = SetSecondaryColor {
+        target_objects: ObjectSet, color: Color;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Sets the secondary transparency of objects.
+    set_secondary_transparency -> SetSecondaryTransparency = SetSecondaryTransparency {
+        target_objects: ObjectSet, transparency: f32;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Sets the border color of objects.
+    set_border_color -> SetBorderColor = SetBorderColor {
+        target_objects: ObjectSet, color: Color;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Sets the border transparency of objects.
+    set_border_transparency -> SetBorderTransparency = SetBorderTransparency {
+        target_objects: ObjectSet, transparency: f32;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Sets the sprite of objects.
+    set_sprite -> SetSprite = SetSprite { target_objects: ObjectSet, sprite: Sprite; }
+    /// Sets the text of text objects.
+    set_text -> SetText = SetText { target_objects: ObjectSet, text: String; }
+    /// Enables or disables objects.
+    set_enabled -> SetEnabled = SetEnabled { target_objects: ObjectSet, enabled: bool; }
+    /// Activates objects.
+    activate -> Activate = Activate { target_objects: ObjectSet; }
+    /// Deactivates objects.
+    deactivate -> Deactivate = Deactivate { target_objects: ObjectSet; }
+    /// Damages objects.
+    damage -> Damage = Damage { target_objects: ObjectSet, damage: f32; }
+    /// Kills objects.
+    kill -> Kill = Kill { target_objects: ObjectSet; }
+    /// Finishes the level.
+    game_finish -> GameFinish = GameFinish { ; }
+    /// Pans the camera to a position.
+    camera_pan -> CameraPan = CameraPan {
+        position: Vec2;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Makes the camera follow the player again.
+    camera_follow_player -> CameraFollowPlayer = CameraFollowPlayer { ; }
+    /// Zooms the camera to a viewport size.
+    camera_zoom -> CameraZoom = CameraZoom {
+        viewport_size: f32;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Resets the camera zoom.
+    camera_zoom_reset -> CameraZoomReset = CameraZoomReset {
+        ;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Offsets the camera.
+    camera_offset -> CameraOffset = CameraOffset {
+        offset: Vec2;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Resets the camera offset.
+    camera_offset_reset -> CameraOffsetReset = CameraOffsetReset {
+        ;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Shakes the camera.
+    camera_shake -> CameraShake = CameraShake {
+        strength: f32, roughness: f32;
+        fade_in: f32 = 0.0, fade_out: f32 = 0.0, duration: f32 = 0.0,
+    }
+    /// Plays a sound.
+    play_sound -> PlaySound = PlaySound {
+        sound: Sound;
+        volume: f32 = 1.0, pitch: f32 = 1.0,
+    }
+    /// Plays music.
+    play_music -> PlayMusic = PlayMusic {
+        music: Music;
+        volume: f32 = 1.0, pitch: f32 = 1.0,
+    }
+    /// Sets the direction of objects.
+    set_direction -> SetDirection = SetDirection { target_objects: ObjectSet, direction: i32; }
+    /// Sets the gravity of objects.
+    set_gravity -> SetGravity = SetGravity { target_objects: ObjectSet, gravity: Vec2; }
+    /// Sets the velocity of objects.
+    set_velocity -> SetVelocity = SetVelocity { target_objects: ObjectSet, velocity: Vec2; }
+    /// Enables or disables cinematic mode.
+    set_cinematic -> SetCinematic = SetCinematic { enabled: bool; }
+    /// Enables or disables player input.
+    set_input_enabled -> SetInputEnabled = SetInputEnabled { enabled: bool; }
+    /// Enables or disables the timer.
+    set_timer_enabled -> SetTimerEnabled = SetTimerEnabled { enabled: bool; }
+    /// Shows a text on the screen.
+    game_text_show -> GameTextShow = GameTextShow {
+        text: String;
+        duration: f32 = 1.0,
+    }
+    /// Shows a dialogue bubble.
+    dialogue_show -> DialogueShow = DialogueShow {
+        text: String, position: Vec2;
+        reverse_direction: bool = false,
+    }
+    /// Stops a running script.
+    stop_script -> StopScript = StopScript { script: Script; }
+    /// Plays a transition into the level.
+    transition_in -> TransitionIn = TransitionIn {
+        type_: Transition;
+        color: Color = Color::new(0.0, 0.0, 0.0, 1.0), duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Plays a transition out of the level.
+    transition_out -> TransitionOut = TransitionOut {
+        type_: Transition;
+        color: Color = Color::new(0.0, 0.0, 0.0, 1.0), duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Changes the speed of time.
+    time_scale -> TimeScale = TimeScale {
+        time_scale: f32;
+        duration: f32 = 0.0, easing: Easing = easing::Easing::Linear,
+    }
+    /// Stops a sound instance.
+    stop_sound -> StopSound = StopSound {
+        sound_instance: i32;
+        fade_out: f32 = 0.0,
+    }
+    /// Plays the particle systems of objects.
+    play_particle_system -> PlayParticleSystem = PlayParticleSystem { target_objects: ObjectSet; }
+    /// Stops the particle systems of objects.
+    stop_particle_system -> StopParticleSystem = StopParticleSystem {
+        target_objects: ObjectSet;
+        clear: bool = false,
+    }
+);
+
+/// Sets a variable to a value.
+pub fn set<T: Type>(variable: Var<T>, value: impl Into<Expr<T>>) -> Action {
+    Action {
+        closed: false,
+        wait: true,
+        action_type: ActionType::SetVariable {
+            variable: variable.id(),
+            value: Some(value.into().into()),
+        },
+    }
+}
+
+/// Resets a variable to its initial value.
+#[must_use]
+pub const fn reset<T: Type>(variable: Var<T>) -> Action {
+    Action {
+        closed: false,
+        wait: true,
+        action_type: ActionType::ResetVariable {
+            variable: variable.id(),
+        },
+    }
+}
+
+/// Gradually changes a variable to a value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct SetOverTime<T> {
+    variable: Var<T>,
+    value: Expr<T>,
+    duration: Expr<f32>,
+    easing: Expr<Easing>,
+    wait: bool,
+}
+
+/// Gradually changes a variable to a value.
+pub fn set_over_time<T: Type>(variable: Var<T>, value: impl Into<Expr<T>>) -> SetOverTime<T> {
+    SetOverTime {
+        variable,
+        value: value.into(),
+        duration: expr::float(0.0),
+        easing: easing::Easing::Linear.into(),
+        wait: true,
+    }
+}
+
+impl<T> SetOverTime<T> {
+    /// Sets the `duration` argument.
+    pub fn duration(mut self, duration: impl Into<Expr<f32>>) -> Self {
+        self.duration = duration.into();
+        self
+    }
+
+    /// Sets the `easing` argument.
+    pub fn easing(mut self, easing: impl Into<Expr<Easing>>) -> Self {
+        self.easing = easing.into();
+        self
+    }
+
+    /// Sets whether the script waits for this action to finish before running the next one.
+    pub const fn wait(mut self, wait: bool) -> Self {
+        self.wait = wait;
+        self
+    }
+}
+
+impl<T: Type> From<SetOverTime<T>> for Action {
+    fn from(builder: SetOverTime<T>) -> Self {
+        Self {
+            closed: false,
+            wait: builder.wait,
+            action_type: ActionType::SetVariableOverTime {
+                variable: builder.variable.id(),
+                value: Some(builder.value.into()),
+                duration: builder.duration.into(),
+                easing: builder.easing.into(),
+            },
+        }
+    }
+}
+
+/// Repeats actions a number of times.
+pub fn repeat(count: impl Into<Expr<i32>>, actions: impl IntoIterator<Item = Action>) -> Action {
+    block(ActionType::Repeat {
+        actions: actions.into_iter().collect(),
+        count: count.into().into(),
+    })
+}
+
+/// Repeats actions while a condition is true.
+pub fn repeat_while(
+    condition: impl Into<Expr<bool>>,
+    actions: impl IntoIterator<Item = Action>,
+) -> Action {
+    block(ActionType::RepeatWhile {
+        actions: actions.into_iter().collect(),
+        condition: condition.into().into(),
+    })
+}
+
+/// Repeats actions for each object in a set, see [`expr::repeat_object`].
+pub fn for_each_object(
+    target_objects: impl Into<Expr<ObjectSet>>,
+    actions: impl IntoIterator<Item = Action>,
+) -> Action {
+    block(ActionType::RepeatForEachObject {
+        target_objects: target_objects.into().into(),
+        actions: actions.into_iter().collect(),
+    })
+}
+
+/// Runs actions if a condition is true, and optionally other actions if it isn't.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct If {
+    condition: Expr<bool>,
+    then: Vec<Action>,
+    otherwise: Vec<Action>,
+}
+
+/// Runs actions if a condition is true. Use [`If::otherwise`] to add actions that run if it isn't.
+pub fn if_then(condition: impl Into<Expr<bool>>, actions: impl IntoIterator<Item = Action>) -> If {
+    If {
+        condition: condition.into(),
+        then: actions.into_iter().collect(),
+        otherwise: Vec::new(),
+    }
+}
+
+impl If {
+    /// Sets the actions that run if the condition is false.
+    pub fn otherwise(mut self, actions: impl IntoIterator<Item = Action>) -> Self {
+        self.otherwise = actions.into_iter().collect();
+        self
+    }
+}
+
+impl From<If> for Action {
+    fn from(builder: If) -> Self {
+        block(ActionType::ConditionBlock {
+            if_actions: builder.then,
+            else_actions: builder.otherwise,
+            condition: builder.condition.into(),
+        })
+    }
+}
+
+/// Runs a function script.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[must_use]
+pub struct RunFunction {
+    function: FunctionCall,
+    wait: bool,
+}
+
+/// Runs a function script. Use [`RunFunction::arg`] to pass arguments to it.
+pub const fn run_function(script_id: i32) -> RunFunction {
+    RunFunction {
+        function: FunctionCall {
+            id: script_id,
+            parameters: Vec::new(),
+        },
+        wait: true,
+    }
+}
+
+impl RunFunction {
+    /// Passes an argument to the function.
+    pub fn arg<T: Type>(mut self, parameter: Param<T>, value: impl Into<Expr<T>>) -> Self {
+        self.function.parameters.push(CallParameter {
+            parameter_id: parameter.id(),
+            value: value.into().into(),
+        });
+        self
+    }
+
+    /// Sets whether the script waits for the function to finish before running the next action.
+    pub const fn wait(mut self, wait: bool) -> Self {
+        self.wait = wait;
+        self
+    }
+}
+
+impl From<RunFunction> for Action {
+    fn from(builder: RunFunction) -> Self {
+        Self {
+            closed: false,
+            wait: builder.wait,
+            action_type: ActionType::RunFunction {
+                function: builder.function,
+            },
+        }
+    }
+}
+
+const fn block(action_type: ActionType) -> Action {
+    Action {
+        closed: false,
+        wait: true,
+        action_type,
+    }
+}
@@ -0,0 +1,43 @@
+//! Functions for building activators, which start a script.
+//!
+//! The parameters of each activator are typed, so e.g. [`collision_enter`] only accepts an object and an object set.
+
+use super::{expr::Expr, Object, ObjectSet};
+use crate::types::novascript::activator::{Activator, ActivatorType, Input};
+
+/// Starts the script when the level starts.
+#[must_use]
+pub const fn level_start() -> Activator {
+    without_parameters(ActivatorType::LevelStart)
+}
+
+/// Starts the script every frame.
+#[must_use]
+pub const fn every_frame() -> Activator {
+    without_parameters(ActivatorType::EveryFrame)
+}
+
+/// Starts the script when the player uses the given input.
+#[must_use]
+pub const fn input(input: Input) -> Activator {
+    without_parameters(ActivatorType::Input(input))
+}
+
+/// Starts the script when `object` starts colliding with one of `objects`.
+#[must_use]
+pub fn collision_enter(
+    object: impl Into<Expr<Object>>,
+    objects: impl Into<Expr<ObjectSet>>,
+) -> Activator {
+    Activator {
+        activator_type: ActivatorType::CollisionEnter,
+        parameters: vec![object.into().into(), objects.into().into()],
+    }
+}
+
+const fn without_parameters(activator_type: ActivatorType) -> Activator {
+    Activator {
+        activator_type,
+        parameters: Vec::new(),
+    }
+}
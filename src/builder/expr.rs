@@ -0,0 +1,745 @@
+//! Functions for building typed expressions.
+
+use super::{
+    Easing, Layer, Music, Object, ObjectSet, Param, Script, Sound, Sprite, Transition, Type, Var,
+};
+use crate::types::{
+    color::Color,
+    dynamic_type::DynamicType,
+    novascript::{
+        color_channel::ColorChannel,
+        nova_value::{NovaValue, NovaValueInner},
+    },
+    vec2::Vec2,
+};
+use core::{
+    marker::PhantomData,
+    ops::{Add, BitAnd, BitOr, Div, Mul, Not, Rem, Sub},
+};
+
+/// An expression that evaluates to a value of type `T`.
+///
+/// Literals can be converted into expressions of the corresponding type, e.g. `2.0` into an `Expr<f32>`.
+#[derive(Debug)]
+pub struct Expr<T> {
+    value: NovaValue,
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<T> Expr<T> {
+    /// Wraps a raw expression.
+    ///
+    /// The type isn't checked, so this should only be used for expressions that are known to evaluate to `T`.
+    #[must_use]
+    pub const fn from_value(value: NovaValue) -> Self {
+        Self {
+            value,
+            ty: PhantomData,
+        }
+    }
+
+    /// Returns the raw expression.
+    #[must_use]
+    pub const fn value(&self) -> &NovaValue {
+        &self.value
+    }
+
+    /// Converts this into the raw expression.
+    #[must_use]
+    pub fn into_value(self) -> NovaValue {
+        self.value
+    }
+
+    fn op<U>(ty: DynamicType, operands: impl IntoIterator<Item = NovaValue>) -> Expr<U> {
+        Expr::from_value(NovaValue::new_sub_values(
+            ty,
+            operands.into_iter().collect(),
+        ))
+    }
+
+    fn unary<U>(self, ty: DynamicType) -> Expr<U> {
+        Self::op(ty, [self.value])
+    }
+
+    fn binary<U, R>(self, ty: DynamicType, rhs: impl Into<Expr<R>>) -> Expr<U> {
+        Self::op(ty, [self.value, rhs.into().value])
+    }
+}
+
+impl<T> Clone for Expr<T> {
+    fn clone(&self) -> Self {
+        Self::from_value(self.value.clone())
+    }
+}
+
+impl<T> PartialEq for Expr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T> Eq for Expr<T> {}
+
+impl<T> core::hash::Hash for Expr<T> {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<T> From<Expr<T>> for NovaValue {
+    fn from(expr: Expr<T>) -> Self {
+        expr.value
+    }
+}
+
+impl<T: Type> From<Var<T>> for Expr<T> {
+    fn from(variable: Var<T>) -> Self {
+        var(variable)
+    }
+}
+
+impl<T: Type> From<Param<T>> for Expr<T> {
+    fn from(parameter: Param<T>) -> Self {
+        param(parameter)
+    }
+}
+
+/// A bool constant.
+#[must_use]
+pub fn bool(value: bool) -> Expr<bool> {
+    Expr::from_value(NovaValue::new_bool(DynamicType::BoolConstant, value))
+}
+
+/// An int constant.
+#[must_use]
+pub fn int(value: i32) -> Expr<i32> {
+    Expr::from_value(NovaValue::new_int(DynamicType::IntConstant, value))
+}
+
+/// A float constant.
+#[must_use]
+pub fn float(value: f32) -> Expr<f32> {
+    Expr::from_value(NovaValue::new_float(DynamicType::FloatConstant, value))
+}
+
+/// A string constant.
+#[must_use]
+pub fn string(value: impl Into<String>) -> Expr<String> {
+    Expr::from_value(NovaValue::new_string(
+        DynamicType::StringConstant,
+        value.into(),
+    ))
+}
+
+/// A color constant.
+#[must_use]
+pub fn color(r: f32, g: f32, b: f32, a: f32) -> Expr<Color> {
+    Expr::from_value(NovaValue::new_color(
+        DynamicType::ColorConstant,
+        Color::new(r, g, b, a),
+    ))
+}
+
+/// A color made from the given components.
+#[must_use]
+pub fn color_from(
+    r: impl Into<Expr<f32>>,
+    g: impl Into<Expr<f32>>,
+    b: impl Into<Expr<f32>>,
+    a: impl Into<Expr<f32>>,
+) -> Expr<Color> {
+    Expr::<Color>::op(
+        DynamicType::ColorValues,
+        [
+            r.into().value,
+            g.into().value,
+            b.into().value,
+            a.into().value,
+        ],
+    )
+}
+
+/// A vector constant.
+#[must_use]
+pub fn vec(x: f32, y: f32) -> Expr<Vec2> {
+    Expr::from_value(NovaValue::new_vector(
+        DynamicType::VectorConstant,
+        Vec2::new(x, y),
+    ))
+}
+
+/// A vector made from the given components.
+#[must_use]
+pub fn vec_from(x: impl Into<Expr<f32>>, y: impl Into<Expr<f32>>) -> Expr<Vec2> {
+    Expr::<Vec2>::op(DynamicType::VectorValues, [x.into().value, y.into().value])
+}
+
+/// A sound constant.
+#[must_use]
+pub fn sound(name: impl Into<String>) -> Expr<Sound> {
+    Expr::from_value(NovaValue::new_string(
+        DynamicType::SoundConstant,
+        name.into(),
+    ))
+}
+
+/// A music constant.
+#[must_use]
+pub fn music(name: impl Into<String>) -> Expr<Music> {
+    Expr::from_value(NovaValue::new_string(
+        DynamicType::MusicConstant,
+        name.into(),
+    ))
+}
+
+/// A sprite constant.
+#[must_use]
+pub fn sprite(name: impl Into<String>) -> Expr<Sprite> {
+    Expr::from_value(NovaValue::new_string(
+        DynamicType::SpriteConstant,
+        name.into(),
+    ))
+}
+
+/// The object with the given entity id.
+#[must_use]
+pub fn object(entity_id: i32) -> Expr<Object> {
+    Expr::from_value(NovaValue::new_int(DynamicType::ObjectConstant, entity_id))
+}
+
+/// The set of objects with the given entity ids.
+#[must_use]
+pub fn object_set(entity_ids: impl IntoIterator<Item = i32>) -> Expr<ObjectSet> {
+    Expr::from_value(NovaValue::new_int_list(
+        DynamicType::ObjectSetConstant,
+        entity_ids.into_iter().collect(),
+    ))
+}
+
+/// The transition with the given id.
+#[must_use]
+pub fn transition(id: i32) -> Expr<Transition> {
+    Expr::from_value(NovaValue::new_int(DynamicType::TransitionConstant, id))
+}
+
+/// The easing with the given id.
+#[must_use]
+pub fn easing(id: i32) -> Expr<Easing> {
+    Expr::from_value(NovaValue::new_int(DynamicType::EasingConstant, id))
+}
+
+/// The script with the given id.
+#[must_use]
+pub fn script(script_id: i32) -> Expr<Script> {
+    Expr::from_value(NovaValue::new_int(DynamicType::ScriptConstant, script_id))
+}
+
+/// The layer with the given id.
+#[must_use]
+pub fn layer(layer_id: i32) -> Expr<Layer> {
+    Expr::from_value(NovaValue::new_int(DynamicType::LayerConstant, layer_id))
+}
+
+/// Reads a variable, e.g. `expr::var::<f32>(speed)`.
+#[must_use]
+pub fn var<T: Type>(variable: impl Into<Var<T>>) -> Expr<T> {
+    Expr::from_value(variable.into().into())
+}
+
+/// Reads a parameter of the current function.
+#[must_use]
+pub fn param<T: Type>(parameter: impl Into<Param<T>>) -> Expr<T> {
+    Expr::from_value(parameter.into().into())
+}
+
+/// The player.
+#[must_use]
+pub fn player() -> Expr<Object> {
+    nullary(DynamicType::ObjectPlayer)
+}
+
+/// All objects with the given tag.
+#[must_use]
+pub fn objects_with_tag(tag: impl Into<Expr<String>>) -> Expr<ObjectSet> {
+    tag.into().unary(DynamicType::ObjectSetObjectsWithTag)
+}
+
+/// A random int between `min` and `max`, inclusive.
+#[must_use]
+pub fn random_int(min: impl Into<Expr<i32>>, max: impl Into<Expr<i32>>) -> Expr<i32> {
+    min.into().binary(DynamicType::IntRandom, max)
+}
+
+/// A random float between `min` and `max`.
+#[must_use]
+pub fn random_float(min: impl Into<Expr<f32>>, max: impl Into<Expr<f32>>) -> Expr<f32> {
+    min.into().binary(DynamicType::FloatRandom, max)
+}
+
+/// The number of times the innermost repeat block has repeated.
+#[must_use]
+pub fn repeat_count() -> Expr<i32> {
+    nullary(DynamicType::IntRepeatCount)
+}
+
+/// The current object of the innermost "repeat for each object" block.
+#[must_use]
+pub fn repeat_object() -> Expr<Object> {
+    nullary(DynamicType::ObjectRepeatObject)
+}
+
+/// The time since the level started, in seconds.
+#[must_use]
+pub fn time() -> Expr<f32> {
+    nullary(DynamicType::FloatTime)
+}
+
+fn nullary<T>(ty: DynamicType) -> Expr<T> {
+    Expr::from_value(NovaValue {
+        dynamic_type: ty,
+        inner: NovaValueInner::default(),
+    })
+}
+
+macro_rules! impl_literals {
+    ($($literal:ty => $ty:ty, $constructor:ident;)*) => {
+        $(
+            impl From<$literal> for Expr<$ty> {
+                fn from(value: $literal) -> Self {
+                    $constructor(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_literals!(
+    bool => bool, bool;
+    i32 => i32, int;
+    f32 => f32, float;
+    String => String, string;
+    &str => String, string;
+);
+
+impl From<Color> for Expr<Color> {
+    fn from(value: Color) -> Self {
+        Self::from_value(NovaValue::new_color(DynamicType::ColorConstant, value))
+    }
+}
+
+impl From<Vec2> for Expr<Vec2> {
+    fn from(value: Vec2) -> Self {
+        Self::from_value(NovaValue::new_vector(DynamicType::VectorConstant, value))
+    }
+}
+
+impl From<crate::types::novascript::easing::Easing> for Expr<Easing> {
+    fn from(value: crate::types::novascript::easing::Easing) -> Self {
+        easing(value.into())
+    }
+}
+
+impl From<crate::types::novascript::transition::Transition> for Expr<Transition> {
+    fn from(value: crate::types::novascript::transition::Transition) -> Self {
+        transition(value.into())
+    }
+}
+
+impl From<ColorChannel> for Expr<i32> {
+    fn from(value: ColorChannel) -> Self {
+        int(value.into())
+    }
+}
+
+impl From<Vec<i32>> for Expr<ObjectSet> {
+    fn from(entity_ids: Vec<i32>) -> Self {
+        object_set(entity_ids)
+    }
+}
+
+impl From<Expr<Object>> for Expr<ObjectSet> {
+    /// Uses a single object as an object set, which is accepted wherever an object set is expected.
+    fn from(object: Expr<Object>) -> Self {
+        Self::from_value(object.value)
+    }
+}
+
+/// Types that can be compared for equality.
+pub trait Equatable: Type {
+    /// The dynamic type used to compare two values for equality.
+    const EQUAL: DynamicType;
+    /// The dynamic type used to compare two values for inequality.
+    const NOT_EQUAL: DynamicType;
+}
+
+macro_rules! impl_equatable {
+    ($($ty:ty => $equal:ident, $not_equal:ident;)*) => {
+        $(
+            impl Equatable for $ty {
+                const EQUAL: DynamicType = DynamicType::$equal;
+                const NOT_EQUAL: DynamicType = DynamicType::$not_equal;
+            }
+        )*
+    };
+}
+
+impl_equatable!(
+    bool => BoolEqualBool, BoolNotEqualBool;
+    i32 => BoolEqualNumber, BoolNotEqualNumber;
+    f32 => BoolEqualNumber, BoolNotEqualNumber;
+    String => BoolEqualString, BoolNotEqualString;
+    Color => BoolEqualColor, BoolNotEqualColor;
+    Vec2 => BoolEqualVector, BoolNotEqualVector;
+    Object => BoolEqualObject, BoolNotEqualObject;
+);
+
+impl<T: Equatable> Expr<T> {
+    /// Whether both values are equal.
+    #[must_use]
+    pub fn equals(self, rhs: impl Into<Self>) -> Expr<bool> {
+        self.binary(T::EQUAL, rhs)
+    }
+
+    /// Whether both values are different.
+    #[must_use]
+    pub fn not_equals(self, rhs: impl Into<Self>) -> Expr<bool> {
+        self.binary(T::NOT_EQUAL, rhs)
+    }
+}
+
+/// Number types, i.e. ints and floats.
+pub trait Number: Type {
+    /// The dynamic types used for addition, subtraction, multiplication, division, modulo, minimum, maximum and
+    /// absolute value, in that order.
+    const OPS: [DynamicType; 8];
+}
+
+impl Number for i32 {
+    const OPS: [DynamicType; 8] = [
+        DynamicType::IntAdd,
+        DynamicType::IntSubtract,
+        DynamicType::IntMultiply,
+        DynamicType::IntDivide,
+        DynamicType::IntModulo,
+        DynamicType::IntMin,
+        DynamicType::IntMax,
+        DynamicType::IntAbs,
+    ];
+}
+
+impl Number for f32 {
+    const OPS: [DynamicType; 8] = [
+        DynamicType::FloatAdd,
+        DynamicType::FloatSubtract,
+        DynamicType::FloatMultiply,
+        DynamicType::FloatDivide,
+        DynamicType::FloatModulo,
+        DynamicType::FloatMin,
+        DynamicType::FloatMax,
+        DynamicType::FloatAbs,
+    ];
+}
+
+impl<T: Number> Expr<T> {
+    /// The smaller of both values.
+    #[must_use]
+    pub fn min(self, rhs: impl Into<Self>) -> Self {
+        self.binary(T::OPS[5], rhs)
+    }
+
+    /// The larger of both values.
+    #[must_use]
+    pub fn max(self, rhs: impl Into<Self>) -> Self {
+        self.binary(T::OPS[6], rhs)
+    }
+
+    /// The absolute value.
+    #[must_use]
+    pub fn abs(self) -> Self {
+        self.unary(T::OPS[7])
+    }
+
+    /// Whether this value is less than `rhs`.
+    #[must_use]
+    pub fn less(self, rhs: impl Into<Self>) -> Expr<bool> {
+        self.binary(DynamicType::BoolLess, rhs)
+    }
+
+    /// Whether this value is less than or equal to `rhs`.
+    #[must_use]
+    pub fn less_or_equal(self, rhs: impl Into<Self>) -> Expr<bool> {
+        self.binary(DynamicType::BoolLessOrEqual, rhs)
+    }
+
+    /// Whether this value is greater than `rhs`.
+    #[must_use]
+    pub fn greater(self, rhs: impl Into<Self>) -> Expr<bool> {
+        self.binary(DynamicType::BoolGreater, rhs)
+    }
+
+    /// Whether this value is greater than or equal to `rhs`.
+    #[must_use]
+    pub fn greater_or_equal(self, rhs: impl Into<Self>) -> Expr<bool> {
+        self.binary(DynamicType::BoolGreaterOrEqual, rhs)
+    }
+}
+
+macro_rules! impl_number_ops {
+    ($($trait:ident, $method:ident, $index:literal;)*) => {
+        $(
+            impl<T: Number, R: Into<Self>> $trait<R> for Expr<T> {
+                type Output = Self;
+
+                fn $method(self, rhs: R) -> Self {
+                    self.binary(T::OPS[$index], rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_number_ops!(
+    Add, add, 0;
+    Sub, sub, 1;
+    Mul, mul, 2;
+    Div, div, 3;
+    Rem, rem, 4;
+);
+
+impl Expr<i32> {
+    /// Converts the int to a string.
+    #[must_use]
+    pub fn into_string(self) -> Expr<String> {
+        self.unary(DynamicType::StringFromInt)
+    }
+}
+
+impl Expr<f32> {
+    /// Raises the value to the power of `exponent`.
+    #[must_use]
+    pub fn pow(self, exponent: impl Into<Self>) -> Self {
+        self.binary(DynamicType::FloatPow, exponent)
+    }
+
+    /// The square root.
+    #[must_use]
+    pub fn sqrt(self) -> Self {
+        self.unary(DynamicType::FloatSqrt)
+    }
+
+    /// The sine of an angle in degrees.
+    #[must_use]
+    pub fn sin(self) -> Self {
+        self.unary(DynamicType::FloatSin)
+    }
+
+    /// The cosine of an angle in degrees.
+    #[must_use]
+    pub fn cos(self) -> Self {
+        self.unary(DynamicType::FloatCos)
+    }
+
+    /// Rounds the value to the nearest int.
+    #[must_use]
+    pub fn round(self) -> Expr<i32> {
+        self.unary(DynamicType::IntRound)
+    }
+
+    /// Rounds the value down to an int.
+    #[must_use]
+    pub fn floor(self) -> Expr<i32> {
+        self.unary(DynamicType::IntFloor)
+    }
+
+    /// Rounds the value up to an int.
+    #[must_use]
+    pub fn ceil(self) -> Expr<i32> {
+        self.unary(DynamicType::IntCeil)
+    }
+
+    /// Converts the float to a string.
+    #[must_use]
+    pub fn into_string(self) -> Expr<String> {
+        self.unary(DynamicType::StringFromFloat)
+    }
+}
+
+impl Not for Expr<bool> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        self.unary(DynamicType::BoolNot)
+    }
+}
+
+impl<R: Into<Self>> BitAnd<R> for Expr<bool> {
+    type Output = Self;
+
+    fn bitand(self, rhs: R) -> Self {
+        self.binary(DynamicType::BoolAnd, rhs)
+    }
+}
+
+impl<R: Into<Self>> BitOr<R> for Expr<bool> {
+    type Output = Self;
+
+    fn bitor(self, rhs: R) -> Self {
+        self.binary(DynamicType::BoolOr, rhs)
+    }
+}
+
+impl<R: Into<Self>> Add<R> for Expr<String> {
+    type Output = Self;
+
+    fn add(self, rhs: R) -> Self {
+        self.binary(DynamicType::StringConcat, rhs)
+    }
+}
+
+impl Expr<String> {
+    /// The number of characters in the string.
+    #[must_use]
+    pub fn len(self) -> Expr<i32> {
+        self.unary(DynamicType::IntStringLength)
+    }
+}
+
+impl<R: Into<Self>> Add<R> for Expr<Vec2> {
+    type Output = Self;
+
+    fn add(self, rhs: R) -> Self {
+        self.binary(DynamicType::VectorAdd, rhs)
+    }
+}
+
+impl<R: Into<Self>> Sub<R> for Expr<Vec2> {
+    type Output = Self;
+
+    fn sub(self, rhs: R) -> Self {
+        self.binary(DynamicType::VectorSubtract, rhs)
+    }
+}
+
+impl<R: Into<Expr<f32>>> Mul<R> for Expr<Vec2> {
+    type Output = Self;
+
+    fn mul(self, rhs: R) -> Self {
+        self.binary(DynamicType::VectorMultiply, rhs)
+    }
+}
+
+impl<R: Into<Expr<f32>>> Div<R> for Expr<Vec2> {
+    type Output = Self;
+
+    fn div(self, rhs: R) -> Self {
+        self.binary(DynamicType::VectorDivide, rhs)
+    }
+}
+
+impl Expr<Vec2> {
+    /// The x component.
+    #[must_use]
+    pub fn x(self) -> Expr<f32> {
+        self.unary(DynamicType::FloatVectorX)
+    }
+
+    /// The y component.
+    #[must_use]
+    pub fn y(self) -> Expr<f32> {
+        self.unary(DynamicType::FloatVectorY)
+    }
+
+    /// The length of the vector.
+    #[must_use]
+    pub fn length(self) -> Expr<f32> {
+        self.unary(DynamicType::FloatVectorLength)
+    }
+
+    /// The vector with the same direction and a length of 1.
+    #[must_use]
+    pub fn normalize(self) -> Self {
+        self.unary(DynamicType::VectorNormalize)
+    }
+
+    /// The vector rotated counterclockwise by an angle in degrees.
+    #[must_use]
+    pub fn rotate(self, degrees: impl Into<Expr<f32>>) -> Self {
+        self.binary(DynamicType::VectorRotate, degrees)
+    }
+
+    /// The distance between both points.
+    #[must_use]
+    pub fn distance(self, rhs: impl Into<Self>) -> Expr<f32> {
+        self.binary(DynamicType::FloatVectorDistance, rhs)
+    }
+
+    /// The dot product of both vectors.
+    #[must_use]
+    pub fn dot(self, rhs: impl Into<Self>) -> Expr<f32> {
+        self.binary(DynamicType::FloatVectorDot, rhs)
+    }
+}
+
+impl Expr<Object> {
+    /// The position of the object.
+    #[must_use]
+    pub fn position(self) -> Expr<Vec2> {
+        self.unary(DynamicType::VectorObjectPos)
+    }
+
+    /// The rotation of the object in degrees.
+    #[must_use]
+    pub fn rotation(self) -> Expr<f32> {
+        self.unary(DynamicType::FloatObjectRotation)
+    }
+
+    /// The tag of the object.
+    #[must_use]
+    pub fn tag(self) -> Expr<String> {
+        self.unary(DynamicType::StringObjectTag)
+    }
+
+    /// Whether the object is dead.
+    #[must_use]
+    pub fn is_dead(self) -> Expr<bool> {
+        self.unary(DynamicType::BoolObjectDead)
+    }
+}
+
+impl Expr<ObjectSet> {
+    /// The number of objects in the set.
+    #[must_use]
+    pub fn count(self) -> Expr<i32> {
+        self.unary(DynamicType::IntObjectSetCount)
+    }
+
+    /// The first object in the set.
+    #[must_use]
+    pub fn first(self) -> Expr<Object> {
+        self.unary(DynamicType::ObjectFirstFromSet)
+    }
+
+    /// The object at the given index in the set.
+    #[must_use]
+    pub fn get(self, index: impl Into<Expr<i32>>) -> Expr<Object> {
+        self.binary(DynamicType::ObjectElementFromSet, index)
+    }
+
+    /// All objects in both sets, without duplicates.
+    #[must_use]
+    pub fn union(self, rhs: impl Into<Self>) -> Self {
+        self.binary(DynamicType::ObjectSetUnion, rhs)
+    }
+
+    /// The objects that are in both sets.
+    #[must_use]
+    pub fn intersection(self, rhs: impl Into<Self>) -> Self {
+        self.binary(DynamicType::ObjectSetIntersection, rhs)
+    }
+
+    /// The objects in this set that aren't in `rhs`.
+    #[must_use]
+    pub fn difference(self, rhs: impl Into<Self>) -> Self {
+        self.binary(DynamicType::ObjectSetDifference, rhs)
+    }
+}
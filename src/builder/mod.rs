@@ -0,0 +1,421 @@
+//! A typed builder API for `NovaScript` scripts, actions and expressions.
+//!
+//! Expressions are built with the functions in [`expr`] and have the type [`Expr<T>`](expr::Expr), where `T` is the
+//! type of the value they evaluate to, so passing e.g. a float where a vector is expected is a compile error.
+//! Actions are built with the functions in [`action`], with optional arguments set using builder methods.
+//!
+//! Scripts and global variables are added to a level with [`LevelData::new_script`] and [`LevelData::add_global`],
+//! which take care of allocating unused ids.
+//!
+//! ```
+//! use exolvl::builder::{action, expr, ObjectSet};
+//! use exolvl::types::level_data::LevelData;
+//! use exolvl::types::novascript::easing::Easing;
+//!
+//! let mut level = LevelData::default();
+//! let speed = level.add_global::<f32>("speed", 2.0);
+//!
+//! let mut script = level.new_script("move the platforms");
+//! let platforms = script.variable::<ObjectSet>("platforms", expr::objects_with_tag("platform"));
+//!
+//! script.action(
+//!     action::move_to(platforms.get(), expr::vec(10.0, 0.0))
+//!         .duration(expr::float(5.0) / speed.get())
+//!         .easing(Easing::In)
+//!         .wait(false),
+//! );
+//!
+//! let script_id = script.finish();
+//!
+//! assert_eq!(level.nova_scripts[0].script_id, script_id);
+//! assert_eq!(level.nova_scripts[0].actions.len(), 1);
+//! ```
+
+pub mod action;
+pub mod activator;
+pub mod expr;
+
+use crate::{
+    private::Sealed,
+    types::{
+        color::Color,
+        dynamic_type::DynamicType,
+        level_data::LevelData,
+        novascript::{
+            action::Action, activator::Activator, nova_value::NovaValue, parameter::Parameter,
+            static_type::StaticType, variable::Variable, NovaScript,
+        },
+        vec2::Vec2,
+    },
+};
+use core::marker::PhantomData;
+use expr::Expr;
+use std::collections::HashSet;
+
+/// A `NovaScript` type, used as the type parameter of [`Expr`], [`Var`] and [`Param`].
+///
+/// Bools, ints, floats, strings, colors and vectors use the corresponding Rust types (`bool`, `i32`, `f32`,
+/// `String`, [`Color`] and [`Vec2`]), the other types have their own marker types in this module.
+pub trait Type: Sealed {
+    /// The static type of this type.
+    const STATIC_TYPE: StaticType;
+    /// The dynamic type used for variables of this type.
+    const VARIABLE: DynamicType;
+    /// The dynamic type used for parameters of this type.
+    const PARAMETER: DynamicType;
+}
+
+macro_rules! define_markers {
+    ($($(#[$meta:meta])* $name:ident),*$(,)?) => {
+        $(
+            $(#[$meta])*
+            #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+            pub struct $name;
+        )*
+    };
+}
+
+define_markers!(
+    /// The type of sound expressions.
+    Sound,
+    /// The type of music expressions.
+    Music,
+    /// The type of object expressions.
+    Object,
+    /// The type of object set expressions. Objects can be used where object sets are expected.
+    ObjectSet,
+    /// The type of transition expressions.
+    Transition,
+    /// The type of easing expressions.
+    Easing,
+    /// The type of sprite expressions.
+    Sprite,
+    /// The type of script expressions.
+    Script,
+    /// The type of layer expressions.
+    Layer,
+);
+
+macro_rules! impl_type {
+    ($($ty:ty => $static_type:ident, $variable:ident, $parameter:ident;)*) => {
+        $(
+            impl Type for $ty {
+                const STATIC_TYPE: StaticType = StaticType::$static_type;
+                const VARIABLE: DynamicType = DynamicType::$variable;
+                const PARAMETER: DynamicType = DynamicType::$parameter;
+            }
+        )*
+    };
+}
+
+impl_type!(
+    bool => Bool, BoolVariable, BoolParameter;
+    i32 => Int, IntVariable, IntParameter;
+    f32 => Float, FloatVariable, FloatParameter;
+    String => String, StringVariable, StringParameter;
+    Color => Color, ColorVariable, ColorParameter;
+    Vec2 => Vector, VectorVariable, VectorParameter;
+    Sound => Sound, SoundVariable, SoundParameter;
+    Music => Music, MusicVariable, MusicParameter;
+    Object => Object, ObjectVariable, ObjectParameter;
+    ObjectSet => ObjectSet, ObjectSetVariable, ObjectSetParameter;
+    Transition => Transition, TransitionVariable, TransitionParameter;
+    Easing => Easing, EasingVariable, EasingParameter;
+    Sprite => Sprite, SpriteVariable, SpriteParameter;
+    Script => Script, ScriptVariable, ScriptParameter;
+    Layer => Layer, LayerVariable, LayerParameter;
+);
+
+/// A reference to a (global or local) variable of type `T`.
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Var<T> {
+    id: i32,
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<T: Type> Var<T> {
+    /// Creates a reference to the variable with the given id.
+    ///
+    /// The type isn't checked, so this should only be used for variables that are known to have type `T`.
+    #[must_use]
+    pub const fn new(id: i32) -> Self {
+        Self {
+            id,
+            ty: PhantomData,
+        }
+    }
+
+    /// Returns the id of the variable.
+    #[must_use]
+    pub const fn id(self) -> i32 {
+        self.id
+    }
+
+    /// Returns an expression that reads the variable.
+    #[must_use]
+    pub fn get(self) -> Expr<T> {
+        expr::var(self)
+    }
+}
+
+impl<T> Clone for Var<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Var<T> {}
+
+impl<T: Type> From<i32> for Var<T> {
+    fn from(id: i32) -> Self {
+        Self::new(id)
+    }
+}
+
+/// A reference to a parameter of type `T` of a function script.
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Param<T> {
+    id: i32,
+    ty: PhantomData<fn() -> T>,
+}
+
+impl<T: Type> Param<T> {
+    /// Creates a reference to the parameter with the given id.
+    ///
+    /// The type isn't checked, so this should only be used for parameters that are known to have type `T`.
+    #[must_use]
+    pub const fn new(id: i32) -> Self {
+        Self {
+            id,
+            ty: PhantomData,
+        }
+    }
+
+    /// Returns the id of the parameter.
+    #[must_use]
+    pub const fn id(self) -> i32 {
+        self.id
+    }
+
+    /// Returns an expression that reads the parameter.
+    #[must_use]
+    pub fn get(self) -> Expr<T> {
+        expr::param(self)
+    }
+}
+
+impl<T> Clone for Param<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Param<T> {}
+
+impl<T: Type> From<i32> for Param<T> {
+    fn from(id: i32) -> Self {
+        Self::new(id)
+    }
+}
+
+impl LevelData {
+    /// Adds a global variable to the level and returns a reference to it.
+    pub fn add_global<T: Type>(
+        &mut self,
+        name: impl Into<String>,
+        initial_value: impl Into<Expr<T>>,
+    ) -> Var<T> {
+        let id = IdAllocator::new(self).allocate();
+
+        self.global_variables.push(Variable {
+            variable_id: id,
+            name: name.into(),
+            static_type: T::STATIC_TYPE,
+            initial_value: initial_value.into().into(),
+        });
+
+        Var::new(id)
+    }
+
+    /// Starts building a new script that will be added to this level by [`ScriptBuilder::finish`].
+    #[must_use]
+    pub fn new_script(&mut self, name: impl Into<String>) -> ScriptBuilder<'_> {
+        let mut ids = IdAllocator::new(self);
+        let script_id = ids.allocate();
+
+        ScriptBuilder {
+            script: NovaScript {
+                script_id,
+                script_name: name.into(),
+                is_function: false,
+                activation_count: 0,
+                condition: expr::bool(true).into(),
+                activation_list: Vec::new(),
+                parameters: Vec::new(),
+                variables: Vec::new(),
+                actions: Vec::new(),
+            },
+            level: self,
+            ids,
+        }
+    }
+}
+
+/// A builder for a script, created by [`LevelData::new_script`].
+#[derive(Debug)]
+pub struct ScriptBuilder<'a> {
+    level: &'a mut LevelData,
+    ids: IdAllocator,
+    script: NovaScript,
+}
+
+impl ScriptBuilder<'_> {
+    /// Returns the id of the script being built.
+    #[must_use]
+    pub const fn id(&self) -> i32 {
+        self.script.script_id
+    }
+
+    /// Turns the script into a function, which is only run by [`action::run_function`].
+    pub const fn function(&mut self) -> &mut Self {
+        self.script.is_function = true;
+        self
+    }
+
+    /// Sets how many times the script can be activated. 0 means there is no limit.
+    pub const fn activation_count(&mut self, activation_count: i32) -> &mut Self {
+        self.script.activation_count = activation_count;
+        self
+    }
+
+    /// Sets the condition that has to be true for the script to run when it's activated.
+    pub fn condition(&mut self, condition: impl Into<Expr<bool>>) -> &mut Self {
+        self.script.condition = condition.into().into();
+        self
+    }
+
+    /// Adds an activator that starts this script.
+    pub fn on(&mut self, activator: Activator) -> &mut Self {
+        self.script.activation_list.push(activator);
+        self
+    }
+
+    /// Adds a local variable to the script and returns a reference to it.
+    pub fn variable<T: Type>(
+        &mut self,
+        name: impl Into<String>,
+        initial_value: impl Into<Expr<T>>,
+    ) -> Var<T> {
+        let id = self.ids.allocate();
+
+        self.script.variables.push(Variable {
+            variable_id: id,
+            name: name.into(),
+            static_type: T::STATIC_TYPE,
+            initial_value: initial_value.into().into(),
+        });
+
+        Var::new(id)
+    }
+
+    /// Adds a parameter to the script and returns a reference to it. This also turns the script into a function.
+    pub fn parameter<T: Type>(
+        &mut self,
+        name: impl Into<String>,
+        default_value: impl Into<Expr<T>>,
+    ) -> Param<T> {
+        let id = self.ids.allocate();
+
+        self.script.is_function = true;
+        self.script.parameters.push(Parameter {
+            parameter_id: id,
+            name: name.into(),
+            static_type: T::STATIC_TYPE,
+            default_value: default_value.into().into(),
+        });
+
+        Param::new(id)
+    }
+
+    /// Adds an action to the end of the script.
+    pub fn action(&mut self, action: impl Into<Action>) -> &mut Self {
+        self.script.actions.push(action.into());
+        self
+    }
+
+    /// Adds the script to the level and returns its id.
+    #[allow(clippy::must_use_candidate)]
+    pub fn finish(self) -> i32 {
+        let id = self.script.script_id;
+        self.level.nova_scripts.push(self.script);
+        id
+    }
+}
+
+/// Hands out positive ids that aren't used yet, lowest first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct IdAllocator {
+    used: HashSet<i32>,
+    next: i32,
+}
+
+impl IdAllocator {
+    /// Returns an allocator for ids that aren't used by any script, variable or parameter in a level yet.
+    pub(crate) fn new(level: &LevelData) -> Self {
+        Self::from_used(used_ids(level))
+    }
+
+    /// Returns an allocator for ids that aren't in `used`.
+    pub(crate) fn from_used(used: impl IntoIterator<Item = i32>) -> Self {
+        Self {
+            used: used.into_iter().collect(),
+            next: 1,
+        }
+    }
+
+    /// Returns the lowest positive id that isn't used and hasn't been handed out yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every positive id is used.
+    pub(crate) fn allocate(&mut self) -> i32 {
+        while self.used.contains(&self.next) {
+            self.next = self.next.checked_add(1).expect("every positive id is used");
+        }
+
+        self.used.insert(self.next);
+        self.next
+    }
+}
+
+/// Returns the ids of all scripts, global and local variables and parameters of a level, which share their ids.
+pub(crate) fn used_ids(level: &LevelData) -> impl Iterator<Item = i32> + '_ {
+    let globals = level
+        .global_variables
+        .iter()
+        .map(|variable| variable.variable_id);
+
+    globals.chain(level.nova_scripts.iter().flat_map(|script| {
+        let locals = script.variables.iter().map(|variable| variable.variable_id);
+        let parameters = script
+            .parameters
+            .iter()
+            .map(|parameter| parameter.parameter_id);
+
+        core::iter::once(script.script_id)
+            .chain(locals)
+            .chain(parameters)
+    }))
+}
+
+impl<T: Type> From<Var<T>> for NovaValue {
+    fn from(variable: Var<T>) -> Self {
+        Self::new_int(T::VARIABLE, variable.id)
+    }
+}
+
+impl<T: Type> From<Param<T>> for NovaValue {
+    fn from(parameter: Param<T>) -> Self {
+        Self::new_int(T::PARAMETER, parameter.id)
+    }
+}
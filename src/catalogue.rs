@@ -0,0 +1,238 @@
+//! A catalogue of known tile ids.
+//!
+//! Every [`Object`] has a [`tile_id`](Object::tile_id) that says what kind of object it is. The ids are opaque
+//! numbers, so this module maps the ones known to this crate to a name, a [`Category`], the properties the editor
+//! gives a new object of that kind and the properties that are valid for it.
+//!
+//! The catalogue only contains tiles whose ids have been identified, [`lookup`] returns `None` for all other ids.
+//! The ids of boosters, swings, revive pads and particle systems aren't known yet, and neither are the ids of the
+//! decorative shapes `-1718767673` and `2044847310` that the test levels use, so they aren't in the catalogue.
+
+use crate::types::{
+    color::Color,
+    object::Object,
+    object_property::{ObjectProperty, PropertyKey},
+    physics_type::PhysicsType,
+    vec2::Vec2,
+};
+use ordered_float::OrderedFloat;
+
+/// The broad kind of a tile.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    /// Terrain, shaped by its [`TerrainCorners`](ObjectProperty::TerrainCorners).
+    Terrain,
+    /// A simple shape, used for decoration or as a collider.
+    Shape,
+    /// A text box.
+    Text,
+    /// A group of other objects, see [`Object::group_members`].
+    Group,
+    /// An object with a special meaning to the game, like the start position.
+    Special,
+}
+
+/// Information about a known tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TileInfo {
+    /// The tile id, as stored in [`Object::tile_id`].
+    pub id: i32,
+    /// A human readable name.
+    pub name: &'static str,
+    /// The kind of tile.
+    pub category: Category,
+    /// The properties a new object of this kind starts with.
+    pub default_properties: &'static [ObjectProperty],
+    /// The physics properties of a solid object of this kind that doesn't kill the player.
+    ///
+    /// The editor doesn't give new objects these properties, so [`TileInfo::new_object`] doesn't add them either,
+    /// but tools that create objects can use them to make the physics of the objects explicit.
+    pub physics_defaults: &'static [ObjectProperty],
+    /// The properties that are valid for objects of this kind.
+    pub allowed_properties: &'static [PropertyKey],
+}
+
+impl TileInfo {
+    /// Returns whether a property is valid for objects of this kind.
+    #[must_use]
+    pub fn allows(&self, key: PropertyKey) -> bool {
+        self.allowed_properties.contains(&key)
+    }
+
+    /// Creates a new object of this kind with the default properties, in the given layer.
+    #[must_use]
+    pub fn new_object(&self, entity_id: i32, position: Vec2, in_layer: i32) -> Object {
+        Object {
+            entity_id,
+            tile_id: self.id,
+            prefab_entity_id: 0,
+            prefab_id: 0,
+            position,
+            scale: Vec2::new(1.0, 1.0),
+            rotation: OrderedFloat(0.0),
+            tag: String::new(),
+            properties: self.default_properties.to_vec(),
+            in_layer,
+            in_group: 0,
+            group_members: Vec::new(),
+        }
+    }
+}
+
+/// The id of the start position of the player.
+pub const START: i32 = -1_356_673_834;
+/// The id of terrain.
+pub const TERRAIN: i32 = 1_078_723_247;
+/// The id of text boxes.
+pub const TEXT: i32 = 589_791_300;
+/// The id of groups.
+pub const GROUP: i32 = 1_944_401_040;
+/// The id of square shapes.
+pub const SQUARE: i32 = 113_491_821;
+/// The id of circle shapes.
+pub const CIRCLE: i32 = -284_493_993;
+
+const WHITE: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+
+const SOLID: &[ObjectProperty] = &[
+    ObjectProperty::PhysicsType(PhysicsType::Static),
+    ObjectProperty::CollisionDetector(false),
+    ObjectProperty::Killer(false),
+];
+
+/// The color used for terrain that doesn't have a [`Color`](ObjectProperty::Color) property.
+pub const DEFAULT_TERRAIN_COLOR: Color = Color::new(0.55, 0.55, 0.6, 1.0);
+
+/// The color used for shapes that don't have a [`Color`](ObjectProperty::Color) property.
+pub const DEFAULT_SHAPE_COLOR: Color = WHITE;
+
+macro_rules! shape_properties {
+    ($($extra:ident),*) => {
+        &[
+            PropertyKey::Color,
+            PropertyKey::SecondaryColor,
+            PropertyKey::FillMode,
+            PropertyKey::Pattern,
+            PropertyKey::PatternTiling,
+            PropertyKey::PatternOffset,
+            PropertyKey::Blending,
+            PropertyKey::BorderColor,
+            PropertyKey::BorderThickness,
+            PropertyKey::PhysicsType,
+            PropertyKey::Friction,
+            PropertyKey::CollisionDetector,
+            PropertyKey::Killer,
+            PropertyKey::FlipX,
+            PropertyKey::FlipY,
+            $(PropertyKey::$extra,)*
+        ]
+    };
+}
+
+/// All tiles known to this crate.
+pub static TILES: &[TileInfo] = &[
+    TileInfo {
+        id: START,
+        name: "Start",
+        category: Category::Special,
+        default_properties: &[],
+        physics_defaults: &[],
+        allowed_properties: &[],
+    },
+    TileInfo {
+        id: TERRAIN,
+        name: "Terrain",
+        category: Category::Terrain,
+        default_properties: &[ObjectProperty::TerrainCorners(Vec::new())],
+        physics_defaults: SOLID,
+        allowed_properties: &[
+            PropertyKey::Color,
+            PropertyKey::SecondaryColor,
+            PropertyKey::FillMode,
+            PropertyKey::Pattern,
+            PropertyKey::PatternTiling,
+            PropertyKey::PatternOffset,
+            PropertyKey::Blending,
+            PropertyKey::BorderColor,
+            PropertyKey::BorderThickness,
+            PropertyKey::TerrainCorners,
+            PropertyKey::RoundReflexAngles,
+            PropertyKey::RoundCollider,
+            PropertyKey::PhysicsType,
+            PropertyKey::Friction,
+            PropertyKey::CollisionDetector,
+            PropertyKey::Killer,
+            PropertyKey::FlipX,
+            PropertyKey::FlipY,
+        ],
+    },
+    TileInfo {
+        id: TEXT,
+        name: "Text",
+        category: Category::Text,
+        default_properties: &[
+            ObjectProperty::Width(OrderedFloat(10.0)),
+            ObjectProperty::Height(OrderedFloat(3.5)),
+            ObjectProperty::FontSize(OrderedFloat(5.0)),
+        ],
+        physics_defaults: &[],
+        allowed_properties: &[
+            PropertyKey::Color,
+            PropertyKey::Width,
+            PropertyKey::Height,
+            PropertyKey::Text,
+            PropertyKey::FontSize,
+            PropertyKey::FlipX,
+            PropertyKey::FlipY,
+        ],
+    },
+    TileInfo {
+        id: GROUP,
+        name: "Group",
+        category: Category::Group,
+        default_properties: &[ObjectProperty::GridOffset(Vec2::ZERO)],
+        physics_defaults: &[],
+        allowed_properties: &[PropertyKey::GridOffset],
+    },
+    TileInfo {
+        id: SQUARE,
+        name: "Square",
+        category: Category::Shape,
+        default_properties: &[ObjectProperty::Color(WHITE)],
+        physics_defaults: SOLID,
+        allowed_properties: shape_properties!(CornerRadius),
+    },
+    TileInfo {
+        id: CIRCLE,
+        name: "Circle",
+        category: Category::Shape,
+        default_properties: &[ObjectProperty::Color(WHITE)],
+        physics_defaults: SOLID,
+        allowed_properties: shape_properties!(Resolution, TotalAngle, Thickness),
+    },
+];
+
+/// Returns the catalogue entry for a tile id, if the id is known.
+#[must_use]
+pub fn lookup(tile_id: i32) -> Option<&'static TileInfo> {
+    TILES.iter().find(|tile| tile.id == tile_id)
+}
+
+impl Object {
+    /// Returns the catalogue entry for the kind of this object, if it is known.
+    #[must_use]
+    pub fn tile(&self) -> Option<&'static TileInfo> {
+        lookup(self.tile_id)
+    }
+
+    /// Returns the properties of this object that aren't valid for its kind.
+    ///
+    /// Nothing is returned for objects whose kind isn't known.
+    pub fn invalid_properties(&self) -> impl Iterator<Item = &ObjectProperty> {
+        let tile = self.tile();
+
+        self.properties
+            .iter()
+            .filter(move |property| tile.is_some_and(|tile| !tile.allows(property.key())))
+    }
+}
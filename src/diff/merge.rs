@@ -0,0 +1,714 @@
+//! Three-way merging of levels.
+
+use super::{
+    reorder_layers, LayerChanges, MedalTimeChanges, MetadataChanges, ObjectFieldChanges,
+    ScriptFieldChanges, SettingsChanges,
+};
+use crate::{
+    builder::IdAllocator,
+    edit::{used_entity_ids, IdMap},
+    types::{
+        exolvl::Exolvl,
+        layer::Layer,
+        level_data::LevelData,
+        novascript::NovaScript,
+        object::Object,
+        object_property::{ObjectProperty, PropertyKey},
+        properties::Properties,
+        vec2::Vec2,
+    },
+};
+use core::fmt::{self, Display, Formatter};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+/// One of the two versions of a level that are merged.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Side {
+    /// The first changed version.
+    Ours,
+    /// The second changed version.
+    Theirs,
+}
+
+/// Where a merge conflict is.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Location {
+    /// The [`LocalLevel`](crate::types::local_level::LocalLevel) of the level.
+    Metadata,
+    /// The medal times of the level.
+    MedalTimes,
+    /// The settings in the [`LevelData`] of the level.
+    Settings,
+    /// The author replay of the level.
+    AuthorReplay,
+    /// The object with an entity id.
+    Object(i32),
+    /// The layer with an id.
+    Layer(i32),
+    /// The order of the layers.
+    LayerOrder,
+    /// The script with an id.
+    Script(i32),
+    /// The global variable with an id.
+    GlobalVariable(i32),
+    /// The prefab with an id.
+    Prefab(i32),
+    /// The brush with an id.
+    Brush(i32),
+    /// The pattern with an id.
+    Pattern(i32),
+}
+
+/// What both sides changed differently.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Both sides changed a field to different values.
+    Field(String),
+    /// Both sides changed, added or removed a property of an object differently.
+    Property(PropertyKey),
+    /// Both sides changed the actions of a script differently.
+    Actions,
+    /// Both sides changed it to different values. This is used for things that are compared as a whole, like global
+    /// variables and prefabs.
+    Modified,
+    /// Both sides added it with the same id, but they are different.
+    Added,
+    /// One side removed it while the other side changed it.
+    RemovedAndModified {
+        /// The side that removed it.
+        removed_by: Side,
+    },
+    /// Both sides changed the order differently.
+    Order,
+}
+
+/// Something that both sides changed differently.
+///
+/// The merged level contains the version of [`Side::Ours`], except for things that one side removed while the other
+/// side changed them, which are kept.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct MergeConflict {
+    /// Where the conflict is.
+    pub location: Location,
+    /// What both sides changed.
+    pub kind: ConflictKind,
+}
+
+/// The result of a [`three_way_merge`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Merge {
+    /// The merged level.
+    pub level: Exolvl,
+    /// The changes that couldn't be merged, in the order they were found.
+    pub conflicts: Vec<MergeConflict>,
+}
+
+/// Merges the changes that two sides made to a level.
+///
+/// Changes that only one side made are applied to the merged level. Objects, layers, scripts, global variables,
+/// prefabs, brushes and patterns are matched by their id and merged separately, and the fields and properties of
+/// objects are merged one by one. Objects that either side added to a layer or group are added to it, and objects
+/// that either side removed are removed from it. If both sides added a different object with the same entity id, their
+/// object gets a new id. The actions of a script are merged as a whole.
+///
+/// ```
+/// use exolvl::diff::three_way_merge;
+/// use exolvl::types::exolvl::Exolvl;
+///
+/// let base = Exolvl::default();
+///
+/// let mut ours = base.clone();
+/// ours.local_level.level_name = String::from("Canyon");
+///
+/// let mut theirs = base.clone();
+/// theirs.level_data.gold_medal_time = 30_000;
+///
+/// let merge = three_way_merge(&base, &ours, &theirs);
+///
+/// assert!(merge.conflicts.is_empty());
+/// assert_eq!(merge.level.local_level.level_name, "Canyon");
+/// assert_eq!(merge.level.level_data.gold_medal_time, 30_000);
+/// ```
+#[must_use]
+pub fn three_way_merge(base: &Exolvl, ours: &Exolvl, theirs: &Exolvl) -> Merge {
+    let mut conflicts = Vec::new();
+    let mut level = ours.clone();
+
+    let mut fields = |location, names: Vec<&str>| {
+        conflicts.extend(names.into_iter().map(|name| MergeConflict {
+            location,
+            kind: ConflictKind::Field(name.to_owned()),
+        }));
+    };
+
+    fields(
+        Location::Metadata,
+        MetadataChanges::merge(
+            &base.local_level,
+            &ours.local_level,
+            &theirs.local_level,
+            &mut level.local_level,
+        ),
+    );
+    fields(
+        Location::MedalTimes,
+        MedalTimeChanges::merge(
+            &base.level_data,
+            &ours.level_data,
+            &theirs.level_data,
+            &mut level.level_data,
+        ),
+    );
+    fields(
+        Location::Settings,
+        SettingsChanges::merge(
+            &base.level_data,
+            &ours.level_data,
+            &theirs.level_data,
+            &mut level.level_data,
+        ),
+    );
+
+    // the local level has a copy of the medal times, whose conflicts are already reported for the level data
+    let (local, base_local, our_local, their_local) = (
+        &mut level.local_level,
+        &base.local_level,
+        &ours.local_level,
+        &theirs.local_level,
+    );
+
+    macro_rules! merge_copies {
+        ($($field:ident),*) => {
+            $(
+                if let Some(value) = merge3(&base_local.$field, &our_local.$field, &their_local.$field) {
+                    local.$field.clone_from(value);
+                }
+            )*
+        };
+    }
+
+    merge_copies!(
+        author_time,
+        author_lap_times,
+        silver_medal_time,
+        gold_medal_time,
+        laps
+    );
+
+    match merge3(
+        &base.author_replay,
+        &ours.author_replay,
+        &theirs.author_replay,
+    ) {
+        Some(replay) => level.author_replay.clone_from(replay),
+        None => conflicts.push(MergeConflict {
+            location: Location::AuthorReplay,
+            kind: ConflictKind::Modified,
+        }),
+    }
+
+    merge_level_data(
+        &base.level_data,
+        &ours.level_data,
+        &theirs.level_data,
+        &mut level.level_data,
+        &mut conflicts,
+    );
+
+    Merge { level, conflicts }
+}
+
+fn merge_level_data(
+    base: &LevelData,
+    ours: &LevelData,
+    theirs: &LevelData,
+    merged: &mut LevelData,
+    conflicts: &mut Vec<MergeConflict>,
+) {
+    let renumbered = renumber_added_objects(base, ours, theirs);
+    let theirs = renumbered.as_ref().unwrap_or(theirs);
+
+    merged.objects = merge_by_id(
+        &base.objects,
+        &ours.objects,
+        &theirs.objects,
+        |object| object.entity_id,
+        merge_object,
+        Location::Object,
+        conflicts,
+    );
+    merged.layers = merge_by_id(
+        &base.layers,
+        &ours.layers,
+        &theirs.layers,
+        |layer| layer.layer_id,
+        merge_layer,
+        Location::Layer,
+        conflicts,
+    );
+    merged.nova_scripts = merge_by_id(
+        &base.nova_scripts,
+        &ours.nova_scripts,
+        &theirs.nova_scripts,
+        |script| script.script_id,
+        merge_script,
+        Location::Script,
+        conflicts,
+    );
+    merged.global_variables = merge_by_id(
+        &base.global_variables,
+        &ours.global_variables,
+        &theirs.global_variables,
+        |variable| variable.variable_id,
+        keep_ours,
+        Location::GlobalVariable,
+        conflicts,
+    );
+    merged.prefabs = merge_by_id(
+        &base.prefabs,
+        &ours.prefabs,
+        &theirs.prefabs,
+        |prefab| prefab.prefab_id,
+        keep_ours,
+        Location::Prefab,
+        conflicts,
+    );
+    merged.brushes = merge_by_id(
+        &base.brushes,
+        &ours.brushes,
+        &theirs.brushes,
+        |brush| brush.brush_id,
+        keep_ours,
+        Location::Brush,
+        conflicts,
+    );
+    merged.patterns = merge_by_id(
+        &base.patterns,
+        &ours.patterns,
+        &theirs.patterns,
+        |pattern| pattern.pattern_id,
+        keep_ours,
+        Location::Pattern,
+        conflicts,
+    );
+
+    if !merge_layer_order(base, ours, theirs, &mut merged.layers) {
+        conflicts.push(MergeConflict {
+            location: Location::LayerOrder,
+            kind: ConflictKind::Order,
+        });
+    }
+
+    repair_hierarchy(merged);
+}
+
+/// Gives the objects that they added with the same entity id as different objects that we added a new id in a copy of
+/// their level, so both objects are kept. Returns `None` if no objects need a new id.
+fn renumber_added_objects(
+    base: &LevelData,
+    ours: &LevelData,
+    theirs: &LevelData,
+) -> Option<LevelData> {
+    let clashing = theirs
+        .objects
+        .iter()
+        .filter(|object| {
+            base.object(object.entity_id).is_none()
+                && ours
+                    .object(object.entity_id)
+                    .is_some_and(|our_object| our_object != *object)
+        })
+        .map(|object| object.entity_id)
+        .collect::<Vec<_>>();
+
+    if clashing.is_empty() {
+        return None;
+    }
+
+    let mut ids =
+        IdAllocator::from_used([base, ours, theirs].into_iter().flat_map(used_entity_ids));
+
+    let map = IdMap {
+        entities: clashing
+            .into_iter()
+            .map(|id| (id, ids.allocate()))
+            .collect(),
+        ..IdMap::default()
+    };
+
+    let mut renumbered = theirs.clone();
+    map.apply(&mut renumbered, Vec2::default());
+
+    Some(renumbered)
+}
+
+/// Returns the value that changed, or `None` if both sides changed it differently.
+pub(super) fn merge3<'a, T: PartialEq + ?Sized>(
+    base: &'a T,
+    ours: &'a T,
+    theirs: &'a T,
+) -> Option<&'a T> {
+    if ours == theirs || theirs == base {
+        Some(ours)
+    } else if ours == base {
+        Some(theirs)
+    } else {
+        None
+    }
+}
+
+/// Merges lists of things with an id, in the order of our list followed by the things that only they added.
+///
+/// Things that both sides changed differently are merged with `merge_item`, which returns the merged version and
+/// the conflicts. Conflicts are added to `conflicts` at the location of the id.
+fn merge_by_id<T: Clone + PartialEq>(
+    base: &[T],
+    ours: &[T],
+    theirs: &[T],
+    key: impl Fn(&T) -> i32,
+    merge_item: impl Fn(&T, &T, &T) -> (T, Vec<ConflictKind>),
+    location: fn(i32) -> Location,
+    conflicts: &mut Vec<MergeConflict>,
+) -> Vec<T> {
+    let mut conflict = |id, kind| {
+        conflicts.push(MergeConflict {
+            location: location(id),
+            kind,
+        });
+    };
+
+    let base_items = base
+        .iter()
+        .map(|item| (key(item), item))
+        .collect::<BTreeMap<_, _>>();
+    let their_items = theirs
+        .iter()
+        .map(|item| (key(item), item))
+        .collect::<BTreeMap<_, _>>();
+    let our_keys = ours.iter().map(&key).collect::<HashSet<_>>();
+
+    let mut merged = Vec::with_capacity(ours.len());
+
+    for item in ours {
+        let id = key(item);
+
+        match (base_items.get(&id), their_items.get(&id)) {
+            (None, None) => merged.push(item.clone()),
+            (None, Some(&their_item)) => {
+                if item != their_item {
+                    conflict(id, ConflictKind::Added);
+                }
+
+                merged.push(item.clone());
+            }
+            (Some(&base_item), None) => {
+                if item != base_item {
+                    conflict(
+                        id,
+                        ConflictKind::RemovedAndModified {
+                            removed_by: Side::Theirs,
+                        },
+                    );
+                    merged.push(item.clone());
+                }
+            }
+            (Some(&base_item), Some(&their_item)) => {
+                if let Some(item) = merge3(base_item, item, their_item) {
+                    merged.push(item.clone());
+                } else {
+                    let (item, conflicts) = merge_item(base_item, item, their_item);
+
+                    for kind in conflicts {
+                        conflict(id, kind);
+                    }
+
+                    merged.push(item);
+                }
+            }
+        }
+    }
+
+    for item in theirs {
+        let id = key(item);
+
+        if our_keys.contains(&id) {
+            continue;
+        }
+
+        match base_items.get(&id) {
+            None => merged.push(item.clone()),
+            Some(&base_item) => {
+                if item != base_item {
+                    conflict(
+                        id,
+                        ConflictKind::RemovedAndModified {
+                            removed_by: Side::Ours,
+                        },
+                    );
+                    merged.push(item.clone());
+                }
+            }
+        }
+    }
+
+    merged
+}
+
+fn keep_ours<T: Clone>(_: &T, ours: &T, _: &T) -> (T, Vec<ConflictKind>) {
+    (ours.clone(), vec![ConflictKind::Modified])
+}
+
+fn field_conflicts(names: Vec<&str>, id_lists: &[&str]) -> Vec<ConflictKind> {
+    names
+        .into_iter()
+        .filter(|name| !id_lists.contains(name))
+        .map(|name| ConflictKind::Field(name.to_owned()))
+        .collect()
+}
+
+fn merge_object(base: &Object, ours: &Object, theirs: &Object) -> (Object, Vec<ConflictKind>) {
+    let mut merged = ours.clone();
+
+    let mut conflicts = field_conflicts(
+        ObjectFieldChanges::merge(base, ours, theirs, &mut merged),
+        &["group_members"],
+    );
+    merged.group_members = merge_ids(
+        &base.group_members,
+        &ours.group_members,
+        &theirs.group_members,
+    );
+
+    let keys = [base, ours, theirs]
+        .into_iter()
+        .flat_map(|object| object.properties.iter().map(ObjectProperty::key))
+        .collect::<BTreeSet<_>>();
+
+    for key in keys {
+        match merge3(
+            &base.property(key),
+            &ours.property(key),
+            &theirs.property(key),
+        ) {
+            Some(Some(property)) => {
+                merged.set_property((*property).clone());
+            }
+            Some(None) => {
+                merged.remove_property(key);
+            }
+            None => conflicts.push(ConflictKind::Property(key)),
+        }
+    }
+
+    (merged, conflicts)
+}
+
+fn merge_layer(base: &Layer, ours: &Layer, theirs: &Layer) -> (Layer, Vec<ConflictKind>) {
+    let mut merged = ours.clone();
+
+    let conflicts = field_conflicts(
+        LayerChanges::merge(base, ours, theirs, &mut merged),
+        &["children"],
+    );
+    merged.children = merge_ids(&base.children, &ours.children, &theirs.children);
+
+    (merged, conflicts)
+}
+
+fn merge_script(
+    base: &NovaScript,
+    ours: &NovaScript,
+    theirs: &NovaScript,
+) -> (NovaScript, Vec<ConflictKind>) {
+    let mut merged = ours.clone();
+
+    let mut conflicts = field_conflicts(
+        ScriptFieldChanges::merge(base, ours, theirs, &mut merged),
+        &[],
+    );
+
+    match merge3(&base.actions, &ours.actions, &theirs.actions) {
+        Some(actions) => merged.actions.clone_from(actions),
+        None => conflicts.push(ConflictKind::Actions),
+    }
+
+    (merged, conflicts)
+}
+
+/// Merges two lists of entity ids that both sides changed.
+///
+/// Ids that either side removed are removed, and ids that they added are inserted after the id that is before them
+/// in their list. If both sides changed the order, our order is kept.
+fn merge_ids(base: &[i32], ours: &[i32], theirs: &[i32]) -> Vec<i32> {
+    if let Some(merged) = merge3(base, ours, theirs) {
+        return merged.to_vec();
+    }
+
+    let mut merged = ours
+        .iter()
+        .copied()
+        .filter(|id| !base.contains(id) || theirs.contains(id))
+        .collect::<Vec<_>>();
+
+    for (index, id) in theirs.iter().enumerate() {
+        if base.contains(id) || merged.contains(id) {
+            continue;
+        }
+
+        let position = theirs[..index]
+            .iter()
+            .rev()
+            .find_map(|before| merged.iter().position(|id| id == before))
+            .map_or(0, |position| position + 1);
+
+        merged.insert(position, *id);
+    }
+
+    merged
+}
+
+/// Reorders the merged layers if only they changed the order, and returns `false` if both sides changed it
+/// differently.
+fn merge_layer_order(
+    base: &LevelData,
+    ours: &LevelData,
+    theirs: &LevelData,
+    merged: &mut [Layer],
+) -> bool {
+    let in_all = |layers: &[Layer]| {
+        layers
+            .iter()
+            .map(|layer| layer.layer_id)
+            .filter(|id| {
+                [base, ours, theirs]
+                    .iter()
+                    .all(|level| level.layers.iter().any(|layer| layer.layer_id == *id))
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let (base_order, our_order, their_order) = (
+        in_all(&base.layers),
+        in_all(&ours.layers),
+        in_all(&theirs.layers),
+    );
+
+    let Some(order) = merge3(&base_order, &our_order, &their_order) else {
+        return false;
+    };
+
+    reorder_layers(merged, order);
+
+    true
+}
+
+/// Fixes the layers and groups after merging objects that one side removed.
+///
+/// References to objects that don't exist are removed, and objects that aren't in their layer or group are added to
+/// the end of it.
+fn repair_hierarchy(level: &mut LevelData) {
+    let ids = level
+        .objects
+        .iter()
+        .map(|object| object.entity_id)
+        .collect::<HashSet<_>>();
+
+    let missing = level
+        .layers
+        .iter()
+        .flat_map(|layer| layer.children.iter().copied())
+        .chain(level.objects.iter().flat_map(|object| {
+            object
+                .group_members
+                .iter()
+                .copied()
+                .chain((object.in_group != 0).then_some(object.in_group))
+        }))
+        .filter(|id| !ids.contains(id))
+        .collect::<HashSet<_>>();
+
+    if !missing.is_empty() {
+        level.remove_references(&missing);
+    }
+
+    let orphans = level
+        .objects
+        .iter()
+        .map(|object| (object.entity_id, object.in_group, object.in_layer))
+        .collect::<Vec<_>>();
+
+    for (id, in_group, in_layer) in orphans {
+        let siblings = if in_group == 0 {
+            level
+                .layers
+                .iter_mut()
+                .find(|layer| layer.layer_id == in_layer)
+                .map(|layer| &mut layer.children)
+        } else {
+            level
+                .object_mut(in_group)
+                .map(|group| &mut group.group_members)
+        };
+
+        if let Some(siblings) = siblings {
+            if !siblings.contains(&id) {
+                siblings.push(id);
+            }
+        }
+    }
+}
+
+impl Display for Side {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ours => f.write_str("ours"),
+            Self::Theirs => f.write_str("theirs"),
+        }
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Metadata => f.write_str("metadata"),
+            Self::MedalTimes => f.write_str("medal times"),
+            Self::Settings => f.write_str("settings"),
+            Self::AuthorReplay => f.write_str("author replay"),
+            Self::Object(id) => write!(f, "object {id}"),
+            Self::Layer(id) => write!(f, "layer {id}"),
+            Self::LayerOrder => f.write_str("layer order"),
+            Self::Script(id) => write!(f, "script {id}"),
+            Self::GlobalVariable(id) => write!(f, "variable {id}"),
+            Self::Prefab(id) => write!(f, "prefab {id}"),
+            Self::Brush(id) => write!(f, "brush {id}"),
+            Self::Pattern(id) => write!(f, "pattern {id}"),
+        }
+    }
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let location = self.location;
+
+        match &self.kind {
+            ConflictKind::Field(name) => write!(f, "{location}: both sides changed {name}"),
+            ConflictKind::Property(key) => {
+                write!(f, "{location}: both sides changed property {key:?}")
+            }
+            ConflictKind::Actions => write!(f, "{location}: both sides changed the actions"),
+            ConflictKind::Modified => write!(f, "{location}: both sides changed it"),
+            ConflictKind::Added => write!(f, "{location}: both sides added it differently"),
+            ConflictKind::RemovedAndModified { removed_by } => {
+                write!(
+                    f,
+                    "{location}: removed by {removed_by} but changed by the other side"
+                )
+            }
+            ConflictKind::Order => write!(f, "{location}: both sides changed the order"),
+        }
+    }
+}
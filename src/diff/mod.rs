@@ -0,0 +1,897 @@
+//! Structural differences between two versions of a level.
+//!
+//! A [`LevelDiff`] lists what changed between two [`Exolvl`]s: objects are matched by their entity id, layers,
+//! scripts and the other parts of a level by their id, and the actions of scripts are compared one by one, down into
+//! nested actions. Changed objects list the fields and [properties](ObjectProperty) that changed, instead of the whole
+//! object.
+//!
+//! The [`Display`](core::fmt::Display) implementation prints a summary for people, and with the `serde` feature a
+//! diff can be serialized for tools.
+//!
+//! [`three_way_merge`] combines the changes that two sides made to the same level, and lists the changes that
+//! conflict. A diff can also be turned into a [`Patch`], which applies the same changes to another copy of the
+//! level.
+//!
+//! ```
+//! use exolvl::diff::LevelDiff;
+//! use exolvl::types::{exolvl::Exolvl, vec2::Vec2};
+//!
+//! let old = Exolvl::default();
+//! let mut new = old.clone();
+//! new.local_level.level_name = String::from("Canyon");
+//! new.level_data.gravity = Vec2::new(0.0, -50.0);
+//!
+//! let diff = LevelDiff::new(&old, &new);
+//!
+//! assert_eq!(diff.metadata.level_name.unwrap().new, "Canyon");
+//! assert!(diff.settings.gravity.is_some());
+//! assert!(diff.objects.is_empty());
+//! ```
+
+use crate::types::{
+    blending::Blending,
+    brush::Brush,
+    color::Color,
+    exolvl::Exolvl,
+    layer::Layer,
+    level_data::LevelData,
+    local_level::LocalLevel,
+    novascript::{action::Action, variable::Variable, NovaScript},
+    object::Object,
+    object_property::{ObjectProperty, PropertyKey},
+    pattern::Pattern,
+    prefab::Prefab,
+    theme::Theme,
+    vec2::Vec2,
+};
+use core::fmt::{self, Debug, Display, Formatter};
+use ordered_float::OrderedFloat;
+use std::collections::BTreeMap;
+
+mod merge;
+mod patch;
+
+pub use merge::{three_way_merge, ConflictKind, Location, Merge, MergeConflict, Side};
+pub use patch::{Item, Operation, Patch};
+
+/// The old and new value of something that changed.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Change<T> {
+    /// The value in the old level.
+    pub old: T,
+    /// The value in the new level.
+    pub new: T,
+}
+
+impl<T: Clone + PartialEq> Change<T> {
+    /// Returns the change between two values, or `None` if they are equal.
+    fn of(old: &T, new: &T) -> Option<Self> {
+        (old != new).then(|| Self {
+            old: old.clone(),
+            new: new.clone(),
+        })
+    }
+}
+
+impl<T: Debug> Display for Change<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (old, new) = (format!("{:?}", self.old), format!("{:?}", self.new));
+
+        // skip most of the start that is the same, so the part that changed is visible
+        let same = old
+            .chars()
+            .zip(new.chars())
+            .take_while(|(old, new)| old == new)
+            .count();
+        let start = old
+            .char_indices()
+            .nth(same.saturating_sub(CONTEXT))
+            .map_or(0, |(index, _)| index);
+
+        write_excerpt(f, &old, start)?;
+        f.write_str(" -> ")?;
+        write_excerpt(f, &new, start)
+    }
+}
+
+/// Something with an id that was added, removed or changed.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum Entry<T, C> {
+    /// It's only in the new level.
+    Added(T),
+    /// It's only in the old level.
+    Removed(T),
+    /// It's in both levels, but it changed.
+    Modified(C),
+}
+
+macro_rules! changes {
+    (
+        $(#[$meta:meta])*
+        $name:ident for $ty:ty {
+            $($(#[$field_meta:meta])* $field:ident: $field_ty:ty,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: Option<Change<$field_ty>>,)*
+        }
+
+        impl $name {
+            fn new(old: &$ty, new: &$ty) -> Self {
+                Self {
+                    $($field: Change::of(&old.$field, &new.$field),)*
+                }
+            }
+
+            /// Returns whether nothing changed.
+            #[must_use]
+            pub const fn is_empty(&self) -> bool {
+                true $(&& self.$field.is_none())*
+            }
+
+            /// Merges the fields that only one side changed into `merged`, and returns the names of the fields that
+            /// both sides changed differently.
+            fn merge(base: &$ty, ours: &$ty, theirs: &$ty, merged: &mut $ty) -> Vec<&'static str> {
+                let mut conflicts = Vec::new();
+
+                $(
+                    match merge::merge3(&base.$field, &ours.$field, &theirs.$field) {
+                        Some(value) => merged.$field.clone_from(value),
+                        None => conflicts.push(stringify!($field)),
+                    }
+                )*
+
+                conflicts
+            }
+
+            /// Returns the names of the changed fields whose value in `target` isn't the old value.
+            fn mismatches(&self, target: &$ty) -> Vec<&'static str> {
+                let mut mismatches = Vec::new();
+
+                $(
+                    if self.$field.as_ref().is_some_and(|change| change.old != target.$field) {
+                        mismatches.push(stringify!($field));
+                    }
+                )*
+
+                mismatches
+            }
+
+            /// Sets the changed fields of `target` to their new value.
+            fn apply(&self, target: &mut $ty) {
+                $(
+                    if let Some(change) = &self.$field {
+                        target.$field.clone_from(&change.new);
+                    }
+                )*
+            }
+
+            fn fmt_fields(&self, f: &mut Formatter<'_>, indent: &str) -> fmt::Result {
+                $(
+                    if let Some(change) = &self.$field {
+                        writeln!(f, "{indent}{}: {change}", stringify!($field))?;
+                    }
+                )*
+
+                Ok(())
+            }
+        }
+    };
+}
+
+changes! {
+    /// Changes to the [`LocalLevel`] of a level, except for its medal times.
+    MetadataChanges for LocalLevel {
+        serialization_version: i32,
+        level_id: uuid::Uuid,
+        level_version: i32,
+        level_name: String,
+        thumbnail: String,
+        creation_date: chrono::DateTime<chrono::Utc>,
+        update_date: chrono::DateTime<chrono::Utc>,
+        private: bool,
+        nova_level: bool,
+    }
+}
+
+changes! {
+    /// Changes to the medal times and laps of a level, as stored in its [`LevelData`].
+    MedalTimeChanges for LevelData {
+        author_time: i64,
+        author_lap_times: Vec<i64>,
+        silver_medal_time: i64,
+        gold_medal_time: i64,
+        laps: i32,
+    }
+}
+
+changes! {
+    /// Changes to the settings in the [`LevelData`] of a level.
+    SettingsChanges for LevelData {
+        level_id: uuid::Uuid,
+        level_version: i32,
+        nova_level: bool,
+        under_decoration_tiles: Vec<i32>,
+        background_decoration_tiles: Vec<i32>,
+        terrain_tiles: Vec<i32>,
+        floating_zone_tiles: Vec<i32>,
+        object_tiles: Vec<i32>,
+        foreground_decoration_tiles: Vec<i32>,
+        color_palette: Option<Vec<Color>>,
+        center_camera: bool,
+        scripts: Vec<i32>,
+        theme: Theme,
+        custom_background_color: Color,
+        custom_terrain_pattern_id: i32,
+        custom_terrain_pattern_tiling: Vec2,
+        custom_terrain_pattern_offset: Vec2,
+        custom_terrain_color: Color,
+        custom_terrain_secondary_color: Color,
+        custom_terrain_blend_mode: Blending,
+        custom_terrain_border_color: Color,
+        custom_terrain_border_thickness: OrderedFloat<f32>,
+        custom_terrain_border_corner_radius: OrderedFloat<f32>,
+        custom_terrain_round_reflex_angles: bool,
+        custom_terrain_round_collider: bool,
+        custom_terrain_friction: OrderedFloat<f32>,
+        default_music: bool,
+        music_ids: Vec<String>,
+        allow_direction_change: bool,
+        disable_replays: bool,
+        disable_revive_pads: bool,
+        disable_start_animation: bool,
+        gravity: Vec2,
+    }
+}
+
+changes! {
+    /// Changes to the fields of an [`Object`], except for its properties.
+    ObjectFieldChanges for Object {
+        tile_id: i32,
+        prefab_entity_id: i32,
+        prefab_id: i32,
+        position: Vec2,
+        scale: Vec2,
+        rotation: OrderedFloat<f32>,
+        tag: String,
+        in_layer: i32,
+        in_group: i32,
+        group_members: Vec<i32>,
+    }
+}
+
+changes! {
+    /// Changes to a [`Layer`]. A change to the order of its children is a change to
+    /// [`children`](Layer::children).
+    LayerChanges for Layer {
+        layer_name: String,
+        selected: bool,
+        invisible: bool,
+        locked: bool,
+        foreground_type: crate::types::foreground_type::ForegroundType,
+        parallax: Vec2,
+        fixed_size: bool,
+        children: Vec<i32>,
+    }
+}
+
+changes! {
+    /// Changes to the fields of a [`NovaScript`], except for its actions.
+    ScriptFieldChanges for NovaScript {
+        script_name: String,
+        is_function: bool,
+        activation_count: i32,
+        condition: crate::types::novascript::nova_value::NovaValue,
+        activation_list: Vec<crate::types::novascript::activator::Activator>,
+        parameters: Vec<crate::types::novascript::parameter::Parameter>,
+        variables: Vec<Variable>,
+    }
+}
+
+/// Changes to an [`Object`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ObjectChanges {
+    /// The fields that changed.
+    pub fields: ObjectFieldChanges,
+    /// The properties that were added, removed or changed, by their key.
+    ///
+    /// Objects have at most one property of each kind, so properties are matched by their key.
+    pub properties: BTreeMap<PropertyKey, Entry<ObjectProperty, Change<ObjectProperty>>>,
+}
+
+impl ObjectChanges {
+    fn new(old: &Object, new: &Object) -> Self {
+        Self {
+            fields: ObjectFieldChanges::new(old, new),
+            properties: entries(&old.properties, &new.properties, ObjectProperty::key),
+        }
+    }
+
+    /// Returns whether nothing changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.properties.is_empty()
+    }
+}
+
+/// Changes to a [`NovaScript`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct ScriptChanges {
+    /// The fields that changed.
+    pub fields: ScriptFieldChanges,
+    /// The changes to the actions, in the order they appear in the script.
+    pub actions: Vec<ActionChange>,
+}
+
+impl ScriptChanges {
+    fn new(old: &NovaScript, new: &NovaScript) -> Self {
+        Self {
+            fields: ScriptFieldChanges::new(old, new),
+            actions: action_changes(&old.actions, &new.actions),
+        }
+    }
+
+    /// Returns whether nothing changed.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.actions.is_empty()
+    }
+}
+
+/// A change to a list of actions of a script.
+///
+/// When an action that contains other actions, like `If`, only changed inside its nested actions, the changes to the
+/// nested actions are listed in a [`Nested`](Self::Nested) change instead of replacing the whole action. The indices
+/// of a change are indices in the list of actions that contains it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum ActionChange {
+    /// An action was inserted at an index of the new actions.
+    Inserted { index: usize, action: Action },
+    /// The action at an index of the old actions was removed.
+    Removed { index: usize, action: Action },
+    /// An action was replaced with a different action of the same type.
+    Modified {
+        old_index: usize,
+        new_index: usize,
+        change: Change<Action>,
+    },
+    /// The nested actions of an action changed, and nothing else about it.
+    Nested {
+        old_index: usize,
+        new_index: usize,
+        /// The index of the list of nested actions, in the order of
+        /// [`ActionType::actions`](crate::types::action_type::ActionType::actions).
+        list: usize,
+        changes: Vec<Self>,
+    },
+}
+
+/// The differences between two versions of a level.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct LevelDiff {
+    /// Changes to the metadata of the level.
+    pub metadata: MetadataChanges,
+    /// Changes to the medal times of the level.
+    pub medal_times: MedalTimeChanges,
+    /// Changes to the settings of the level.
+    pub settings: SettingsChanges,
+    /// The objects that changed, by their entity id.
+    pub objects: BTreeMap<i32, Entry<Object, ObjectChanges>>,
+    /// The layers that changed, by their id.
+    pub layers: BTreeMap<i32, Entry<Layer, LayerChanges>>,
+    /// The order of the layers that are in both levels, if it changed.
+    pub layer_order: Option<Change<Vec<i32>>>,
+    /// The scripts that changed, by their id.
+    pub scripts: BTreeMap<i32, Entry<NovaScript, ScriptChanges>>,
+    /// The global variables that changed, by their id.
+    pub global_variables: BTreeMap<i32, Entry<Variable, Change<Variable>>>,
+    /// The prefabs that changed, by their id.
+    pub prefabs: BTreeMap<i32, Entry<Prefab, Change<Prefab>>>,
+    /// The brushes that changed, by their id.
+    pub brushes: BTreeMap<i32, Entry<Brush, Change<Brush>>>,
+    /// The patterns that changed, by their id.
+    pub patterns: BTreeMap<i32, Entry<Pattern, Change<Pattern>>>,
+}
+
+impl LevelDiff {
+    /// Compares two versions of a level.
+    ///
+    /// The author replay isn't compared.
+    #[must_use]
+    pub fn new(old: &Exolvl, new: &Exolvl) -> Self {
+        let (old_data, new_data) = (&old.level_data, &new.level_data);
+
+        let layer_id = |layer: &Layer| layer.layer_id;
+
+        Self {
+            metadata: MetadataChanges::new(&old.local_level, &new.local_level),
+            medal_times: MedalTimeChanges::new(old_data, new_data),
+            settings: SettingsChanges::new(old_data, new_data),
+            objects: changed(
+                &old_data.objects,
+                &new_data.objects,
+                |object| object.entity_id,
+                ObjectChanges::new,
+                ObjectChanges::is_empty,
+            ),
+            layers: changed(
+                &old_data.layers,
+                &new_data.layers,
+                layer_id,
+                LayerChanges::new,
+                LayerChanges::is_empty,
+            ),
+            layer_order: layer_order(&old_data.layers, &new_data.layers),
+            scripts: changed(
+                &old_data.nova_scripts,
+                &new_data.nova_scripts,
+                |script| script.script_id,
+                ScriptChanges::new,
+                ScriptChanges::is_empty,
+            ),
+            global_variables: entries(
+                &old_data.global_variables,
+                &new_data.global_variables,
+                |variable| variable.variable_id,
+            ),
+            prefabs: entries(&old_data.prefabs, &new_data.prefabs, |prefab| {
+                prefab.prefab_id
+            }),
+            brushes: entries(&old_data.brushes, &new_data.brushes, |brush| brush.brush_id),
+            patterns: entries(&old_data.patterns, &new_data.patterns, |pattern| {
+                pattern.pattern_id
+            }),
+        }
+    }
+
+    /// Returns whether the levels are the same.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty()
+            && self.medal_times.is_empty()
+            && self.settings.is_empty()
+            && self.objects.is_empty()
+            && self.layers.is_empty()
+            && self.layer_order.is_none()
+            && self.scripts.is_empty()
+            && self.global_variables.is_empty()
+            && self.prefabs.is_empty()
+            && self.brushes.is_empty()
+            && self.patterns.is_empty()
+    }
+}
+
+impl Display for LevelDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const INDENT: &str = "    ";
+
+        for (title, fields) in [
+            ("metadata", &self.metadata as &dyn FmtFields),
+            ("medal times", &self.medal_times),
+            ("settings", &self.settings),
+        ] {
+            if !fields.is_empty() {
+                writeln!(f, "~ {title}")?;
+                fields.fmt_fields(f, INDENT)?;
+            }
+        }
+
+        if let Some(order) = &self.layer_order {
+            writeln!(f, "~ layer order: {order}")?;
+        }
+
+        for (id, entry) in &self.layers {
+            fmt_entry(f, "layer", *id, entry, |f, changes| {
+                changes.fmt_fields(f, INDENT)
+            })?;
+        }
+
+        for (id, entry) in &self.objects {
+            fmt_entry(f, "object", *id, entry, |f, changes| {
+                changes.fields.fmt_fields(f, INDENT)?;
+                fmt_properties(f, &changes.properties, INDENT)
+            })?;
+        }
+
+        for (id, entry) in &self.scripts {
+            fmt_entry(f, "script", *id, entry, |f, changes| {
+                changes.fields.fmt_fields(f, INDENT)?;
+                fmt_actions(f, &changes.actions, INDENT)
+            })?;
+        }
+
+        for (id, entry) in &self.global_variables {
+            fmt_entry(f, "variable", *id, entry, |f, change| {
+                writeln!(f, "{INDENT}{change}")
+            })?;
+        }
+
+        for (id, entry) in &self.prefabs {
+            fmt_entry(f, "prefab", *id, entry, |_, _| Ok(()))?;
+        }
+
+        for (id, entry) in &self.brushes {
+            fmt_entry(f, "brush", *id, entry, |_, _| Ok(()))?;
+        }
+
+        for (id, entry) in &self.patterns {
+            fmt_entry(f, "pattern", *id, entry, |_, _| Ok(()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The generated field change structs, so they can be printed in a loop.
+trait FmtFields {
+    fn is_empty(&self) -> bool;
+    fn fmt_fields(&self, f: &mut Formatter<'_>, indent: &str) -> fmt::Result;
+}
+
+macro_rules! impl_fmt_fields {
+    ($($name:ident),*) => {
+        $(
+            impl FmtFields for $name {
+                fn is_empty(&self) -> bool {
+                    Self::is_empty(self)
+                }
+
+                fn fmt_fields(&self, f: &mut Formatter<'_>, indent: &str) -> fmt::Result {
+                    Self::fmt_fields(self, f, indent)
+                }
+            }
+        )*
+    };
+}
+
+impl_fmt_fields!(MetadataChanges, MedalTimeChanges, SettingsChanges);
+
+fn fmt_entry<T, C>(
+    f: &mut Formatter<'_>,
+    kind: &str,
+    id: i32,
+    entry: &Entry<T, C>,
+    fmt_changes: impl FnOnce(&mut Formatter<'_>, &C) -> fmt::Result,
+) -> fmt::Result {
+    match entry {
+        Entry::Added(_) => writeln!(f, "+ {kind} {id}"),
+        Entry::Removed(_) => writeln!(f, "- {kind} {id}"),
+        Entry::Modified(changes) => {
+            writeln!(f, "~ {kind} {id}")?;
+            fmt_changes(f, changes)
+        }
+    }
+}
+
+fn fmt_properties(
+    f: &mut Formatter<'_>,
+    properties: &BTreeMap<PropertyKey, Entry<ObjectProperty, Change<ObjectProperty>>>,
+    indent: &str,
+) -> fmt::Result {
+    for (key, entry) in properties {
+        match entry {
+            Entry::Added(property) => writeln!(f, "{indent}+ {key:?}: {}", Short(property))?,
+            Entry::Removed(property) => writeln!(f, "{indent}- {key:?}: {}", Short(property))?,
+            Entry::Modified(change) => writeln!(f, "{indent}~ {key:?}: {change}")?,
+        }
+    }
+
+    Ok(())
+}
+
+fn fmt_actions(f: &mut Formatter<'_>, actions: &[ActionChange], indent: &str) -> fmt::Result {
+    const INDENT: &str = "    ";
+
+    for change in actions {
+        match change {
+            ActionChange::Inserted { index, action } => {
+                writeln!(
+                    f,
+                    "{indent}+ action {index}: {}",
+                    Short(&action.action_type)
+                )?;
+            }
+            ActionChange::Removed { index, action } => {
+                writeln!(
+                    f,
+                    "{indent}- action {index}: {}",
+                    Short(&action.action_type)
+                )?;
+            }
+            ActionChange::Modified {
+                old_index,
+                new_index,
+                change,
+            } => {
+                if old_index == new_index {
+                    writeln!(f, "{indent}~ action {new_index}: {change}")?;
+                } else {
+                    writeln!(f, "{indent}~ action {old_index} -> {new_index}: {change}")?;
+                }
+            }
+            ActionChange::Nested {
+                old_index,
+                new_index,
+                list,
+                changes,
+            } => {
+                if old_index == new_index {
+                    writeln!(f, "{indent}~ action {new_index}, list {list}:")?;
+                } else {
+                    writeln!(
+                        f,
+                        "{indent}~ action {old_index} -> {new_index}, list {list}:"
+                    )?;
+                }
+                fmt_actions(f, changes, &format!("{indent}{INDENT}"))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The number of characters of a value that are printed.
+const MAX_LEN: usize = 80;
+
+/// The number of unchanged characters that are printed before the first change in a value.
+const CONTEXT: usize = 20;
+
+/// Prints the debug representation of a value, shortened if it's too long to be readable.
+struct Short<'a, T>(&'a T);
+
+impl<T: Debug> Display for Short<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_excerpt(f, &format!("{:?}", self.0), 0)
+    }
+}
+
+/// Prints at most [`MAX_LEN`] characters of a text, starting at a byte index.
+fn write_excerpt(f: &mut Formatter<'_>, text: &str, start: usize) -> fmt::Result {
+    if start > 0 {
+        f.write_str("...")?;
+    }
+
+    let text = &text[start..];
+
+    match text.char_indices().nth(MAX_LEN) {
+        Some((end, _)) => write!(f, "{}...", &text[..end]),
+        None => f.write_str(text),
+    }
+}
+
+/// Compares two lists of things with an id, and returns the ones that were added, removed or changed.
+fn entries<T: Clone + PartialEq, K: Ord>(
+    old: &[T],
+    new: &[T],
+    key: impl Fn(&T) -> K,
+) -> BTreeMap<K, Entry<T, Change<T>>> {
+    changed(
+        old,
+        new,
+        key,
+        |old, new| Change::of(old, new),
+        Option::is_none,
+    )
+    .into_iter()
+    .filter_map(|(key, entry)| {
+        Some((
+            key,
+            match entry {
+                Entry::Added(item) => Entry::Added(item),
+                Entry::Removed(item) => Entry::Removed(item),
+                Entry::Modified(change) => Entry::Modified(change?),
+            },
+        ))
+    })
+    .collect()
+}
+
+/// Compares two lists of things with an id, and returns the ones that were added, removed or changed according to
+/// `compare`.
+fn changed<T: Clone, K: Ord, C>(
+    old: &[T],
+    new: &[T],
+    key: impl Fn(&T) -> K,
+    compare: impl Fn(&T, &T) -> C,
+    is_unchanged: impl Fn(&C) -> bool,
+) -> BTreeMap<K, Entry<T, C>> {
+    let old = old
+        .iter()
+        .map(|item| (key(item), item))
+        .collect::<BTreeMap<_, _>>();
+    let mut new = new
+        .iter()
+        .map(|item| (key(item), item))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut entries = BTreeMap::new();
+
+    for (id, old_item) in old {
+        match new.remove(&id) {
+            Some(new_item) => {
+                let changes = compare(old_item, new_item);
+
+                if !is_unchanged(&changes) {
+                    entries.insert(id, Entry::Modified(changes));
+                }
+            }
+            None => {
+                entries.insert(id, Entry::Removed(old_item.clone()));
+            }
+        }
+    }
+
+    for (id, new_item) in new {
+        entries.insert(id, Entry::Added(new_item.clone()));
+    }
+
+    entries
+}
+
+/// Moves the layers whose id is in `order` so they are in that order, in the places those layers were in.
+fn reorder_layers(layers: &mut [Layer], order: &[i32]) {
+    let reordered = order
+        .iter()
+        .filter_map(|id| layers.iter().find(|layer| layer.layer_id == *id).cloned())
+        .collect::<Vec<_>>();
+
+    let slots = layers
+        .iter_mut()
+        .filter(|layer| order.contains(&layer.layer_id));
+
+    for (slot, layer) in slots.zip(reordered) {
+        *slot = layer;
+    }
+}
+
+fn layer_order(old: &[Layer], new: &[Layer]) -> Option<Change<Vec<i32>>> {
+    let in_both = |layers: &[Layer], others: &[Layer]| {
+        layers
+            .iter()
+            .map(|layer| layer.layer_id)
+            .filter(|id| others.iter().any(|other| other.layer_id == *id))
+            .collect::<Vec<_>>()
+    };
+
+    Change::of(&in_both(old, new), &in_both(new, old))
+}
+
+/// Compares two lists of actions, using the longest common subsequence of equal actions.
+///
+/// Between two actions that are in both lists, removed and inserted actions of the same type are paired up as
+/// modified actions, and the nested actions of paired up actions are compared the same way.
+fn action_changes(old: &[Action], new: &[Action]) -> Vec<ActionChange> {
+    // lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0_usize; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut removed, mut inserted) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            pair_up(old, new, &mut removed, &mut inserted, &mut changes);
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lengths[i + 1][j] >= lengths[i][j + 1]) {
+            removed.push(i);
+            i += 1;
+        } else {
+            inserted.push(j);
+            j += 1;
+        }
+    }
+
+    pair_up(old, new, &mut removed, &mut inserted, &mut changes);
+
+    changes
+}
+
+/// Turns a run of removed and inserted actions into changes.
+fn pair_up(
+    old: &[Action],
+    new: &[Action],
+    removed: &mut Vec<usize>,
+    inserted: &mut Vec<usize>,
+    changes: &mut Vec<ActionChange>,
+) {
+    let mut inserted = core::mem::take(inserted).into_iter().peekable();
+
+    for old_index in core::mem::take(removed) {
+        let new_index = inserted.next_if(|&new_index| {
+            i32::from(&old[old_index].action_type) == i32::from(&new[new_index].action_type)
+        });
+
+        let Some(new_index) = new_index else {
+            changes.push(ActionChange::Removed {
+                index: old_index,
+                action: old[old_index].clone(),
+            });
+            continue;
+        };
+
+        match nested_changes(&old[old_index], &new[new_index]) {
+            Some(nested) => {
+                changes.extend(
+                    nested
+                        .into_iter()
+                        .map(|(list, nested)| ActionChange::Nested {
+                            old_index,
+                            new_index,
+                            list,
+                            changes: nested,
+                        }),
+                );
+            }
+            None => changes.push(ActionChange::Modified {
+                old_index,
+                new_index,
+                change: Change {
+                    old: old[old_index].clone(),
+                    new: new[new_index].clone(),
+                },
+            }),
+        }
+    }
+
+    changes.extend(inserted.map(|index| ActionChange::Inserted {
+        index,
+        action: new[index].clone(),
+    }));
+}
+
+/// Compares the nested actions of two actions, and returns the changes to each list of nested actions that changed.
+///
+/// Returns `None` if the actions have no nested actions, or if anything else about them changed.
+fn nested_changes(old: &Action, new: &Action) -> Option<Vec<(usize, Vec<ActionChange>)>> {
+    let (old_lists, new_lists) = (old.action_type.actions(), new.action_type.actions());
+
+    if old_lists.is_empty() || old_lists.len() != new_lists.len() {
+        return None;
+    }
+
+    let mut nested_replaced = old.clone();
+    for (list, new_list) in nested_replaced
+        .action_type
+        .actions_mut()
+        .into_iter()
+        .zip(&new_lists)
+    {
+        list.clone_from(new_list);
+    }
+
+    if nested_replaced != *new {
+        return None;
+    }
+
+    Some(
+        old_lists
+            .into_iter()
+            .zip(new_lists)
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(list, (old, new))| (list, action_changes(old, new)))
+            .collect(),
+    )
+}
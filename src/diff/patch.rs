@@ -0,0 +1,663 @@
+//! Recorded edits that can be applied to a level.
+
+use super::{
+    reorder_layers, ActionChange, Change, Entry, LayerChanges, LevelDiff, MedalTimeChanges,
+    MetadataChanges, ObjectFieldChanges, ScriptFieldChanges, SettingsChanges,
+};
+use crate::{
+    error::Error,
+    types::{
+        brush::Brush,
+        exolvl::Exolvl,
+        layer::Layer,
+        level_data::LevelData,
+        novascript::{action::Action, variable::Variable, NovaScript},
+        object::Object,
+        object_property::ObjectProperty,
+        pattern::Pattern,
+        prefab::Prefab,
+        properties::Properties,
+    },
+};
+
+/// Something with an id that a patch adds, removes or replaces.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum Item {
+    /// An object, identified by its entity id.
+    Object(Object),
+    /// A layer.
+    Layer(Layer),
+    /// A script.
+    Script(NovaScript),
+    /// A global variable.
+    GlobalVariable(Variable),
+    /// A prefab.
+    Prefab(Prefab),
+    /// A brush.
+    Brush(Brush),
+    /// A pattern.
+    Pattern(Pattern),
+}
+
+macro_rules! items {
+    ($($variant:ident: $list:ident.$id:ident, $name:literal;)*) => {
+        impl Item {
+            /// Returns the id of the item.
+            #[must_use]
+            pub const fn id(&self) -> i32 {
+                match self {
+                    $(Self::$variant(item) => item.$id,)*
+                }
+            }
+
+            /// Returns a description of the item for error messages.
+            fn describe(&self) -> String {
+                match self {
+                    $(Self::$variant(item) => format!("{} {}", $name, item.$id),)*
+                }
+            }
+
+            /// Returns the index of the item with the same id in a level, and whether it's equal to this item.
+            fn find(&self, level: &LevelData) -> Option<(usize, bool)> {
+                match self {
+                    $(
+                        Self::$variant(item) => level
+                            .$list
+                            .iter()
+                            .position(|existing| existing.$id == item.$id)
+                            .map(|index| (index, level.$list[index] == *item)),
+                    )*
+                }
+            }
+
+            fn push_to(self, level: &mut LevelData) {
+                match self {
+                    $(Self::$variant(item) => level.$list.push(item),)*
+                }
+            }
+
+            fn remove_from(&self, level: &mut LevelData, index: usize) {
+                match self {
+                    $(Self::$variant(_) => {
+                        level.$list.remove(index);
+                    })*
+                }
+            }
+
+            fn replace_in(self, level: &mut LevelData, index: usize) {
+                match self {
+                    $(Self::$variant(item) => level.$list[index] = item,)*
+                }
+            }
+        }
+    };
+}
+
+items! {
+    Object: objects.entity_id, "object";
+    Layer: layers.layer_id, "layer";
+    Script: nova_scripts.script_id, "script";
+    GlobalVariable: global_variables.variable_id, "variable";
+    Prefab: prefabs.prefab_id, "prefab";
+    Brush: brushes.brush_id, "brush";
+    Pattern: patterns.pattern_id, "pattern";
+}
+
+/// One edit of a patch.
+///
+/// Every operation has a precondition: the old values of changes, and the items that are removed, must match the
+/// level the patch is applied to.
+///
+/// Actions are found by a path: the index of the action in the script, optionally followed by pairs of the index of
+/// a list of nested actions (in the order of [`ActionType::actions`](crate::types::action_type::ActionType::actions))
+/// and the index of an action in that list.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)]
+pub enum Operation {
+    /// Changes fields of the [`LocalLevel`](crate::types::local_level::LocalLevel).
+    ChangeMetadata(MetadataChanges),
+    /// Changes the medal times.
+    ChangeMedalTimes(MedalTimeChanges),
+    /// Changes the settings of the level.
+    ChangeSettings(SettingsChanges),
+    /// Adds an item to the end of its list. There must not be an item with the same id.
+    ///
+    /// This doesn't add objects to a layer or group. The layer or group has to be changed separately.
+    Add(Item),
+    /// Removes an item.
+    ///
+    /// This doesn't remove objects from their layer or group, or references to them.
+    Remove(Item),
+    /// Replaces an item. Both items must have the same id.
+    Replace(Change<Item>),
+    /// Changes fields of an object.
+    ChangeObject {
+        entity_id: i32,
+        changes: ObjectFieldChanges,
+    },
+    /// Adds, removes or replaces a property of an object. `None` means that the object doesn't have the property.
+    ChangeProperty {
+        entity_id: i32,
+        change: Change<Option<ObjectProperty>>,
+    },
+    /// Changes fields of a layer.
+    ChangeLayer {
+        layer_id: i32,
+        changes: LayerChanges,
+    },
+    /// Changes the order of layers. The layers that aren't in the order keep their place.
+    ReorderLayers(Change<Vec<i32>>),
+    /// Changes fields of a script, except for its actions.
+    ChangeScript {
+        script_id: i32,
+        changes: ScriptFieldChanges,
+    },
+    /// Inserts an action into a script.
+    InsertAction {
+        script_id: i32,
+        path: Vec<usize>,
+        action: Action,
+    },
+    /// Removes an action from a script.
+    RemoveAction {
+        script_id: i32,
+        path: Vec<usize>,
+        action: Action,
+    },
+    /// Replaces an action of a script.
+    ReplaceAction {
+        script_id: i32,
+        path: Vec<usize>,
+        change: Change<Action>,
+    },
+}
+
+/// A list of edits that can be stored and applied to a level.
+///
+/// A patch can be [made from a diff](Self::from_diff), which gives a patch that turns the old level of the diff into
+/// the new level, or written by hand to make the same fix in many levels.
+///
+/// ```
+/// use exolvl::diff::{Change, Operation, Patch, SettingsChanges};
+/// use exolvl::types::{exolvl::Exolvl, vec2::Vec2};
+///
+/// let mut level = Exolvl::default();
+///
+/// let patch = Patch {
+///     operations: vec![Operation::ChangeSettings(SettingsChanges {
+///         gravity: Some(Change {
+///             old: level.level_data.gravity,
+///             new: Vec2::new(0.0, -50.0),
+///         }),
+///         ..Default::default()
+///     })],
+/// };
+///
+/// patch.apply(&mut level).unwrap();
+/// assert_eq!(level.level_data.gravity, Vec2::new(0.0, -50.0));
+///
+/// // the gravity isn't the old value anymore
+/// assert!(patch.apply(&mut level).is_err());
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Patch {
+    /// The edits, in the order they are applied in.
+    pub operations: Vec<Operation>,
+}
+
+impl Patch {
+    /// Returns a patch with the changes of a diff.
+    #[must_use]
+    pub fn from_diff(diff: &LevelDiff) -> Self {
+        let mut operations = Vec::new();
+
+        if !diff.metadata.is_empty() {
+            operations.push(Operation::ChangeMetadata(diff.metadata.clone()));
+        }
+
+        if !diff.medal_times.is_empty() {
+            operations.push(Operation::ChangeMedalTimes(diff.medal_times.clone()));
+        }
+
+        if !diff.settings.is_empty() {
+            operations.push(Operation::ChangeSettings(diff.settings.clone()));
+        }
+
+        for (&layer_id, entry) in &diff.layers {
+            push_entry(
+                &mut operations,
+                entry,
+                Item::Layer,
+                |operations, changes| {
+                    operations.push(Operation::ChangeLayer {
+                        layer_id,
+                        changes: changes.clone(),
+                    });
+                },
+            );
+        }
+
+        if let Some(order) = &diff.layer_order {
+            operations.push(Operation::ReorderLayers(order.clone()));
+        }
+
+        for (&entity_id, entry) in &diff.objects {
+            push_entry(
+                &mut operations,
+                entry,
+                Item::Object,
+                |operations, changes| {
+                    if !changes.fields.is_empty() {
+                        operations.push(Operation::ChangeObject {
+                            entity_id,
+                            changes: changes.fields.clone(),
+                        });
+                    }
+
+                    for entry in changes.properties.values() {
+                        let change = match entry {
+                            Entry::Added(property) => Change {
+                                old: None,
+                                new: Some(property.clone()),
+                            },
+                            Entry::Removed(property) => Change {
+                                old: Some(property.clone()),
+                                new: None,
+                            },
+                            Entry::Modified(change) => Change {
+                                old: Some(change.old.clone()),
+                                new: Some(change.new.clone()),
+                            },
+                        };
+
+                        operations.push(Operation::ChangeProperty { entity_id, change });
+                    }
+                },
+            );
+        }
+
+        for (&script_id, entry) in &diff.scripts {
+            push_entry(
+                &mut operations,
+                entry,
+                Item::Script,
+                |operations, changes| {
+                    if !changes.fields.is_empty() {
+                        operations.push(Operation::ChangeScript {
+                            script_id,
+                            changes: changes.fields.clone(),
+                        });
+                    }
+
+                    push_action_changes(operations, script_id, &[], &changes.actions);
+                },
+            );
+        }
+
+        for entry in diff.global_variables.values() {
+            push_item_entry(&mut operations, entry, Item::GlobalVariable);
+        }
+
+        for entry in diff.prefabs.values() {
+            push_item_entry(&mut operations, entry, Item::Prefab);
+        }
+
+        for entry in diff.brushes.values() {
+            push_item_entry(&mut operations, entry, Item::Brush);
+        }
+
+        for entry in diff.patterns.values() {
+            push_item_entry(&mut operations, entry, Item::Pattern);
+        }
+
+        Self { operations }
+    }
+
+    /// Applies the operations in order, and checks their preconditions.
+    ///
+    /// The level is only changed if all operations can be applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operation refers to something that doesn't exist, or if its precondition doesn't
+    /// hold.
+    pub fn apply(&self, level: &mut Exolvl) -> Result<(), Error> {
+        self.apply_with(level, true)
+    }
+
+    /// Applies the operations in order, without checking the old values of changes and removed items.
+    ///
+    /// The level is only changed if all operations can be applied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an operation refers to something that doesn't exist.
+    pub fn apply_ignoring_preconditions(&self, level: &mut Exolvl) -> Result<(), Error> {
+        self.apply_with(level, false)
+    }
+
+    fn apply_with(&self, level: &mut Exolvl, check: bool) -> Result<(), Error> {
+        let mut patched = level.clone();
+
+        for (index, operation) in self.operations.iter().enumerate() {
+            operation
+                .apply(&mut patched, check)
+                .map_err(|reason| Error::PatchFailed {
+                    operation: index,
+                    reason,
+                })?;
+        }
+
+        *level = patched;
+
+        Ok(())
+    }
+}
+
+impl Operation {
+    #[allow(clippy::too_many_lines)]
+    fn apply(&self, level: &mut Exolvl, check: bool) -> Result<(), String> {
+        let data = &mut level.level_data;
+
+        match self {
+            Self::ChangeMetadata(changes) => {
+                expect_fields(check, &changes.mismatches(&level.local_level))?;
+                changes.apply(&mut level.local_level);
+            }
+            Self::ChangeMedalTimes(changes) => {
+                expect_fields(check, &changes.mismatches(data))?;
+                changes.apply(data);
+            }
+            Self::ChangeSettings(changes) => {
+                expect_fields(check, &changes.mismatches(data))?;
+                changes.apply(data);
+            }
+            Self::Add(item) => {
+                if item.find(data).is_some() {
+                    return Err(format!("{} already exists", item.describe()));
+                }
+
+                item.clone().push_to(data);
+            }
+            Self::Remove(item) => {
+                let index = find(item, data, check)?;
+                item.remove_from(data, index);
+            }
+            Self::Replace(change) => {
+                if change.old.id() != change.new.id()
+                    || core::mem::discriminant(&change.old) != core::mem::discriminant(&change.new)
+                {
+                    return Err(format!(
+                        "can't replace {} with {}",
+                        change.old.describe(),
+                        change.new.describe()
+                    ));
+                }
+
+                let index = find(&change.old, data, check)?;
+                change.new.clone().replace_in(data, index);
+            }
+            Self::ChangeObject { entity_id, changes } => {
+                let object = object_mut(data, *entity_id)?;
+                expect_fields(check, &changes.mismatches(object))?;
+                changes.apply(object);
+            }
+            Self::ChangeProperty { entity_id, change } => {
+                let key = match (&change.old, &change.new) {
+                    (Some(old), Some(new)) if old.key() != new.key() => {
+                        return Err(format!(
+                            "can't replace property {:?} with {:?}",
+                            old.key(),
+                            new.key()
+                        ));
+                    }
+                    (Some(property), _) | (None, Some(property)) => property.key(),
+                    (None, None) => return Err(String::from("property change without a property")),
+                };
+
+                let object = object_mut(data, *entity_id)?;
+
+                if check && object.property(key) != change.old.as_ref() {
+                    return Err(format!(
+                        "property {key:?} of object {entity_id} doesn't have the old value"
+                    ));
+                }
+
+                match &change.new {
+                    Some(property) => object.set_property(property.clone()),
+                    None => object.remove_property(key),
+                };
+            }
+            Self::ChangeLayer { layer_id, changes } => {
+                let layer = data
+                    .layers
+                    .iter_mut()
+                    .find(|layer| layer.layer_id == *layer_id)
+                    .ok_or_else(|| format!("no layer with id {layer_id}"))?;
+
+                expect_fields(check, &changes.mismatches(layer))?;
+                changes.apply(layer);
+            }
+            Self::ReorderLayers(change) => {
+                if let Some(id) = change
+                    .new
+                    .iter()
+                    .find(|id| !data.layers.iter().any(|layer| layer.layer_id == **id))
+                {
+                    return Err(format!("no layer with id {id}"));
+                }
+
+                let order = data
+                    .layers
+                    .iter()
+                    .map(|layer| layer.layer_id)
+                    .filter(|id| change.new.contains(id))
+                    .collect::<Vec<_>>();
+
+                if check && order != change.old {
+                    return Err(String::from("the layers aren't in the old order"));
+                }
+
+                reorder_layers(&mut data.layers, &change.new);
+            }
+            Self::ChangeScript { script_id, changes } => {
+                let script = script_mut(data, *script_id)?;
+                expect_fields(check, &changes.mismatches(script))?;
+                changes.apply(script);
+            }
+            Self::InsertAction {
+                script_id,
+                path,
+                action,
+            } => {
+                let (actions, index) = action_list(script_mut(data, *script_id)?, path)?;
+
+                if index > actions.len() {
+                    return Err(format!("can't insert an action at {path:?}"));
+                }
+
+                actions.insert(index, action.clone());
+            }
+            Self::RemoveAction {
+                script_id,
+                path,
+                action,
+            } => {
+                let (actions, index) = action_list(script_mut(data, *script_id)?, path)?;
+                expect_action(check, actions, index, action, path)?;
+                actions.remove(index);
+            }
+            Self::ReplaceAction {
+                script_id,
+                path,
+                change,
+            } => {
+                let (actions, index) = action_list(script_mut(data, *script_id)?, path)?;
+                expect_action(check, actions, index, &change.old, path)?;
+                actions[index] = change.new.clone();
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn push_entry<T: Clone, C>(
+    operations: &mut Vec<Operation>,
+    entry: &Entry<T, C>,
+    item: fn(T) -> Item,
+    push_changes: impl FnOnce(&mut Vec<Operation>, &C),
+) {
+    match entry {
+        Entry::Added(added) => operations.push(Operation::Add(item(added.clone()))),
+        Entry::Removed(removed) => operations.push(Operation::Remove(item(removed.clone()))),
+        Entry::Modified(changes) => push_changes(operations, changes),
+    }
+}
+
+fn push_item_entry<T: Clone>(
+    operations: &mut Vec<Operation>,
+    entry: &Entry<T, Change<T>>,
+    item: fn(T) -> Item,
+) {
+    push_entry(operations, entry, item, |operations, change| {
+        operations.push(Operation::Replace(Change {
+            old: item(change.old.clone()),
+            new: item(change.new.clone()),
+        }));
+    });
+}
+
+/// Turns the changes of a list of actions into operations that are applied one after the other.
+///
+/// `prefix` is the path of the list of actions that the changes belong to, empty for the actions of the script.
+fn push_action_changes(
+    operations: &mut Vec<Operation>,
+    script_id: i32,
+    prefix: &[usize],
+    changes: &[ActionChange],
+) {
+    // the number of actions that were inserted minus the number that were removed so far, to turn indices in the
+    // old actions into indices in the partly changed actions
+    let mut shift = 0;
+
+    let path = |index: usize| [prefix, &[index]].concat();
+
+    for change in changes {
+        let operation = match change {
+            ActionChange::Inserted { index, action } => {
+                shift += 1;
+
+                Operation::InsertAction {
+                    script_id,
+                    path: path(*index),
+                    action: action.clone(),
+                }
+            }
+            ActionChange::Removed { index, action } => {
+                let path = path(index.saturating_add_signed(shift));
+                shift -= 1;
+
+                Operation::RemoveAction {
+                    script_id,
+                    path,
+                    action: action.clone(),
+                }
+            }
+            ActionChange::Modified {
+                new_index, change, ..
+            } => Operation::ReplaceAction {
+                script_id,
+                path: path(*new_index),
+                change: change.clone(),
+            },
+            ActionChange::Nested {
+                new_index,
+                list,
+                changes,
+                ..
+            } => {
+                let prefix = [prefix, &[*new_index, *list]].concat();
+                push_action_changes(operations, script_id, &prefix, changes);
+                continue;
+            }
+        };
+
+        operations.push(operation);
+    }
+}
+
+fn expect_fields(check: bool, mismatches: &[&str]) -> Result<(), String> {
+    if check && !mismatches.is_empty() {
+        return Err(format!(
+            "these fields don't have the old value: {}",
+            mismatches.join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+/// Returns the index of an item, and checks that it's equal to the item if `check` is true.
+fn find(item: &Item, level: &LevelData, check: bool) -> Result<usize, String> {
+    match item.find(level) {
+        Some((index, equal)) if equal || !check => Ok(index),
+        Some(_) => Err(format!("{} was changed", item.describe())),
+        None => Err(format!("{} doesn't exist", item.describe())),
+    }
+}
+
+fn object_mut(level: &mut LevelData, entity_id: i32) -> Result<&mut Object, String> {
+    level
+        .object_mut(entity_id)
+        .ok_or_else(|| format!("no object with entity id {entity_id}"))
+}
+
+fn script_mut(level: &mut LevelData, script_id: i32) -> Result<&mut NovaScript, String> {
+    level
+        .nova_scripts
+        .iter_mut()
+        .find(|script| script.script_id == script_id)
+        .ok_or_else(|| format!("no script with id {script_id}"))
+}
+
+/// Returns the list of actions that an action path points into, and the index in that list.
+fn action_list<'a>(
+    script: &'a mut NovaScript,
+    path: &[usize],
+) -> Result<(&'a mut Vec<Action>, usize), String> {
+    let invalid = || format!("invalid action path {path:?}");
+
+    if path.len().is_multiple_of(2) {
+        return Err(invalid());
+    }
+
+    let mut actions = &mut script.actions;
+
+    for step in path[..path.len() - 1].chunks_exact(2) {
+        actions = actions
+            .get_mut(step[0])
+            .and_then(|action| action.action_type.actions_mut().into_iter().nth(step[1]))
+            .ok_or_else(invalid)?;
+    }
+
+    Ok((actions, path[path.len() - 1]))
+}
+
+fn expect_action(
+    check: bool,
+    actions: &[Action],
+    index: usize,
+    expected: &Action,
+    path: &[usize],
+) -> Result<(), String> {
+    match actions.get(index) {
+        Some(action) if !check || action == expected => Ok(()),
+        Some(_) => Err(format!("the action at {path:?} was changed")),
+        None => Err(format!("no action at {path:?}")),
+    }
+}
@@ -0,0 +1,196 @@
+//! Scattering objects along a stroke with the settings of a brush.
+//!
+//! How the game paints with a brush isn't known, so this doesn't place objects like the game does. It only uses the
+//! settings of the brush in a way that is close to what they describe.
+
+use crate::{
+    rng::Rng,
+    types::{
+        brush::{Brush, BrushObject},
+        object::Object,
+        object_property::ObjectProperty,
+        properties::Properties,
+        vec2::Vec2,
+    },
+};
+use ordered_float::OrderedFloat;
+use std::collections::HashSet;
+
+impl Brush {
+    /// The most positions along a stroke that objects are placed at, so that a huge frequency can't place an
+    /// unbounded number of objects.
+    pub const MAX_STAMPS: usize = 100_000;
+
+    /// Scatters objects along a stroke, and returns them in the order they were placed.
+    ///
+    /// An object is placed at the start of the stroke and then every `1 / frequency` units along it, or at every
+    /// point of the stroke if the frequency isn't positive, up to [`MAX_STAMPS`](Self::MAX_STAMPS) objects. Each
+    /// object is picked from the brush objects with a probability proportional to their weight, and moved by a random
+    /// offset of up to `spread` in each direction. If the grid is set, objects are snapped to it, and at most one
+    /// object is placed in each cell. An axis whose grid size isn't positive isn't snapped, so objects are only
+    /// compared by their exact position on that axis.
+    ///
+    /// The [`entity_id`](BrushObject::entity_id) of a brush object is used as the tile id of the objects it places.
+    /// The objects don't have entity ids or a layer yet, use
+    /// [`LevelData::add_object`](crate::types::level_data::LevelData::add_object) to add them to a level.
+    ///
+    /// This isn't how the game paints with the brush: the spacing, the meaning of the entity ids and the grid are
+    /// assumptions, and the random numbers aren't the ones of the game. The same brush, stroke and seed always give
+    /// the same objects.
+    #[must_use]
+    pub fn scatter(&self, stroke: &[Vec2], seed: u64) -> Vec<Object> {
+        let mut rng = Rng::new(seed);
+        let mut cells = HashSet::new();
+        let mut objects = Vec::new();
+
+        for position in self.stamps(stroke) {
+            let Some(brush_object) = self.pick(&mut rng) else {
+                break;
+            };
+
+            let offset = Vec2::new(
+                self.spread.x.0 * rng.signed(),
+                self.spread.y.0 * rng.signed(),
+            );
+
+            let position = self.snap(position + offset);
+
+            if self.grid.x > 0 || self.grid.y > 0 {
+                let cell = (position.x, position.y);
+
+                if !cells.insert(cell) {
+                    continue;
+                }
+            }
+
+            objects.push(brush_object.object(position));
+        }
+
+        objects
+    }
+
+    /// Returns the positions along a stroke where objects are placed.
+    fn stamps(&self, stroke: &[Vec2]) -> Vec<Vec2> {
+        let frequency = self.frequency.0;
+
+        if frequency <= 0.0 || !frequency.is_finite() {
+            return stroke.iter().copied().take(Self::MAX_STAMPS).collect();
+        }
+
+        let step = 1.0 / frequency;
+        let mut stamps = stroke.first().copied().into_iter().collect::<Vec<_>>();
+
+        // the distance from the last stamp
+        let mut travelled = 0.0;
+
+        for segment in stroke.windows(2) {
+            let (start, end) = (segment[0], segment[1]);
+            let length = (end - start).length();
+
+            if length == 0.0 {
+                continue;
+            }
+
+            // the distance along the segment to the first stamp on it
+            let first = step - travelled;
+
+            if first > length {
+                travelled += length;
+                continue;
+            }
+
+            // the number of stamps after the first one, which saturates for tiny steps
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let after = ((length - first) / step) as usize;
+            let after = after.min(Self::MAX_STAMPS.saturating_sub(stamps.len() + 1));
+
+            for k in 0..=after {
+                #[allow(clippy::cast_precision_loss)]
+                let along = (k as f32).mul_add(step, first);
+                stamps.push(start + (end - start) * (along / length));
+            }
+
+            if stamps.len() >= Self::MAX_STAMPS {
+                stamps.truncate(Self::MAX_STAMPS);
+                break;
+            }
+
+            #[allow(clippy::cast_precision_loss)]
+            let last = (after as f32).mul_add(step, first);
+            travelled = length - last;
+        }
+
+        stamps
+    }
+
+    fn pick(&self, rng: &mut Rng) -> Option<&BrushObject> {
+        let weight = |object: &BrushObject| object.weight.0.max(0.0);
+        let total = self.objects.iter().map(weight).sum::<f32>();
+
+        if total <= 0.0 {
+            return None;
+        }
+
+        let mut target = rng.unit() * total;
+
+        for object in &self.objects {
+            target -= weight(object);
+
+            if target < 0.0 {
+                return Some(object);
+            }
+        }
+
+        // rounding errors can leave a tiny bit of weight
+        self.objects
+            .iter()
+            .rev()
+            .find(|object| weight(object) > 0.0)
+    }
+
+    fn snap(&self, position: Vec2) -> Vec2 {
+        let snap = |value: f32, size: i32| {
+            if size > 0 {
+                #[allow(clippy::cast_precision_loss)]
+                let size = size as f32;
+                (value / size).round() * size
+            } else {
+                value
+            }
+        };
+
+        Vec2::new(
+            snap(position.x.0, self.grid.x),
+            snap(position.y.0, self.grid.y),
+        )
+    }
+}
+
+impl BrushObject {
+    fn object(&self, position: Vec2) -> Object {
+        let mut object = Object {
+            entity_id: 0,
+            tile_id: self.entity_id,
+            prefab_entity_id: 0,
+            prefab_id: 0,
+            position,
+            scale: Vec2::new(self.scale.0, self.scale.0),
+            rotation: OrderedFloat(self.rotation.0),
+            tag: String::new(),
+            properties: self.properties.clone(),
+            in_layer: 0,
+            in_group: 0,
+            group_members: Vec::new(),
+        };
+
+        if self.flip_x {
+            object.set_property(ObjectProperty::FlipX(true));
+        }
+
+        if self.flip_y {
+            object.set_property(ObjectProperty::FlipY(true));
+        }
+
+        object
+    }
+}
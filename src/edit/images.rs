@@ -0,0 +1,148 @@
+//! Finding and removing duplicate images, and making images smaller.
+
+use crate::types::{image::Image, level_data::LevelData, object_property::ObjectProperty};
+use std::collections::{BTreeMap, HashMap};
+
+/// Where an image is embedded in a level.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ImageLocation {
+    /// The image of the prefab with an id.
+    Prefab(i32),
+    /// A frame of a pattern.
+    PatternFrame {
+        /// The id of the pattern.
+        pattern: i32,
+        /// The index of the frame.
+        frame: usize,
+    },
+}
+
+impl LevelData {
+    /// Returns the groups of identical images embedded in the level, with at least two images each.
+    ///
+    /// Groups are ordered by their first image, and images in prefabs come before images in patterns.
+    #[must_use]
+    pub fn duplicate_images(&self) -> Vec<Vec<ImageLocation>> {
+        let prefabs = self.prefabs.iter().map(|prefab| {
+            (
+                ImageLocation::Prefab(prefab.prefab_id),
+                &prefab.prefab_image_data,
+            )
+        });
+
+        let frames = self.patterns.iter().flat_map(|pattern| {
+            pattern
+                .pattern_frames
+                .iter()
+                .enumerate()
+                .map(|(frame, image)| {
+                    let location = ImageLocation::PatternFrame {
+                        pattern: pattern.pattern_id,
+                        frame,
+                    };
+
+                    (location, image)
+                })
+        });
+
+        let mut groups = Vec::<Vec<ImageLocation>>::new();
+        let mut group_of = HashMap::<&Image, usize>::new();
+
+        for (location, image) in prefabs.chain(frames) {
+            let group = *group_of.entry(image).or_insert_with(|| {
+                groups.push(Vec::new());
+                groups.len() - 1
+            });
+
+            groups[group].push(location);
+        }
+
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    /// Merges patterns that have exactly the same frames into the first of them, and returns the ids of the removed
+    /// patterns with the id of the pattern they were merged into.
+    ///
+    /// All references to the removed patterns from objects, prefab items, brushes and the copied terrain are changed
+    /// to the pattern they were merged into.
+    pub fn dedup_patterns(&mut self) -> BTreeMap<i32, i32> {
+        let mut kept = HashMap::<&[Image], i32>::new();
+        let mut merged = BTreeMap::new();
+
+        for pattern in &self.patterns {
+            let into = *kept
+                .entry(&pattern.pattern_frames)
+                .or_insert(pattern.pattern_id);
+
+            if into != pattern.pattern_id {
+                merged.insert(pattern.pattern_id, into);
+            }
+        }
+
+        if merged.is_empty() {
+            return merged;
+        }
+
+        self.patterns
+            .retain(|pattern| !merged.contains_key(&pattern.pattern_id));
+
+        let properties = self
+            .objects
+            .iter_mut()
+            .chain(self.prefabs.iter_mut().flat_map(|prefab| &mut prefab.items))
+            .flat_map(|object| &mut object.properties)
+            .chain(
+                self.brushes
+                    .iter_mut()
+                    .flat_map(|brush| &mut brush.objects)
+                    .flat_map(|object| &mut object.properties),
+            );
+
+        for property in properties {
+            if let ObjectProperty::Pattern(pattern) = property {
+                if let Some(&into) = merged.get(pattern) {
+                    *pattern = into;
+                }
+            }
+        }
+
+        if let Some(&into) = merged.get(&self.custom_terrain_pattern_id) {
+            self.custom_terrain_pattern_id = into;
+        }
+
+        merged
+    }
+
+    /// Recompresses all images in the level losslessly, see [`Image::recompress`], and returns the number of bytes
+    /// that were saved.
+    ///
+    /// With the `image` feature, images are decoded when they're read and encoded again when they're written, so
+    /// this isn't available. Use [`recompress_png`](crate::types::image::recompress_png) on the encoded images
+    /// instead.
+    #[cfg(not(feature = "image"))]
+    pub fn recompress_images(&mut self) -> u64 {
+        let images = self
+            .prefabs
+            .iter_mut()
+            .map(|prefab| &mut prefab.prefab_image_data)
+            .chain(
+                self.patterns
+                    .iter_mut()
+                    .flat_map(|pattern| &mut pattern.pattern_frames),
+            );
+
+        let mut saved = 0;
+
+        for image in images {
+            let len = image.0.len();
+
+            if image.recompress() {
+                saved += (len - image.0.len()) as u64;
+            }
+        }
+
+        saved
+    }
+}
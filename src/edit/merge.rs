@@ -0,0 +1,395 @@
+//! Merging one level into another.
+//!
+//! Everything in the merged level that has an id gets an id that is free in the level it's merged into: objects,
+//! layers, prefabs, brushes, patterns, scripts and global variables. Ids that are already free are kept. All
+//! references to the changed ids are updated, including the ones in script expressions and
+//! [`LinkedObjects`](ObjectProperty::LinkedObjects).
+//!
+//! Scripts, variables and parameters share their ids, so new script and variable ids are ids that no script,
+//! variable or parameter of either level has. Otherwise a local variable could hide a global variable that got its
+//! id.
+
+use super::used_entity_ids;
+use crate::{
+    builder::{used_ids, IdAllocator},
+    geometry::Rect,
+    types::{
+        action_type::ActionType, dynamic_type::DynamicType, level_data::LevelData,
+        novascript::nova_value::NovaValue, object_property::ObjectProperty, vec2::Vec2,
+    },
+    visit,
+};
+use std::collections::{HashMap, HashSet};
+
+/// What to do with a layer or variable that has the same name as one in the level that is merged into.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub enum Conflict {
+    /// Add it anyway, so there are two with the same name.
+    #[default]
+    KeepBoth,
+    /// Use the existing one instead. For layers, the objects of the merged layer are added to the existing layer.
+    /// Variables are only merged if they also have the same type.
+    Merge,
+}
+
+/// Options for [`LevelData::merge`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MergeOptions {
+    /// Added to the position of the merged objects.
+    pub offset: Vec2,
+    /// Only merge the objects whose [bounds](crate::types::object::Object::bounds) overlap this area, with all members of the groups among
+    /// them. References to objects that aren't merged are removed.
+    pub region: Option<Rect>,
+    /// What to do with layers that have the same name. Layers without a name are never merged.
+    pub layers: Conflict,
+    /// What to do with global variables that have the same name.
+    pub variables: Conflict,
+}
+
+/// The new ids of everything that was merged, by their id in the merged level.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IdMap {
+    /// The entity ids of objects.
+    pub entities: HashMap<i32, i32>,
+    /// The ids of layers.
+    pub layers: HashMap<i32, i32>,
+    /// The ids of prefabs.
+    pub prefabs: HashMap<i32, i32>,
+    /// The ids of brushes.
+    pub brushes: HashMap<i32, i32>,
+    /// The ids of patterns.
+    pub patterns: HashMap<i32, i32>,
+    /// The ids of scripts.
+    pub scripts: HashMap<i32, i32>,
+    /// The ids of global variables.
+    pub variables: HashMap<i32, i32>,
+}
+
+impl LevelData {
+    /// Merges the objects, layers, prefabs, brushes, patterns, scripts and global variables of another level into this
+    /// one, and returns the new ids of everything that was merged.
+    ///
+    /// The settings of this level, like its name, theme and music, are kept.
+    pub fn merge(&mut self, other: &Self, options: &MergeOptions) -> IdMap {
+        let mut source = other.clone();
+
+        if let Some(region) = options.region {
+            source.retain_region(region);
+        }
+
+        let map = IdMap::new(self, &source, options);
+        map.apply(&mut source, options.offset);
+
+        for layer in source.layers {
+            match self
+                .layers
+                .iter_mut()
+                .find(|existing| existing.layer_id == layer.layer_id)
+            {
+                Some(existing) => existing.children.extend(layer.children),
+                None => self.layers.push(layer),
+            }
+        }
+
+        let existing_variables = self
+            .global_variables
+            .iter()
+            .map(|variable| variable.variable_id)
+            .collect::<HashSet<_>>();
+
+        self.global_variables.extend(
+            source
+                .global_variables
+                .into_iter()
+                .filter(|variable| !existing_variables.contains(&variable.variable_id)),
+        );
+
+        self.objects.extend(source.objects);
+        self.prefabs.extend(source.prefabs);
+        self.brushes.extend(source.brushes);
+        self.patterns.extend(source.patterns);
+        self.nova_scripts.extend(source.nova_scripts);
+
+        map
+    }
+
+    /// Removes all objects outside of a region, keeping groups together.
+    fn retain_region(&mut self, region: Rect) {
+        let mut kept = HashSet::new();
+
+        for object in &self.objects {
+            let top_level = object.in_group == 0 || self.object(object.in_group).is_none();
+
+            if top_level && object.bounds().intersects(region) {
+                kept.extend(self.with_members(object.entity_id));
+            }
+        }
+
+        let removed = self
+            .objects
+            .iter()
+            .map(|object| object.entity_id)
+            .filter(|id| !kept.contains(id))
+            .collect::<HashSet<_>>();
+
+        self.objects
+            .retain(|object| kept.contains(&object.entity_id));
+        self.remove_references(&removed);
+    }
+}
+
+impl IdMap {
+    fn new(target: &LevelData, source: &LevelData, options: &MergeOptions) -> Self {
+        let mut layers = HashMap::new();
+
+        if options.layers == Conflict::Merge {
+            for layer in &source.layers {
+                if let Some(existing) = target.layers.iter().find(|existing| {
+                    !layer.layer_name.is_empty() && existing.layer_name == layer.layer_name
+                }) {
+                    layers.insert(layer.layer_id, existing.layer_id);
+                }
+            }
+        }
+
+        let mut variables = HashMap::new();
+
+        if options.variables == Conflict::Merge {
+            // the scripts of the merged level can't use a variable that one of their own variables hides
+            let hiding = source
+                .nova_scripts
+                .iter()
+                .flat_map(|script| {
+                    let locals = script.variables.iter().map(|variable| variable.variable_id);
+                    locals.chain(
+                        script
+                            .parameters
+                            .iter()
+                            .map(|parameter| parameter.parameter_id),
+                    )
+                })
+                .collect::<HashSet<_>>();
+
+            for variable in &source.global_variables {
+                if let Some(existing) = target.global_variables.iter().find(|existing| {
+                    existing.name == variable.name
+                        && existing.static_type == variable.static_type
+                        && (existing.variable_id == variable.variable_id
+                            || !hiding.contains(&existing.variable_id))
+                }) {
+                    variables.insert(variable.variable_id, existing.variable_id);
+                }
+            }
+        }
+
+        // new script and variable ids come from the ids that are shared by both levels
+        let mut shared_ids = IdAllocator::from_used(used_ids(target).chain(used_ids(source)));
+
+        Self {
+            entities: free_ids(
+                source.objects.iter().map(|object| object.entity_id),
+                used_entity_ids(target),
+                HashMap::new(),
+                None,
+            ),
+            layers: free_ids(
+                source.layers.iter().map(|layer| layer.layer_id),
+                target.layers.iter().map(|layer| layer.layer_id),
+                layers,
+                None,
+            ),
+            prefabs: free_ids(
+                source.prefabs.iter().map(|prefab| prefab.prefab_id),
+                target.prefabs.iter().map(|prefab| prefab.prefab_id),
+                HashMap::new(),
+                None,
+            ),
+            brushes: free_ids(
+                source.brushes.iter().map(|brush| brush.brush_id),
+                target.brushes.iter().map(|brush| brush.brush_id),
+                HashMap::new(),
+                None,
+            ),
+            patterns: free_ids(
+                source.patterns.iter().map(|pattern| pattern.pattern_id),
+                target.patterns.iter().map(|pattern| pattern.pattern_id),
+                HashMap::new(),
+                None,
+            ),
+            scripts: free_ids(
+                source.nova_scripts.iter().map(|script| script.script_id),
+                used_ids(target),
+                HashMap::new(),
+                Some(&mut shared_ids),
+            ),
+            variables: free_ids(
+                source
+                    .global_variables
+                    .iter()
+                    .map(|variable| variable.variable_id),
+                used_ids(target),
+                variables,
+                Some(&mut shared_ids),
+            ),
+        }
+    }
+
+    /// Changes all ids in a level, and moves its objects.
+    pub(crate) fn apply(&self, level: &mut LevelData, offset: Vec2) {
+        let entity = |id: &mut i32| remap(&self.entities, id);
+
+        for object in &mut level.objects {
+            if object.in_group == 0 {
+                object.position = object.position + offset;
+            } else {
+                entity(&mut object.in_group);
+            }
+
+            entity(&mut object.entity_id);
+            object.group_members.iter_mut().for_each(entity);
+            remap(&self.layers, &mut object.in_layer);
+
+            if object.prefab_id != 0 {
+                remap(&self.prefabs, &mut object.prefab_id);
+            }
+
+            self.apply_to_properties(&mut object.properties);
+        }
+
+        for layer in &mut level.layers {
+            remap(&self.layers, &mut layer.layer_id);
+            layer.children.iter_mut().for_each(entity);
+        }
+
+        for prefab in &mut level.prefabs {
+            remap(&self.prefabs, &mut prefab.prefab_id);
+
+            for item in &mut prefab.items {
+                // items refer to each other with their own ids, which don't change
+                for property in &mut item.properties {
+                    if let ObjectProperty::Pattern(pattern) = property {
+                        remap(&self.patterns, pattern);
+                    }
+                }
+            }
+        }
+
+        for brush in &mut level.brushes {
+            remap(&self.brushes, &mut brush.brush_id);
+
+            for object in &mut brush.objects {
+                self.apply_to_properties(&mut object.properties);
+            }
+        }
+
+        for pattern in &mut level.patterns {
+            remap(&self.patterns, &mut pattern.pattern_id);
+        }
+
+        let no_locals = HashSet::new();
+
+        for variable in &mut level.global_variables {
+            remap(&self.variables, &mut variable.variable_id);
+            visit::value_mut(&mut variable.initial_value, &mut |value| {
+                self.apply_to_value(value, &no_locals);
+            });
+        }
+
+        for script in &mut level.nova_scripts {
+            remap(&self.scripts, &mut script.script_id);
+
+            // local variables hide global variables with the same id
+            let locals = script
+                .variables
+                .iter()
+                .map(|variable| variable.variable_id)
+                .collect::<HashSet<_>>();
+
+            visit::script_values_mut(script, &mut |value| self.apply_to_value(value, &locals));
+
+            visit::actions_mut(&mut script.actions, &mut |action| match action {
+                ActionType::SetVariable { variable, .. }
+                | ActionType::ResetVariable { variable }
+                | ActionType::SetVariableOverTime { variable, .. }
+                    if !locals.contains(variable) =>
+                {
+                    remap(&self.variables, variable);
+                }
+                ActionType::RunFunction { function } => remap(&self.scripts, &mut function.id),
+                _ => {}
+            });
+        }
+    }
+
+    fn apply_to_properties(&self, properties: &mut [ObjectProperty]) {
+        for property in properties {
+            match property {
+                ObjectProperty::LinkedObjects(linked) => {
+                    for id in linked {
+                        remap(&self.entities, id);
+                    }
+                }
+                ObjectProperty::Pattern(pattern) => remap(&self.patterns, pattern),
+                _ => {}
+            }
+        }
+    }
+
+    fn apply_to_value(&self, value: &mut NovaValue, locals: &HashSet<i32>) {
+        let inner = &mut value.inner;
+
+        match value.dynamic_type {
+            DynamicType::ObjectConstant => remap(&self.entities, &mut inner.int_value),
+            DynamicType::ObjectSetConstant => {
+                for id in inner.int_list_value.iter_mut().flatten() {
+                    remap(&self.entities, id);
+                }
+            }
+            DynamicType::LayerConstant => remap(&self.layers, &mut inner.int_value),
+            DynamicType::ScriptConstant => remap(&self.scripts, &mut inner.int_value),
+            ty if ty.is_variable() && !locals.contains(&inner.int_value) => {
+                remap(&self.variables, &mut inner.int_value);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn remap(map: &HashMap<i32, i32>, id: &mut i32) {
+    if let Some(&new) = map.get(id) {
+        *id = new;
+    }
+}
+
+/// Maps every id in `ids` that isn't in `map` yet to itself if it isn't used, or to a new unused id otherwise.
+///
+/// New ids are taken from `shared_ids` if it's given, which has to exclude `used` and `ids` already.
+fn free_ids(
+    ids: impl Iterator<Item = i32> + Clone,
+    used: impl Iterator<Item = i32>,
+    mut map: HashMap<i32, i32>,
+    shared_ids: Option<&mut IdAllocator>,
+) -> HashMap<i32, i32> {
+    let mut taken = used.collect::<HashSet<_>>();
+    taken.extend(map.values().copied());
+
+    // new ids can't be one of the ids that are kept either
+    let mut own_ids = IdAllocator::from_used(taken.iter().copied().chain(ids.clone()));
+    let new_ids = shared_ids.unwrap_or(&mut own_ids);
+
+    for id in ids {
+        if map.contains_key(&id) {
+            continue;
+        }
+
+        let new = if taken.insert(id) {
+            id
+        } else {
+            new_ids.allocate()
+        };
+
+        map.insert(id, new);
+    }
+
+    map
+}
@@ -0,0 +1,503 @@
+//! Editing operations on levels that keep the references between objects consistent.
+//!
+//! Objects are referenced by their entity id from the [children](Layer::children) of their layer, the
+//! [members](Object::group_members) of their group, the [`LinkedObjects`](Properties::linked_objects) of other
+//! objects and object constants in scripts. The operations here update all of these together, so a level never ends
+//! up with references to objects that don't exist.
+//!
+//! Objects in a group are positioned relative to their group. Moving objects into or out of a group keeps them where
+//! they are in the level, except that a group with a non-uniform scale can't be undone exactly for rotated members.
+//!
+//! ```
+//! use exolvl::catalogue;
+//! use exolvl::types::{level_data::LevelData, vec2::Vec2};
+//! # use exolvl::types::{foreground_type::ForegroundType, layer::Layer};
+//!
+//! let mut level = LevelData::default();
+//! # level.layers.push(Layer {
+//! #     layer_id: 1,
+//! #     layer_name: String::from("Layer"),
+//! #     selected: false,
+//! #     invisible: false,
+//! #     locked: false,
+//! #     foreground_type: ForegroundType::Background,
+//! #     parallax: Vec2::new(1.0, 1.0),
+//! #     fixed_size: false,
+//! #     children: Vec::new(),
+//! # });
+//! let layer = level.layers[0].layer_id;
+//!
+//! let square = catalogue::lookup(catalogue::SQUARE).unwrap();
+//! let a = level.add_object(square.new_object(0, Vec2::new(-1.0, 0.0), layer)).unwrap();
+//! let b = level.add_object(square.new_object(0, Vec2::new(1.0, 0.0), layer)).unwrap();
+//!
+//! let group = level.group(&[a, b]).unwrap();
+//! assert_eq!(level.object(a).unwrap().in_group, group);
+//!
+//! level.remove_object(group);
+//! assert!(level.objects.is_empty());
+//! ```
+
+mod brush;
+mod images;
+mod merge;
+#[cfg(feature = "image")]
+mod pattern;
+mod prefab;
+mod prune;
+mod refactor;
+
+pub use images::ImageLocation;
+pub use merge::{Conflict, IdMap, MergeOptions};
+pub use prune::Unused;
+pub use refactor::TypeError;
+
+use crate::{
+    builder::IdAllocator,
+    catalogue,
+    error::Error,
+    geometry::{Rect, Transform},
+    types::{
+        dynamic_type::DynamicType, layer::Layer, level_data::LevelData,
+        novascript::nova_value::NovaValue, object::Object, object_property::ObjectProperty,
+        properties::Properties, vec2::Vec2,
+    },
+    visit,
+};
+use std::collections::HashSet;
+
+/// Hands out entity ids that aren't used in a level.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntityIdAllocator(IdAllocator);
+
+impl EntityIdAllocator {
+    /// Returns an allocator for ids that aren't used by any object or layer of the level.
+    #[must_use]
+    pub fn new(level: &LevelData) -> Self {
+        Self(IdAllocator::from_used(used_entity_ids(level)))
+    }
+
+    /// Returns the lowest positive id that isn't used in the level. Ids are never handed out twice by the same
+    /// allocator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every positive id is used.
+    pub fn allocate(&mut self) -> i32 {
+        self.0.allocate()
+    }
+}
+
+/// Returns the entity ids of the objects of a level, and the ids in its layers, which may refer to objects that
+/// don't exist.
+pub(crate) fn used_entity_ids(level: &LevelData) -> impl Iterator<Item = i32> + '_ {
+    level.objects.iter().map(|object| object.entity_id).chain(
+        level
+            .layers
+            .iter()
+            .flat_map(|layer| layer.children.iter().copied()),
+    )
+}
+
+impl LevelData {
+    /// Returns the lowest positive entity id that isn't used in the level.
+    #[must_use]
+    pub fn next_entity_id(&self) -> i32 {
+        EntityIdAllocator::new(self).allocate()
+    }
+
+    /// Returns the object with an entity id.
+    #[must_use]
+    pub fn object(&self, entity_id: i32) -> Option<&Object> {
+        self.objects
+            .iter()
+            .find(|object| object.entity_id == entity_id)
+    }
+
+    /// Returns the object with an entity id mutably.
+    pub fn object_mut(&mut self, entity_id: i32) -> Option<&mut Object> {
+        self.objects
+            .iter_mut()
+            .find(|object| object.entity_id == entity_id)
+    }
+
+    /// Adds an object to the level with a new entity id, and returns the id.
+    ///
+    /// If [`in_group`](Object::in_group) is the id of a group, the object is added to the end of that group and moved
+    /// to its layer. If there is no object with that id, it's added to the end of the children of
+    /// [`in_layer`](Object::in_layer) instead. The [`group_members`](Object::group_members) of the object are
+    /// cleared, since they can't refer to objects that are already in a group.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `in_group` is the id of an object that isn't a group, or if the object isn't added to a
+    /// group and there is no layer with the id `in_layer`. Nothing is changed in that case.
+    pub fn add_object(&mut self, mut object: Object) -> Result<i32, Error> {
+        let in_group = object.in_group;
+        let group = self.object(in_group).filter(|_| in_group != 0);
+
+        if group.is_some_and(|group| group.tile_id != catalogue::GROUP) {
+            return Err(Error::InvalidEdit(format!(
+                "object {in_group} isn't a group"
+            )));
+        }
+
+        if group.is_none()
+            && !self
+                .layers
+                .iter()
+                .any(|layer| layer.layer_id == object.in_layer)
+        {
+            return Err(Error::LayerNotFound(object.in_layer));
+        }
+
+        object.entity_id = self.next_entity_id();
+        object.group_members.clear();
+
+        if let Some(group) = self.object_mut(in_group).filter(|_| in_group != 0) {
+            group.group_members.push(object.entity_id);
+            object.in_layer = group.in_layer;
+        } else {
+            object.in_group = 0;
+
+            if let Some(layer) = self.layer_mut(object.in_layer) {
+                layer.children.push(object.entity_id);
+            }
+        }
+
+        let id = object.entity_id;
+        self.objects.push(object);
+
+        Ok(id)
+    }
+
+    /// Removes an object and, if it's a group, all of its members, and returns the removed objects.
+    ///
+    /// References to the removed objects are removed from layers, groups and [`LinkedObjects`](Properties::linked_objects),
+    /// and from object set constants in scripts. Object constants that refer to a removed object are set to 0, which
+    /// doesn't refer to any object.
+    pub fn remove_object(&mut self, entity_id: i32) -> Vec<Object> {
+        let Some(object) = self.object(entity_id) else {
+            return Vec::new();
+        };
+        let in_group = object.in_group;
+
+        let removed = self.with_members(entity_id);
+        let removed_set = removed.iter().copied().collect::<HashSet<_>>();
+
+        if let Some(list) = self.parent_list_mut(in_group, entity_id) {
+            list.retain(|&id| id != entity_id);
+        }
+
+        let mut objects = Vec::with_capacity(removed.len());
+        let mut kept = Vec::with_capacity(self.objects.len());
+
+        for object in core::mem::take(&mut self.objects) {
+            if removed_set.contains(&object.entity_id) {
+                objects.push(object);
+            } else {
+                kept.push(object);
+            }
+        }
+
+        self.objects = kept;
+        self.remove_references(&removed_set);
+
+        objects.sort_by_key(|object| removed.iter().position(|&id| id == object.entity_id));
+        objects
+    }
+
+    /// Moves an object, and its members if it's a group, to the end of a layer.
+    ///
+    /// An object in a group is taken out of the group first, keeping its position in the level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the object or the layer doesn't exist.
+    pub fn move_to_layer(&mut self, entity_id: i32, layer_id: i32) -> Result<(), Error> {
+        if !self.layers.iter().any(|layer| layer.layer_id == layer_id) {
+            return Err(Error::LayerNotFound(layer_id));
+        }
+
+        let object = self
+            .object(entity_id)
+            .ok_or(Error::ObjectNotFound(entity_id))?;
+        let (in_group, in_layer) = (object.in_group, object.in_layer);
+
+        if in_group == 0 {
+            if let Some(layer) = self.layer_mut(in_layer) {
+                layer.children.retain(|&id| id != entity_id);
+            }
+        } else {
+            self.take_out_of_groups(entity_id);
+        }
+
+        for id in self.with_members(entity_id) {
+            if let Some(object) = self.object_mut(id) {
+                object.in_layer = layer_id;
+            }
+        }
+
+        if let Some(layer) = self.layer_mut(layer_id) {
+            layer.children.push(entity_id);
+        }
+
+        Ok(())
+    }
+
+    /// Puts objects into a new group, and returns the entity id of the group.
+    ///
+    /// The group is placed at the center of the objects, where the first object was in its layer or group. The
+    /// members keep their position in the level.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no objects, an object doesn't exist or is listed twice, or the objects aren't all
+    /// in the same layer and group.
+    pub fn group(&mut self, entity_ids: &[i32]) -> Result<i32, Error> {
+        let Some(&first) = entity_ids.first() else {
+            return Err(Error::InvalidEdit("can't group zero objects".to_string()));
+        };
+
+        let first_object = self.object(first).ok_or(Error::ObjectNotFound(first))?;
+        let (in_layer, in_group) = (first_object.in_layer, first_object.in_group);
+
+        let mut seen = HashSet::new();
+        let mut bounds: Option<Rect> = None;
+
+        for &id in entity_ids {
+            let object = self.object(id).ok_or(Error::ObjectNotFound(id))?;
+
+            if !seen.insert(id) {
+                return Err(Error::InvalidEdit(format!("object {id} is listed twice")));
+            }
+
+            if object.in_layer != in_layer || object.in_group != in_group {
+                return Err(Error::InvalidEdit(format!(
+                    "object {id} isn't in the same layer and group as object {first}"
+                )));
+            }
+
+            let object_bounds = object.bounds();
+            bounds = Some(bounds.map_or(object_bounds, |bounds| bounds.union(object_bounds)));
+        }
+
+        let center = bounds.map_or(first_object.position, Rect::center);
+        let group_id = self.next_entity_id();
+
+        let mut group = group_tile().new_object(group_id, center, in_layer);
+        group.in_group = in_group;
+        group.group_members = entity_ids.to_vec();
+
+        for &id in entity_ids {
+            if let Some(object) = self.object_mut(id) {
+                object.position = object.position - center;
+                object.in_group = group_id;
+            }
+        }
+
+        if let Some(list) = self.parent_list_mut(in_group, first) {
+            if let Some(index) = list.iter().position(|&id| id == first) {
+                list[index] = group_id;
+            } else {
+                list.push(group_id);
+            }
+
+            list.retain(|id| !seen.contains(id));
+        }
+
+        self.objects.push(group);
+
+        Ok(group_id)
+    }
+
+    /// Dissolves a group, and returns its members.
+    ///
+    /// The members take the place of the group in its layer or group and keep their position in the level. References
+    /// to the group are removed like in [`remove_object`](Self::remove_object).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the object doesn't exist or isn't a group.
+    pub fn ungroup(&mut self, group_id: i32) -> Result<Vec<i32>, Error> {
+        let group = self
+            .object(group_id)
+            .ok_or(Error::ObjectNotFound(group_id))?;
+
+        if group.tile_id != catalogue::GROUP {
+            return Err(Error::InvalidEdit(format!(
+                "object {group_id} isn't a group"
+            )));
+        }
+
+        let transform = Transform::of(group);
+        let in_group = group.in_group;
+        let members = group.group_members.clone();
+
+        for &id in &members {
+            if let Some(object) = self.object_mut(id) {
+                to_parent_space(object, transform);
+                object.in_group = in_group;
+            }
+        }
+
+        if let Some(list) = self.parent_list_mut(in_group, group_id) {
+            if let Some(index) = list.iter().position(|&id| id == group_id) {
+                list.splice(index..=index, members.iter().copied());
+            } else {
+                list.extend(&members);
+            }
+        }
+
+        if let Some(group) = self.object_mut(group_id) {
+            group.group_members.clear();
+        }
+
+        self.remove_object(group_id);
+
+        Ok(members)
+    }
+
+    fn layer_mut(&mut self, layer_id: i32) -> Option<&mut Layer> {
+        self.layers
+            .iter_mut()
+            .find(|layer| layer.layer_id == layer_id)
+    }
+
+    /// Returns the list an object is in: the members of its group, or the children of its layer.
+    fn parent_list_mut(&mut self, in_group: i32, entity_id: i32) -> Option<&mut Vec<i32>> {
+        if in_group == 0 {
+            let in_layer = self.object(entity_id)?.in_layer;
+            self.layer_mut(in_layer).map(|layer| &mut layer.children)
+        } else {
+            self.object_mut(in_group)
+                .map(|group| &mut group.group_members)
+        }
+    }
+
+    /// Returns the id of an object followed by the ids of all objects in it, recursively.
+    pub(crate) fn with_members(&self, entity_id: i32) -> Vec<i32> {
+        let mut ids = vec![entity_id];
+        let mut i = 0;
+
+        while let Some(&id) = ids.get(i) {
+            if let Some(object) = self.object(id) {
+                for &member in &object.group_members {
+                    // guard against groups that contain themselves
+                    if !ids.contains(&member) {
+                        ids.push(member);
+                    }
+                }
+            }
+
+            i += 1;
+        }
+
+        ids
+    }
+
+    /// Takes an object out of its group and all groups around it, keeping its position in the level.
+    fn take_out_of_groups(&mut self, entity_id: i32) {
+        let mut visited = HashSet::new();
+
+        while let Some(object) = self.object(entity_id) {
+            let in_group = object.in_group;
+
+            if in_group == 0 || !visited.insert(in_group) {
+                break;
+            }
+
+            let Some(group) = self.object_mut(in_group) else {
+                break;
+            };
+            group.group_members.retain(|&id| id != entity_id);
+
+            let transform = Transform::of(group);
+            let parent = group.in_group;
+
+            if let Some(object) = self.object_mut(entity_id) {
+                to_parent_space(object, transform);
+                object.in_group = parent;
+            }
+        }
+
+        if let Some(object) = self.object_mut(entity_id) {
+            object.in_group = 0;
+        }
+    }
+
+    /// Removes all references to objects.
+    pub(crate) fn remove_references(&mut self, removed: &HashSet<i32>) {
+        for layer in &mut self.layers {
+            layer.children.retain(|id| !removed.contains(id));
+        }
+
+        for object in &mut self.objects {
+            object.group_members.retain(|id| !removed.contains(id));
+
+            if removed.contains(&object.in_group) {
+                object.in_group = 0;
+            }
+
+            for property in &mut object.properties {
+                if let ObjectProperty::LinkedObjects(linked) = property {
+                    linked.retain(|id| !removed.contains(id));
+                }
+            }
+        }
+
+        self.for_each_value_mut(|value| match value.dynamic_type {
+            DynamicType::ObjectConstant if removed.contains(&value.inner.int_value) => {
+                value.inner.int_value = 0;
+            }
+            DynamicType::ObjectSetConstant => {
+                if let Some(ids) = &mut value.inner.int_list_value {
+                    ids.retain(|id| !removed.contains(id));
+                }
+            }
+            _ => {}
+        });
+    }
+
+    /// Calls `f` on every value in the scripts and global variables of the level, including values nested in other
+    /// values.
+    pub(crate) fn for_each_value_mut(&mut self, mut f: impl FnMut(&mut NovaValue)) {
+        for variable in &mut self.global_variables {
+            visit::value_mut(&mut variable.initial_value, &mut f);
+        }
+
+        for script in &mut self.nova_scripts {
+            visit::script_values_mut(script, &mut f);
+        }
+    }
+}
+
+fn group_tile() -> &'static catalogue::TileInfo {
+    catalogue::lookup(catalogue::GROUP).expect("the catalogue contains groups")
+}
+
+/// Changes the placement of an object from relative to its group to relative to the parent of the group.
+fn to_parent_space(object: &mut Object, group: Transform) {
+    let mirrored = group.flip_x != group.flip_y;
+
+    object.position = group.apply(object.position);
+    object.scale = Vec2::new(
+        object.scale.x.0 * group.scale.x.0,
+        object.scale.y.0 * group.scale.y.0,
+    );
+    object.rotation = if mirrored {
+        group.rotation - object.rotation
+    } else {
+        group.rotation + object.rotation
+    };
+
+    if group.flip_x {
+        let flip_x = object.flip_x().unwrap_or(false);
+        object.set_flip_x(!flip_x);
+    }
+
+    if group.flip_y {
+        let flip_y = object.flip_y().unwrap_or(false);
+        object.set_flip_y(!flip_y);
+    }
+}
@@ -0,0 +1,200 @@
+//! Editing the frames of patterns, and exporting them as animations.
+
+use crate::{
+    error::Error,
+    types::{image::Image, pattern::Pattern},
+};
+use core::time::Duration;
+use image::{
+    codecs::gif::{GifEncoder, Repeat},
+    error::{EncodingError, ImageFormatHint},
+    imageops::{self, FilterType},
+    Delay, Frame, ImageError, ImageFormat, RgbaImage,
+};
+use std::path::Path;
+
+impl Pattern {
+    /// Inserts a frame at an index, shifting the frames after it.
+    ///
+    /// If the pattern already has frames, the new frame is resized to the size of the first frame, so all frames
+    /// keep the same size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of frames.
+    pub fn insert_frame(&mut self, index: usize, frame: RgbaImage) {
+        let frame = match self.pattern_frames.first() {
+            Some(first) if first.0.dimensions() != frame.dimensions() => {
+                let (width, height) = first.0.dimensions();
+                imageops::resize(&frame, width, height, FilterType::Lanczos3)
+            }
+            _ => frame,
+        };
+
+        self.pattern_frames.insert(index, Image(frame));
+    }
+
+    /// Loads an image file and inserts it as a frame at an index, see [`Pattern::insert_frame`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file can't be read or decoded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is greater than the number of frames.
+    pub fn insert_frame_from_file(
+        &mut self,
+        index: usize,
+        path: impl AsRef<Path>,
+    ) -> Result<(), Error> {
+        let frame = image::open(path)?.to_rgba8();
+        self.insert_frame(index, frame);
+
+        Ok(())
+    }
+
+    /// Removes the frame at an index and returns it, or returns `None` if there is no frame at the index.
+    pub fn remove_frame(&mut self, index: usize) -> Option<Image> {
+        (index < self.pattern_frames.len()).then(|| self.pattern_frames.remove(index))
+    }
+
+    /// Moves the frame at index `from` to index `to`, shifting the frames between them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either index is out of bounds.
+    pub fn move_frame(&mut self, from: usize, to: usize) {
+        let frame = self.pattern_frames.remove(from);
+        self.pattern_frames.insert(to, frame);
+    }
+
+    /// Resizes all frames to the same size.
+    pub fn resize_frames(&mut self, width: u32, height: u32, filter: FilterType) {
+        for frame in &mut self.pattern_frames {
+            if frame.0.dimensions() != (width, height) {
+                frame.0 = imageops::resize(&frame.0, width, height, filter);
+            }
+        }
+    }
+
+    /// Removes frames that are identical to the frame before them, and returns the number of removed frames.
+    ///
+    /// Only consecutive frames are merged, so the order of the animation is kept.
+    pub fn dedup_frames(&mut self) -> usize {
+        let len = self.pattern_frames.len();
+        self.pattern_frames.dedup();
+
+        len - self.pattern_frames.len()
+    }
+
+    /// Returns all frames next to each other from left to right, aligned to the top.
+    ///
+    /// Returns `None` if the combined width of the frames doesn't fit in a `u32`.
+    #[must_use]
+    pub fn sprite_sheet(&self) -> Option<RgbaImage> {
+        let width = self
+            .pattern_frames
+            .iter()
+            .try_fold(0_u32, |width, frame| width.checked_add(frame.0.width()))?;
+        let height = self
+            .pattern_frames
+            .iter()
+            .map(|frame| frame.0.height())
+            .max()
+            .unwrap_or(0);
+
+        let mut sheet = RgbaImage::new(width, height);
+        let mut x = 0;
+
+        for frame in &self.pattern_frames {
+            imageops::replace(&mut sheet, &frame.0, x, 0);
+            x += i64::from(frame.0.width());
+        }
+
+        Some(sheet)
+    }
+
+    /// Writes the frames as an animated GIF that loops forever, showing each frame for `frame_delay`.
+    ///
+    /// GIF only supports fully transparent or opaque pixels, and at most 256 colors per frame.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the animation can't be encoded or written.
+    pub fn write_gif(
+        &self,
+        output: impl std::io::Write,
+        frame_delay: Duration,
+    ) -> Result<(), Error> {
+        let mut encoder = GifEncoder::new(output);
+        encoder.set_repeat(Repeat::Infinite)?;
+
+        let delay = Delay::from_saturating_duration(frame_delay);
+
+        encoder.encode_frames(
+            self.pattern_frames
+                .iter()
+                .map(|frame| Frame::from_parts(frame.0.clone(), 0, 0, delay)),
+        )?;
+
+        Ok(())
+    }
+
+    /// Writes the frames as an animated PNG that loops forever, showing each frame for `frame_delay`, rounded to
+    /// milliseconds.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pattern has no frames, if the frames don't all have the same size, or if the animation
+    /// can't be encoded or written.
+    pub fn write_apng(
+        &self,
+        output: impl std::io::Write,
+        frame_delay: Duration,
+    ) -> Result<(), Error> {
+        let Some(first) = self.pattern_frames.first() else {
+            return Err(Error::InvalidEdit(String::from(
+                "the pattern has no frames",
+            )));
+        };
+
+        let (width, height) = first.0.dimensions();
+
+        if self
+            .pattern_frames
+            .iter()
+            .any(|frame| frame.0.dimensions() != (width, height))
+        {
+            return Err(Error::InvalidEdit(String::from(
+                "the frames don't all have the same size",
+            )));
+        }
+
+        let frames = u32::try_from(self.pattern_frames.len())
+            .map_err(|_| Error::InvalidEdit(String::from("the pattern has too many frames")))?;
+        let delay = u16::try_from(frame_delay.as_millis()).unwrap_or(u16::MAX);
+
+        let mut encoder = png::Encoder::new(output, width, height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(frames, 0).map_err(png_error)?;
+        encoder.set_frame_delay(delay, 1000).map_err(png_error)?;
+
+        let mut writer = encoder.write_header().map_err(png_error)?;
+
+        for frame in &self.pattern_frames {
+            writer.write_image_data(&frame.0).map_err(png_error)?;
+        }
+
+        writer.finish().map_err(png_error)
+    }
+}
+
+fn png_error(err: png::EncodingError) -> Error {
+    ImageError::Encoding(EncodingError::new(
+        ImageFormatHint::Exact(ImageFormat::Png),
+        err,
+    ))
+    .into()
+}
@@ -0,0 +1,249 @@
+//! Instances of prefabs.
+//!
+//! The [items](Prefab::items) of a prefab are template objects, positioned relative to the origin of the prefab.
+//! An instance is a group at the position of the instance, with [`prefab_id`](Object::prefab_id) set to the prefab
+//! and [`prefab_entity_id`](Object::prefab_entity_id) set to 0. The group contains a copy of every item, with
+//! `prefab_id` set to the prefab and `prefab_entity_id` set to the entity id of the item it was copied from.
+
+use super::{group_tile, EntityIdAllocator};
+use crate::{
+    catalogue,
+    error::Error,
+    types::{
+        level_data::LevelData, object::Object, object_property::ObjectProperty, prefab::Prefab,
+        vec2::Vec2,
+    },
+};
+use std::collections::HashMap;
+
+impl LevelData {
+    /// Returns the prefab with an id.
+    #[must_use]
+    pub fn prefab(&self, prefab_id: i32) -> Option<&Prefab> {
+        self.prefabs
+            .iter()
+            .find(|prefab| prefab.prefab_id == prefab_id)
+    }
+
+    /// Returns the entity ids of the groups that are instances of a prefab, in the order they are stored in.
+    #[must_use]
+    pub fn prefab_instances(&self, prefab_id: i32) -> Vec<i32> {
+        self.objects
+            .iter()
+            .filter(|object| is_instance(object) && object.prefab_id == prefab_id)
+            .map(|object| object.entity_id)
+            .collect()
+    }
+
+    /// Adds an instance of a prefab to the end of a layer, and returns the entity id of the group containing it.
+    ///
+    /// Every item of the prefab is copied with a new entity id. Groups and [`LinkedObjects`](ObjectProperty::LinkedObjects)
+    /// between the items are kept between the copies.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the prefab or the layer doesn't exist.
+    pub fn instantiate_prefab(
+        &mut self,
+        prefab_id: i32,
+        position: Vec2,
+        layer_id: i32,
+    ) -> Result<i32, Error> {
+        let items = self
+            .prefab(prefab_id)
+            .ok_or(Error::PrefabNotFound(prefab_id))?
+            .items
+            .clone();
+
+        if !self.layers.iter().any(|layer| layer.layer_id == layer_id) {
+            return Err(Error::LayerNotFound(layer_id));
+        }
+
+        let mut ids = EntityIdAllocator::new(self);
+        let root_id = ids.allocate();
+        let mapping = items
+            .iter()
+            .map(|item| (item.entity_id, ids.allocate()))
+            .collect::<HashMap<_, _>>();
+
+        let mut root = group_tile().new_object(root_id, position, layer_id);
+        root.prefab_id = prefab_id;
+        root.group_members = top_level_items(&items, &mapping);
+
+        self.objects.push(root);
+        self.objects.extend(
+            items
+                .iter()
+                .map(|item| instance_object(item, &mapping, prefab_id, root_id, layer_id)),
+        );
+
+        if let Some(layer) = self.layer_mut(layer_id) {
+            layer.children.push(root_id);
+        }
+
+        Ok(root_id)
+    }
+
+    /// Updates all instances of a prefab to match its items, and returns the number of instances.
+    ///
+    /// Objects copied from an item are overwritten with a new copy of the item, keeping their entity id. Items that
+    /// are missing from an instance are added to it, and objects whose item was removed from the prefab are removed
+    /// like with [`remove_object`](Self::remove_object). Changes made to the objects of an instance are lost, except
+    /// for changes to the group of the instance itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the prefab doesn't exist.
+    pub fn sync_prefab_instances(&mut self, prefab_id: i32) -> Result<usize, Error> {
+        let items = self
+            .prefab(prefab_id)
+            .ok_or(Error::PrefabNotFound(prefab_id))?
+            .items
+            .clone();
+
+        let instances = self.prefab_instances(prefab_id);
+
+        for &root_id in &instances {
+            self.sync_instance(root_id, prefab_id, &items);
+        }
+
+        Ok(instances.len())
+    }
+
+    /// Turns an instance of a prefab into plain objects, and returns the entity ids of the objects that were directly
+    /// in the instance.
+    ///
+    /// The objects lose their link to the prefab, and the group of the instance is [dissolved](Self::ungroup).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the object doesn't exist or isn't an instance of a prefab.
+    pub fn detach_prefab_instance(&mut self, root_id: i32) -> Result<Vec<i32>, Error> {
+        let root = self.object(root_id).ok_or(Error::ObjectNotFound(root_id))?;
+
+        if !is_instance(root) {
+            return Err(Error::InvalidEdit(format!(
+                "object {root_id} isn't an instance of a prefab"
+            )));
+        }
+
+        for id in self.with_members(root_id) {
+            if let Some(object) = self.object_mut(id) {
+                object.prefab_id = 0;
+                object.prefab_entity_id = 0;
+            }
+        }
+
+        self.ungroup(root_id)
+    }
+
+    fn sync_instance(&mut self, root_id: i32, prefab_id: i32, items: &[Object]) {
+        let Some(layer_id) = self.object(root_id).map(|root| root.in_layer) else {
+            return;
+        };
+
+        let objects = self.with_members(root_id).split_off(1);
+
+        // the objects of the instance that were copied from an item, by the entity id of the item
+        let mut mapping = HashMap::new();
+
+        for &id in &objects {
+            if let Some(object) = self.object(id) {
+                let is_item = object.prefab_id == prefab_id
+                    && items
+                        .iter()
+                        .any(|item| item.entity_id == object.prefab_entity_id);
+
+                if is_item {
+                    mapping.entry(object.prefab_entity_id).or_insert(id);
+                }
+            }
+        }
+
+        let stale = objects
+            .into_iter()
+            .filter(|id| !mapping.values().any(|kept| kept == id))
+            .collect::<Vec<_>>();
+
+        let mut ids = EntityIdAllocator::new(self);
+
+        for item in items {
+            mapping
+                .entry(item.entity_id)
+                .or_insert_with(|| ids.allocate());
+        }
+
+        for item in items {
+            let object = instance_object(item, &mapping, prefab_id, root_id, layer_id);
+
+            match self.object_mut(object.entity_id) {
+                Some(existing) => *existing = object,
+                None => self.objects.push(object),
+            }
+        }
+
+        if let Some(root) = self.object_mut(root_id) {
+            root.group_members = top_level_items(items, &mapping);
+        }
+
+        // detach stale objects first, so removing them doesn't remove objects that are still part of the instance
+        for &id in &stale {
+            if let Some(object) = self.object_mut(id) {
+                object.in_group = 0;
+                object.group_members.clear();
+            }
+        }
+
+        for id in stale {
+            self.remove_object(id);
+        }
+    }
+}
+
+/// Returns whether an object is the group of a prefab instance.
+const fn is_instance(object: &Object) -> bool {
+    object.tile_id == catalogue::GROUP && object.prefab_id != 0 && object.prefab_entity_id == 0
+}
+
+/// Returns the copies of the items that aren't in a group of the prefab.
+fn top_level_items(items: &[Object], mapping: &HashMap<i32, i32>) -> Vec<i32> {
+    items
+        .iter()
+        .filter(|item| !mapping.contains_key(&item.in_group))
+        .map(|item| mapping[&item.entity_id])
+        .collect()
+}
+
+/// Copies an item of a prefab into an instance, replacing references to items with references to their copies.
+fn instance_object(
+    item: &Object,
+    mapping: &HashMap<i32, i32>,
+    prefab_id: i32,
+    root_id: i32,
+    layer_id: i32,
+) -> Object {
+    let map = |id: &i32| mapping.get(id).copied().unwrap_or(*id);
+
+    let mut object = item.clone();
+    object.entity_id = mapping[&item.entity_id];
+    object.prefab_id = prefab_id;
+    object.prefab_entity_id = item.entity_id;
+    object.in_layer = layer_id;
+    object.in_group = mapping.get(&item.in_group).copied().unwrap_or(root_id);
+    object.group_members = item
+        .group_members
+        .iter()
+        .filter(|id| mapping.contains_key(id))
+        .map(map)
+        .collect();
+
+    for property in &mut object.properties {
+        if let ObjectProperty::LinkedObjects(linked) = property {
+            for id in linked {
+                *id = map(id);
+            }
+        }
+    }
+
+    object
+}
@@ -0,0 +1,226 @@
+//! Removing assets that aren't used anywhere in a level.
+
+use crate::{
+    diff::Location,
+    references::Target,
+    types::{
+        color::Color, dynamic_type::DynamicType, level_data::LevelData,
+        novascript::nova_value::NovaValue, object_property::ObjectProperty,
+    },
+    visit, Write,
+};
+use core::fmt::{self, Display, Formatter};
+use std::collections::HashSet;
+
+/// The assets that [`LevelData::prune_unused`] removes.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Unused {
+    /// The ids of the patterns that aren't used by any object, prefab item, brush or by the copied terrain.
+    pub patterns: Vec<i32>,
+    /// The ids of the prefabs that have no instances.
+    pub prefabs: Vec<i32>,
+    /// The ids of the brushes without any objects. Brushes aren't referenced by anything in a level, so only the ones
+    /// that can't paint anything are unused.
+    pub brushes: Vec<i32>,
+    /// The entries of the color palette that aren't used by any object, prefab item, brush, script or level setting,
+    /// and entries that are already in the palette earlier.
+    pub colors: Vec<Color>,
+    /// The number of bytes the unused assets take up in the uncompressed file.
+    pub bytes: u64,
+}
+
+impl Unused {
+    /// Returns whether nothing is unused.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+            && self.prefabs.is_empty()
+            && self.brushes.is_empty()
+            && self.colors.is_empty()
+    }
+}
+
+impl LevelData {
+    /// Returns the assets that [`LevelData::prune_unused`] would remove, without removing them.
+    #[must_use]
+    pub fn unused_assets(&self) -> Unused {
+        let index = self.reference_index();
+
+        let prefabs = self
+            .prefabs
+            .iter()
+            .map(|prefab| prefab.prefab_id)
+            .filter(|&id| !index.is_used(Target::Prefab(id)))
+            .collect::<Vec<_>>();
+
+        let brushes = self
+            .brushes
+            .iter()
+            .filter(|brush| brush.objects.is_empty())
+            .map(|brush| brush.brush_id)
+            .collect::<Vec<_>>();
+
+        // patterns that are only used by removed prefabs and brushes are unused as well
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|pattern| pattern.pattern_id)
+            .filter(|&id| {
+                id != self.custom_terrain_pattern_id
+                    && index.uses(Target::Pattern(id)).iter().all(|reference| {
+                        match reference.location {
+                            Location::Prefab(prefab) => prefabs.contains(&prefab),
+                            Location::Brush(brush) => brushes.contains(&brush),
+                            _ => false,
+                        }
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        let used_colors = self.used_colors(&prefabs);
+        let mut seen = HashSet::new();
+
+        let colors = self
+            .color_palette
+            .iter()
+            .flatten()
+            .filter(|color| !used_colors.contains(*color) || !seen.insert(**color))
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut bytes = 0;
+
+        for prefab in &self.prefabs {
+            if prefabs.contains(&prefab.prefab_id) {
+                bytes += encoded_len(prefab);
+            }
+        }
+
+        for brush in &self.brushes {
+            if brushes.contains(&brush.brush_id) {
+                bytes += encoded_len(brush);
+            }
+        }
+
+        for pattern in &self.patterns {
+            if patterns.contains(&pattern.pattern_id) {
+                bytes += encoded_len(pattern);
+            }
+        }
+
+        for color in &colors {
+            bytes += encoded_len(color);
+        }
+
+        Unused {
+            patterns,
+            prefabs,
+            brushes,
+            colors,
+            bytes,
+        }
+    }
+
+    /// Removes the patterns, prefabs, brushes and color palette entries that aren't used anywhere in the level, and
+    /// returns what was removed. See [`Unused`] for when an asset is unused.
+    ///
+    /// Use [`LevelData::unused_assets`] to find out what would be removed first.
+    pub fn prune_unused(&mut self) -> Unused {
+        let unused = self.unused_assets();
+        let used_colors = self.used_colors(&unused.prefabs);
+
+        self.prefabs
+            .retain(|prefab| !unused.prefabs.contains(&prefab.prefab_id));
+        self.brushes
+            .retain(|brush| !unused.brushes.contains(&brush.brush_id));
+        self.patterns
+            .retain(|pattern| !unused.patterns.contains(&pattern.pattern_id));
+
+        if let Some(palette) = &mut self.color_palette {
+            let mut seen = HashSet::new();
+
+            palette.retain(|color| used_colors.contains(color) && seen.insert(*color));
+        }
+
+        unused
+    }
+
+    /// Returns all colors used in the level, except by the items of some prefabs.
+    fn used_colors(&self, removed_prefabs: &[i32]) -> HashSet<Color> {
+        let mut colors = HashSet::from([
+            self.custom_background_color,
+            self.custom_terrain_color,
+            self.custom_terrain_secondary_color,
+            self.custom_terrain_border_color,
+        ]);
+
+        let properties = self
+            .objects
+            .iter()
+            .chain(
+                self.prefabs
+                    .iter()
+                    .filter(|prefab| !removed_prefabs.contains(&prefab.prefab_id))
+                    .flat_map(|prefab| &prefab.items),
+            )
+            .flat_map(|object| &object.properties)
+            .chain(
+                self.brushes
+                    .iter()
+                    .flat_map(|brush| &brush.objects)
+                    .flat_map(|object| &object.properties),
+            );
+
+        for property in properties {
+            match property {
+                ObjectProperty::Color(color)
+                | ObjectProperty::SecondaryColor(color)
+                | ObjectProperty::BorderColor(color)
+                | ObjectProperty::EditorColor(color)
+                | ObjectProperty::Color2(color)
+                | ObjectProperty::Color3(color)
+                | ObjectProperty::Color4(color)
+                | ObjectProperty::StartColorMultiplier(color)
+                | ObjectProperty::EndColorMultiplier(color) => {
+                    colors.insert(*color);
+                }
+                _ => {}
+            }
+        }
+
+        let mut add_constant = |value: &NovaValue| {
+            if value.dynamic_type == DynamicType::ColorConstant {
+                colors.insert(value.inner.color_value);
+            }
+        };
+
+        for variable in &self.global_variables {
+            visit::value(&variable.initial_value, &mut add_constant);
+        }
+
+        for script in &self.nova_scripts {
+            visit::script_values(script, &mut add_constant);
+        }
+
+        colors
+    }
+}
+
+/// Returns the number of bytes a value takes up when it's written.
+fn encoded_len(value: &impl Write) -> u64 {
+    let mut bytes = Vec::new();
+
+    // writing to a vector only fails if an image can't be encoded, which then isn't written either
+    value.write(&mut bytes).map_or(0, |()| bytes.len() as u64)
+}
+
+impl Display for Unused {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "unused patterns: {:?}", self.patterns)?;
+        writeln!(f, "unused prefabs: {:?}", self.prefabs)?;
+        writeln!(f, "unused brushes: {:?}", self.brushes)?;
+        writeln!(f, "unused palette colors: {}", self.colors.len())?;
+        write!(f, "bytes saved: {}", self.bytes)
+    }
+}
@@ -0,0 +1,694 @@
+//! Refactorings of variables, scripts and layers.
+//!
+//! Variables, scripts and layers are referenced by their id, so renaming them only has to keep their names unique.
+//! The other refactorings update all references to what they change.
+
+use crate::{
+    builder::IdAllocator,
+    diff::Location,
+    error::Error,
+    references::{Reference, Step, Target},
+    types::{
+        action_type::ActionType,
+        color::Color,
+        dynamic_type::DynamicType,
+        function_call::{CallParameter, FunctionCall},
+        level_data::LevelData,
+        novascript::{
+            action::Action, nova_value::NovaValue, static_type::StaticType, variable::Variable,
+            NovaScript,
+        },
+        vec2::Vec2,
+    },
+    visit,
+};
+use core::ops::Range;
+use std::collections::{HashMap, HashSet};
+
+/// A use of a variable that doesn't have the right type after [`LevelData::change_variable_type`].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct TypeError {
+    /// Where the variable is used.
+    pub reference: Reference,
+    /// The type that is expected there.
+    pub expected: StaticType,
+    /// The type that is found there.
+    pub found: StaticType,
+}
+
+impl LevelData {
+    /// Renames a global or local variable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `variable` isn't a variable of the level, or if another variable in the same scope already
+    /// has the name.
+    pub fn rename_variable(&mut self, variable: Target, name: &str) -> Result<(), Error> {
+        let (variables, index) = self.variables_mut(variable)?;
+
+        if variables
+            .iter()
+            .enumerate()
+            .any(|(other, variable)| other != index && variable.name == name)
+        {
+            return Err(Error::DuplicateName(name.to_owned()));
+        }
+
+        name.clone_into(&mut variables[index].name);
+
+        Ok(())
+    }
+
+    /// Renames a script.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no script with the id, or if another script already has the name.
+    pub fn rename_script(&mut self, script_id: i32, name: &str) -> Result<(), Error> {
+        if self
+            .nova_scripts
+            .iter()
+            .any(|script| script.script_id != script_id && script.script_name == name)
+        {
+            return Err(Error::DuplicateName(name.to_owned()));
+        }
+
+        name.clone_into(&mut self.script_mut(script_id)?.script_name);
+
+        Ok(())
+    }
+
+    /// Renames a layer.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no layer with the id, or if another layer already has the name.
+    pub fn rename_layer(&mut self, layer_id: i32, name: &str) -> Result<(), Error> {
+        if self
+            .layers
+            .iter()
+            .any(|layer| layer.layer_id != layer_id && layer.layer_name == name)
+        {
+            return Err(Error::DuplicateName(name.to_owned()));
+        }
+
+        let layer = self
+            .layers
+            .iter_mut()
+            .find(|layer| layer.layer_id == layer_id)
+            .ok_or(Error::LayerNotFound(layer_id))?;
+
+        name.clone_into(&mut layer.layer_name);
+
+        Ok(())
+    }
+
+    /// Changes the type of a global or local variable, and returns the uses of the variable that don't have the right
+    /// type anymore.
+    ///
+    /// All values that read the variable are changed to read a value of the new type. Reads are returned as type
+    /// errors if the type expected where they are used isn't the new type. That type is the type of the variable for
+    /// values that variables are set to, the type of the parameter for arguments of functions, default values and
+    /// activator parameters, and the old type of the variable everywhere else, since values have to have the type of
+    /// the slot they are in. Actions that set the variable to a value of another type are returned as well. The
+    /// initial value is reset to a default value of the new type if it has another type.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `variable` isn't a variable of the level.
+    pub fn change_variable_type(
+        &mut self,
+        variable: Target,
+        static_type: StaticType,
+    ) -> Result<Vec<TypeError>, Error> {
+        let (variables, index) = self.variables_mut(variable)?;
+        let changed = &mut variables[index];
+        let old_type = changed.static_type;
+
+        if old_type == static_type {
+            return Ok(Vec::new());
+        }
+
+        changed.static_type = static_type;
+
+        if changed.initial_value.dynamic_type.static_type() != static_type {
+            changed.initial_value = default_value(static_type);
+        }
+
+        let (scripts, variable_id) = match variable {
+            Target::LocalVariable { script, variable } => (Some(script), variable),
+            Target::GlobalVariable(variable) => (None, variable),
+            _ => unreachable!("variables_mut only accepts variables"),
+        };
+
+        let read_type = DynamicType::variable(static_type);
+        let mut retype = |value: &mut NovaValue| {
+            if value.dynamic_type.is_variable() && value.inner.int_value == variable_id {
+                value.dynamic_type = read_type;
+            }
+        };
+
+        for script in &mut self.nova_scripts {
+            let in_scope = match scripts {
+                Some(script_id) => script.script_id == script_id,
+                None => !has_local(script, variable_id),
+            };
+
+            if in_scope {
+                visit::script_values_mut(script, &mut retype);
+            }
+        }
+
+        if scripts.is_none() {
+            for global in &mut self.global_variables {
+                visit::value_mut(&mut global.initial_value, &mut retype);
+            }
+        }
+
+        let errors = self
+            .reference_index()
+            .uses(variable)
+            .iter()
+            .filter_map(|reference| {
+                if reference.path.last() != Some(&Step::Variable) {
+                    let expected = self.slot_type(reference).unwrap_or(old_type);
+
+                    return (expected != static_type).then(|| TypeError {
+                        reference: reference.clone(),
+                        expected,
+                        found: static_type,
+                    });
+                }
+
+                let Location::Script(script_id) = reference.location else {
+                    return None;
+                };
+
+                let script = self.script(script_id)?;
+
+                let value = match action_at(&script.actions, &reference.path)? {
+                    ActionType::SetVariable { value, .. }
+                    | ActionType::SetVariableOverTime { value, .. } => value.as_ref()?,
+                    _ => return None,
+                };
+
+                let found = value.dynamic_type.static_type();
+
+                (found != static_type).then(|| TypeError {
+                    reference: reference.clone(),
+                    expected: static_type,
+                    found,
+                })
+            })
+            .collect();
+
+        Ok(errors)
+    }
+
+    /// Turns a local variable of a script into a global variable, and returns the id of the global variable.
+    ///
+    /// The variable gets a new id, and all references to it in the script are changed to the new id. Note that
+    /// global variables keep their value between runs of the script, while local variables are reset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the script doesn't have the variable, or if a global variable already has its name.
+    pub fn make_variable_global(&mut self, script_id: i32, variable_id: i32) -> Result<i32, Error> {
+        let (variables, index) = self.variables_mut(Target::LocalVariable {
+            script: script_id,
+            variable: variable_id,
+        })?;
+        let name = variables[index].name.clone();
+
+        if self
+            .global_variables
+            .iter()
+            .any(|global| global.name == name)
+        {
+            return Err(Error::DuplicateName(name));
+        }
+
+        let new_id = IdAllocator::new(self).allocate();
+        let script = self.script_mut(script_id)?;
+
+        let mut variable = script.variables.remove(index);
+        variable.variable_id = new_id;
+
+        remap_variable(script, variable_id, new_id);
+        self.global_variables.push(variable);
+
+        Ok(new_id)
+    }
+
+    /// Replaces all calls to a function with the actions of the function, and returns the number of calls that were
+    /// replaced.
+    ///
+    /// The parameters of the function are replaced by the arguments of each call, or by their default values if the
+    /// call doesn't pass them. Arguments are evaluated once per call by the game, but every time they're used after
+    /// inlining, so arguments that change over time (like random numbers) can behave differently. Calls that don't
+    /// wait for the function run it at the same time as the caller, so they aren't replaced. The function itself is
+    /// kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no function with the id, if it calls itself, or if it has local variables, which
+    /// can be made global first with [`LevelData::make_variable_global`]. It's also an error if a script that calls
+    /// the function has a local variable with the id of a global variable the function uses, since the local variable
+    /// would hide it after inlining. Nothing is changed in these cases.
+    pub fn inline_function(&mut self, function_id: i32) -> Result<usize, Error> {
+        let function = self
+            .script(function_id)
+            .ok_or(Error::ScriptNotFound(function_id))?;
+
+        if !function.is_function {
+            return Err(Error::InvalidEdit(format!(
+                "script {function_id} isn't a function"
+            )));
+        }
+
+        if !function.variables.is_empty() {
+            return Err(Error::InvalidEdit(format!(
+                "function {function_id} has local variables"
+            )));
+        }
+
+        let mut recursive = false;
+        visit::actions(&function.actions, &mut |action| {
+            recursive |= matches!(action, ActionType::RunFunction { function } if function.id == function_id);
+        });
+
+        if recursive {
+            return Err(Error::InvalidEdit(format!(
+                "function {function_id} calls itself"
+            )));
+        }
+
+        let used = used_variables(function);
+
+        if let Some(script) = self.nova_scripts.iter().find(|script| {
+            script.script_id != function_id
+                && calls(&script.actions, function_id)
+                && script
+                    .variables
+                    .iter()
+                    .any(|variable| used.contains(&variable.variable_id))
+        }) {
+            return Err(Error::InvalidEdit(format!(
+                "a local variable of script {} hides a variable that function {function_id} uses",
+                script.script_id
+            )));
+        }
+
+        let function = function.clone();
+        let mut count = 0;
+
+        for script in &mut self.nova_scripts {
+            if script.script_id != function_id {
+                count += inline_calls(&mut script.actions, &function);
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Moves a range of the actions of a script into a new function, which is called in their place, and returns the
+    /// id of the function.
+    ///
+    /// The parameters of the script that the actions use become parameters of the function, and are passed by the
+    /// call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no script with the id, if the range is out of bounds, if a script already has the
+    /// name, or if the actions use local variables of the script, which can be made global first with
+    /// [`LevelData::make_variable_global`].
+    pub fn extract_function(
+        &mut self,
+        script_id: i32,
+        actions: Range<usize>,
+        name: &str,
+    ) -> Result<i32, Error> {
+        if self
+            .nova_scripts
+            .iter()
+            .any(|script| script.script_name == name)
+        {
+            return Err(Error::DuplicateName(name.to_owned()));
+        }
+
+        let function_id = IdAllocator::new(self).allocate();
+        let script = self.script_mut(script_id)?;
+
+        if actions.start > actions.end || actions.end > script.actions.len() {
+            return Err(Error::InvalidEdit(format!(
+                "actions {actions:?} are out of bounds"
+            )));
+        }
+
+        let locals = script
+            .variables
+            .iter()
+            .map(|variable| variable.variable_id)
+            .collect::<HashSet<_>>();
+
+        let mut extracted = script.actions[actions.clone()].to_vec();
+        let mut used_local = None;
+        let mut used_parameters = HashSet::new();
+
+        visit::actions_mut(&mut extracted, &mut |action| {
+            if let ActionType::SetVariable { variable, .. }
+            | ActionType::ResetVariable { variable }
+            | ActionType::SetVariableOverTime { variable, .. } = action
+            {
+                if locals.contains(variable) {
+                    used_local = Some(*variable);
+                }
+            }
+
+            for value in action.values_mut() {
+                visit::value_mut(value, &mut |value| {
+                    let id = value.inner.int_value;
+
+                    if value.dynamic_type.is_variable() && locals.contains(&id) {
+                        used_local = Some(id);
+                    } else if value.dynamic_type.is_parameter() {
+                        used_parameters.insert(id);
+                    }
+                });
+            }
+        });
+
+        if let Some(variable) = used_local {
+            return Err(Error::InvalidEdit(format!(
+                "the actions use local variable {variable}"
+            )));
+        }
+
+        let parameters = script
+            .parameters
+            .iter()
+            .filter(|parameter| used_parameters.contains(&parameter.parameter_id))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let call = ActionType::RunFunction {
+            function: FunctionCall {
+                id: function_id,
+                parameters: parameters
+                    .iter()
+                    .map(|parameter| CallParameter {
+                        parameter_id: parameter.parameter_id,
+                        value: NovaValue::new_int(
+                            DynamicType::parameter(parameter.static_type),
+                            parameter.parameter_id,
+                        ),
+                    })
+                    .collect(),
+            },
+        };
+
+        script.actions.splice(
+            actions,
+            [Action {
+                closed: false,
+                wait: true,
+                action_type: call,
+            }],
+        );
+
+        self.nova_scripts.push(NovaScript {
+            script_id: function_id,
+            script_name: name.to_owned(),
+            is_function: true,
+            activation_count: 0,
+            condition: NovaValue::new_bool(DynamicType::BoolConstant, true),
+            activation_list: Vec::new(),
+            parameters,
+            variables: Vec::new(),
+            actions: extracted,
+        });
+
+        Ok(function_id)
+    }
+
+    fn script(&self, script_id: i32) -> Option<&NovaScript> {
+        self.nova_scripts
+            .iter()
+            .find(|script| script.script_id == script_id)
+    }
+
+    fn script_mut(&mut self, script_id: i32) -> Result<&mut NovaScript, Error> {
+        self.nova_scripts
+            .iter_mut()
+            .find(|script| script.script_id == script_id)
+            .ok_or(Error::ScriptNotFound(script_id))
+    }
+
+    /// Returns the type of the value at a reference, if it's decided by something else than the value that is there.
+    fn slot_type(&self, reference: &Reference) -> Option<StaticType> {
+        let Location::Script(script_id) = reference.location else {
+            return match (reference.location, reference.path.as_slice()) {
+                (Location::GlobalVariable(variable_id), [Step::InitialValue]) => self
+                    .global_variables
+                    .iter()
+                    .find(|variable| variable.variable_id == variable_id)
+                    .map(|variable| variable.static_type),
+                _ => None,
+            };
+        };
+
+        let script = self.script(script_id)?;
+
+        match reference.path.as_slice() {
+            [Step::Condition] => Some(StaticType::Bool),
+            [Step::Parameter(index)] => Some(script.parameters.get(*index)?.static_type),
+            [Step::LocalVariable(index)] => Some(script.variables.get(*index)?.static_type),
+            [Step::Activator {
+                activator,
+                parameter,
+            }] => script
+                .activation_list
+                .get(*activator)?
+                .activator_type
+                .parameter_types()
+                .get(*parameter)
+                .copied(),
+            [.., Step::Action(_), Step::Value(index)] => {
+                match action_at(&script.actions, &reference.path)? {
+                    ActionType::SetVariable {
+                        variable,
+                        value: Some(_),
+                    }
+                    | ActionType::SetVariableOverTime {
+                        variable,
+                        value: Some(_),
+                        ..
+                    } if *index == 0 => script
+                        .variables
+                        .iter()
+                        .chain(&self.global_variables)
+                        .find(|candidate| candidate.variable_id == *variable)
+                        .map(|variable| variable.static_type),
+                    ActionType::RunFunction { function } => {
+                        let parameter_id = function.parameters.get(*index)?.parameter_id;
+
+                        self.script(function.id)?
+                            .parameters
+                            .iter()
+                            .find(|parameter| parameter.parameter_id == parameter_id)
+                            .map(|parameter| parameter.static_type)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the variables in the scope of a variable, and the index of the variable in them.
+    fn variables_mut(&mut self, variable: Target) -> Result<(&mut Vec<Variable>, usize), Error> {
+        let (variables, variable_id) = match variable {
+            Target::GlobalVariable(variable_id) => (&mut self.global_variables, variable_id),
+            Target::LocalVariable { script, variable } => {
+                (&mut self.script_mut(script)?.variables, variable)
+            }
+            _ => return Err(Error::InvalidEdit(format!("{variable:?} isn't a variable"))),
+        };
+
+        let index = variables
+            .iter()
+            .position(|variable| variable.variable_id == variable_id)
+            .ok_or(Error::VariableNotFound(variable_id))?;
+
+        Ok((variables, index))
+    }
+}
+
+fn has_local(script: &NovaScript, variable_id: i32) -> bool {
+    script
+        .variables
+        .iter()
+        .any(|variable| variable.variable_id == variable_id)
+}
+
+/// Returns a constant with the default value of a type.
+fn default_value(static_type: StaticType) -> NovaValue {
+    match static_type {
+        StaticType::Bool => NovaValue::new_bool(DynamicType::BoolConstant, false),
+        StaticType::Int => NovaValue::new_int(DynamicType::IntConstant, 0),
+        StaticType::Float => NovaValue::new_float(DynamicType::FloatConstant, 0.0),
+        StaticType::String => NovaValue::new_string(DynamicType::StringConstant, String::new()),
+        StaticType::Color => NovaValue::new_color(DynamicType::ColorConstant, Color::default()),
+        StaticType::Vector => NovaValue::new_vector(DynamicType::VectorConstant, Vec2::default()),
+        StaticType::Sound => NovaValue::new_string(DynamicType::SoundConstant, String::new()),
+        StaticType::Music => NovaValue::new_string(DynamicType::MusicConstant, String::new()),
+        StaticType::Object => NovaValue::new_int(DynamicType::ObjectConstant, 0),
+        StaticType::ObjectSet => {
+            NovaValue::new_int_list(DynamicType::ObjectSetConstant, Vec::new())
+        }
+        StaticType::Transition => NovaValue::new_int(DynamicType::TransitionConstant, 0),
+        StaticType::Easing => NovaValue::new_int(DynamicType::EasingConstant, 0),
+        StaticType::Sprite => NovaValue::new_string(DynamicType::SpriteConstant, String::new()),
+        StaticType::Script => NovaValue::new_int(DynamicType::ScriptConstant, 0),
+        StaticType::Layer => NovaValue::new_int(DynamicType::LayerConstant, 0),
+    }
+}
+
+/// Returns the action at the end of a path from a script, see [`Step`].
+fn action_at<'a>(actions: &'a [Action], path: &[Step]) -> Option<&'a ActionType> {
+    let mut actions = actions;
+    let mut action = None;
+
+    for step in path {
+        match step {
+            Step::Action(index) => action = Some(&actions.get(*index)?.action_type),
+            Step::Nested(index) => actions = action?.actions().get(*index)?,
+            _ => break,
+        }
+    }
+
+    action
+}
+
+fn remap_variable(script: &mut NovaScript, from: i32, to: i32) {
+    visit::script_values_mut(script, &mut |value| {
+        if value.dynamic_type.is_variable() && value.inner.int_value == from {
+            value.inner.int_value = to;
+        }
+    });
+
+    visit::actions_mut(&mut script.actions, &mut |action| {
+        if let ActionType::SetVariable { variable, .. }
+        | ActionType::ResetVariable { variable }
+        | ActionType::SetVariableOverTime { variable, .. } = action
+        {
+            if *variable == from {
+                *variable = to;
+            }
+        }
+    });
+}
+
+/// Replaces the calls to a function that wait for it in a list of actions and all nested lists.
+/// Returns the ids of the variables that a script reads or sets.
+fn used_variables(script: &NovaScript) -> HashSet<i32> {
+    let mut used = HashSet::new();
+
+    visit::script_values(script, &mut |value| {
+        if value.dynamic_type.is_variable() {
+            used.insert(value.inner.int_value);
+        }
+    });
+
+    visit::actions(&script.actions, &mut |action| {
+        if let ActionType::SetVariable { variable, .. }
+        | ActionType::ResetVariable { variable }
+        | ActionType::SetVariableOverTime { variable, .. } = action
+        {
+            used.insert(*variable);
+        }
+    });
+
+    used
+}
+
+/// Returns whether actions contain a call to a function that [`inline_calls`] replaces.
+fn calls(actions: &[Action], function_id: i32) -> bool {
+    actions.iter().any(|action| {
+        matches!(&action.action_type, ActionType::RunFunction { function } if function.id == function_id && action.wait)
+            || action
+                .action_type
+                .actions()
+                .into_iter()
+                .any(|nested| calls(nested, function_id))
+    })
+}
+
+fn inline_calls(actions: &mut Vec<Action>, function: &NovaScript) -> usize {
+    let mut count = 0;
+
+    for action in actions.iter_mut() {
+        for nested in action.action_type.actions_mut() {
+            count += inline_calls(nested, function);
+        }
+    }
+
+    let mut index = 0;
+
+    while index < actions.len() {
+        let action = &actions[index];
+
+        let ActionType::RunFunction { function: call } = &action.action_type else {
+            index += 1;
+            continue;
+        };
+
+        if call.id != function.script_id || !action.wait {
+            index += 1;
+            continue;
+        }
+
+        let arguments = function
+            .parameters
+            .iter()
+            .map(|parameter| {
+                let argument = call
+                    .parameters
+                    .iter()
+                    .find(|argument| argument.parameter_id == parameter.parameter_id)
+                    .map_or(&parameter.default_value, |argument| &argument.value);
+
+                (parameter.parameter_id, argument.clone())
+            })
+            .collect::<HashMap<_, _>>();
+
+        let mut body = function.actions.clone();
+        visit::actions_mut(&mut body, &mut |action| {
+            for value in action.values_mut() {
+                substitute(value, &arguments);
+            }
+        });
+
+        let len = body.len();
+        actions.splice(index..=index, body);
+        index += len;
+        count += 1;
+    }
+
+    count
+}
+
+/// Replaces the parameters in a value with arguments. Arguments aren't searched for parameters themselves, since they
+/// refer to the parameters of the caller.
+fn substitute(value: &mut NovaValue, arguments: &HashMap<i32, NovaValue>) {
+    if value.dynamic_type.is_parameter() {
+        if let Some(argument) = arguments.get(&value.inner.int_value) {
+            value.clone_from(argument);
+            return;
+        }
+    }
+
+    for sub_value in value.inner.sub_values.iter_mut().flatten() {
+        substitute(sub_value, arguments);
+    }
+}
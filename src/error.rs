@@ -17,6 +17,31 @@ pub enum Error {
     InvalidActionType(i32),
     /// The theme is invalid.
     InvalidTheme(String),
+    /// An SVG document couldn't be imported.
+    InvalidSvg(String),
+    /// A tile layer isn't encoded correctly.
+    InvalidTileLayer(String),
+    /// There is no object with this entity id.
+    ObjectNotFound(i32),
+    /// There is no layer with this id.
+    LayerNotFound(i32),
+    /// There is no prefab with this id.
+    PrefabNotFound(i32),
+    /// There is no variable with this id.
+    VariableNotFound(i32),
+    /// There is no script with this id.
+    ScriptNotFound(i32),
+    /// Something else already has this name.
+    DuplicateName(String),
+    /// An editing operation can't be done on the given objects.
+    InvalidEdit(String),
+    /// An operation of a patch can't be applied to a level.
+    PatchFailed {
+        /// The index of the operation in the patch.
+        operation: usize,
+        /// Why the operation can't be applied.
+        reason: String,
+    },
     /// An error occurred while reading a LEB128 value.
     LebRead(leb128::read::Error),
     /// An I/O error occurred while reading/writing to a file.
@@ -38,6 +63,18 @@ impl core::fmt::Display for Error {
             Self::InvalidOldActionType(value) => write!(f, "invalid old action type: {value}"),
             Self::InvalidActionType(value) => write!(f, "invalid action type: {value}"),
             Self::InvalidTheme(value) => write!(f, "invalid theme: {value}"),
+            Self::InvalidSvg(message) => write!(f, "invalid svg: {message}"),
+            Self::InvalidTileLayer(message) => write!(f, "invalid tile layer: {message}"),
+            Self::ObjectNotFound(id) => write!(f, "no object with entity id {id}"),
+            Self::LayerNotFound(id) => write!(f, "no layer with id {id}"),
+            Self::PrefabNotFound(id) => write!(f, "no prefab with id {id}"),
+            Self::VariableNotFound(id) => write!(f, "no variable with id {id}"),
+            Self::ScriptNotFound(id) => write!(f, "no script with id {id}"),
+            Self::DuplicateName(name) => write!(f, "the name {name:?} is already used"),
+            Self::InvalidEdit(message) => write!(f, "invalid edit: {message}"),
+            Self::PatchFailed { operation, reason } => {
+                write!(f, "patch operation {operation} failed: {reason}")
+            }
             Self::LebRead(err) => write!(f, "{err}"),
             Self::Io(err) => write!(f, "{err}"),
             #[cfg(feature = "image")]
@@ -0,0 +1,682 @@
+//! Geometry of terrain objects.
+//!
+//! Terrain shapes are stored in [`ObjectProperty::TerrainCorners`](crate::types::object_property::ObjectProperty)
+//! as a list of closed outlines, relative to the object. Outlines inside another outline are holes in it. This
+//! module turns them into world-space [`Polygon`]s with their holes attached, and provides the usual operations on
+//! them.
+//!
+//! Objects in a group are placed relative to their group, see [`Object::in_group`]. Methods that take only an
+//! object work in the space of its group, while the methods on [`LevelData`] also apply the groups around it.
+//!
+//! ```
+//! use exolvl::geometry::Polygon;
+//! use exolvl::types::vec2::Vec2;
+//!
+//! let square = |min: f32, max: f32| {
+//!     vec![Vec2::new(min, min), Vec2::new(max, min), Vec2::new(max, max), Vec2::new(min, max)]
+//! };
+//!
+//! let polygons = Polygon::from_rings(&[square(0.0, 4.0), square(1.0, 3.0)]);
+//!
+//! assert_eq!(polygons.len(), 1);
+//! assert_eq!(polygons[0].area(), 12.0);
+//! assert!(polygons[0].contains(Vec2::new(0.5, 0.5)));
+//! assert!(!polygons[0].contains(Vec2::new(2.0, 2.0)));
+//! ```
+
+use crate::types::{level_data::LevelData, object::Object, properties::Properties, vec2::Vec2};
+use std::collections::{HashMap, HashSet};
+
+/// An axis-aligned rectangle.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Rect {
+    /// The corner with the smallest coordinates.
+    pub min: Vec2,
+    /// The corner with the largest coordinates.
+    pub max: Vec2,
+}
+
+impl Rect {
+    /// Returns the smallest rectangle containing all the given points, or `None` if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = Vec2>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(Self::point(first), |rect, point| {
+            rect.union(Self::point(point))
+        }))
+    }
+
+    /// Returns a rectangle containing only `point`.
+    #[must_use]
+    pub const fn point(point: Vec2) -> Self {
+        Self {
+            min: point,
+            max: point,
+        }
+    }
+
+    /// Returns the smallest rectangle containing both `self` and `other`.
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self {
+            min: Vec2::new(
+                self.min.x.0.min(other.min.x.0),
+                self.min.y.0.min(other.min.y.0),
+            ),
+            max: Vec2::new(
+                self.max.x.0.max(other.max.x.0),
+                self.max.y.0.max(other.max.y.0),
+            ),
+        }
+    }
+
+    /// Returns the width of the rectangle.
+    #[must_use]
+    pub fn width(self) -> f32 {
+        self.max.x.0 - self.min.x.0
+    }
+
+    /// Returns the height of the rectangle.
+    #[must_use]
+    pub fn height(self) -> f32 {
+        self.max.y.0 - self.min.y.0
+    }
+
+    /// Returns the center of the rectangle.
+    #[must_use]
+    pub fn center(self) -> Vec2 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Returns whether `point` is inside the rectangle or on its edge.
+    #[must_use]
+    pub fn contains(self, point: Vec2) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    /// Returns whether the two rectangles overlap or touch.
+    #[must_use]
+    pub fn intersects(self, other: Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+    }
+
+    /// Returns the distance from `point` to the closest point of the rectangle, 0 if it is inside.
+    #[must_use]
+    pub fn distance_to(self, point: Vec2) -> f32 {
+        let dx = (self.min.x.0 - point.x.0)
+            .max(point.x.0 - self.max.x.0)
+            .max(0.0);
+        let dy = (self.min.y.0 - point.y.0)
+            .max(point.y.0 - self.max.y.0)
+            .max(0.0);
+
+        dx.hypot(dy)
+    }
+}
+
+/// The placement of an object in the world, used to turn object-relative coordinates into world coordinates.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Transform {
+    /// The position of the object.
+    pub position: Vec2,
+    /// The scale of the object.
+    pub scale: Vec2,
+    /// The counter-clockwise rotation of the object in degrees.
+    pub rotation: ordered_float::OrderedFloat<f32>,
+    /// Whether the object is mirrored horizontally.
+    pub flip_x: bool,
+    /// Whether the object is mirrored vertically.
+    pub flip_y: bool,
+}
+
+impl Transform {
+    /// Returns the transform of an object, including its [`FlipX`](Properties::flip_x) and
+    /// [`FlipY`](Properties::flip_y) properties.
+    #[must_use]
+    pub fn of(object: &Object) -> Self {
+        Self {
+            position: object.position,
+            scale: object.scale,
+            rotation: object.rotation,
+            flip_x: object.flip_x().unwrap_or(false),
+            flip_y: object.flip_y().unwrap_or(false),
+        }
+    }
+
+    /// Turns an object-relative point into a world-space point. The point is flipped, scaled, rotated and then
+    /// moved, in that order.
+    #[must_use]
+    pub fn apply(&self, point: Vec2) -> Vec2 {
+        let flipped = Vec2::new(
+            if self.flip_x { -point.x.0 } else { point.x.0 },
+            if self.flip_y { -point.y.0 } else { point.y.0 },
+        );
+        let scaled = Vec2::new(flipped.x.0 * self.scale.x.0, flipped.y.0 * self.scale.y.0);
+
+        scaled.rotate(self.rotation.0) + self.position
+    }
+}
+
+/// An affine transform, used to place objects in the world through all the groups around them.
+///
+/// Unlike a [`Transform`], this can represent a group with a non-uniform scale around a rotated object, which skews
+/// the object.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Affine {
+    /// Where the point `(1, 0)` is moved to, without the translation.
+    pub x_axis: Vec2,
+    /// Where the point `(0, 1)` is moved to, without the translation.
+    pub y_axis: Vec2,
+    /// Where the origin is moved to.
+    pub translation: Vec2,
+}
+
+impl Affine {
+    /// The transform that doesn't change anything.
+    pub const IDENTITY: Self = Self {
+        x_axis: Vec2::new(1.0, 0.0),
+        y_axis: Vec2::new(0.0, 1.0),
+        translation: Vec2::ZERO,
+    };
+
+    /// Transforms a point.
+    #[must_use]
+    pub fn apply(&self, point: Vec2) -> Vec2 {
+        self.x_axis * point.x.0 + self.y_axis * point.y.0 + self.translation
+    }
+
+    /// Returns the transform that applies `self` and then `outer`.
+    #[must_use]
+    pub fn then(&self, outer: &Self) -> Self {
+        let linear = |axis: Vec2| outer.x_axis * axis.x.0 + outer.y_axis * axis.y.0;
+
+        Self {
+            x_axis: linear(self.x_axis),
+            y_axis: linear(self.y_axis),
+            translation: outer.apply(self.translation),
+        }
+    }
+}
+
+impl From<Transform> for Affine {
+    fn from(transform: Transform) -> Self {
+        let origin = transform.apply(Vec2::ZERO);
+
+        Self {
+            x_axis: transform.apply(Vec2::new(1.0, 0.0)) - origin,
+            y_axis: transform.apply(Vec2::new(0.0, 1.0)) - origin,
+            translation: origin,
+        }
+    }
+}
+
+/// Returns the world transforms of a list of objects, in the same order.
+///
+/// The groups of the objects are looked up by entity id in the same list. Groups that aren't in the list are
+/// ignored, like groups that contain themselves.
+#[must_use]
+pub fn world_transforms(objects: &[Object]) -> Vec<Affine> {
+    // the first object with an id wins, like in `LevelData::object`
+    let by_id = objects
+        .iter()
+        .rev()
+        .map(|object| (object.entity_id, object))
+        .collect::<HashMap<_, _>>();
+
+    objects
+        .iter()
+        .map(|object| world_transform(object, |id| by_id.get(&id).copied()))
+        .collect()
+}
+
+fn world_transform<'a>(object: &Object, group: impl Fn(i32) -> Option<&'a Object>) -> Affine {
+    let mut transform = Affine::from(Transform::of(object));
+    let mut visited = HashSet::new();
+    let mut in_group = object.in_group;
+
+    while in_group != 0 && visited.insert(in_group) {
+        let Some(parent) = group(in_group) else {
+            break;
+        };
+
+        transform = transform.then(&Affine::from(Transform::of(parent)));
+        in_group = parent.in_group;
+    }
+
+    transform
+}
+
+impl Object {
+    /// Returns the bounding box of the object, in the space of its group. Use [`LevelData::object_bounds`] to get
+    /// the bounding box in the world.
+    ///
+    /// Terrain uses its [`TerrainCorners`](Properties::terrain_corners), objects with a
+    /// [`Width`](Properties::width) and [`Height`](Properties::height) or a [`Radius`](Properties::radius) use a box of
+    /// that size around their position, and all other objects are treated as a unit square, before applying their
+    /// scale and rotation.
+    #[must_use]
+    pub fn bounds(&self) -> Rect {
+        self.bounds_with(&Transform::of(self).into())
+    }
+
+    /// Returns the bounding box of the object placed with a transform, see [`Object::bounds`].
+    #[must_use]
+    pub fn bounds_with(&self, transform: &Affine) -> Rect {
+        if let Some(rings) = self.terrain_corners() {
+            let points = rings.iter().flatten().map(|&point| transform.apply(point));
+
+            if let Some(bounds) = Rect::from_points(points) {
+                return bounds;
+            }
+        }
+
+        let half_size = match (self.width(), self.height(), self.radius()) {
+            (Some(width), Some(height), _) => Vec2::new(width / 2.0, height / 2.0),
+            (_, _, Some(radius)) => Vec2::new(radius, radius),
+            _ => Vec2::new(0.5, 0.5),
+        };
+
+        let corners = [
+            Vec2::new(-half_size.x.0, -half_size.y.0),
+            Vec2::new(half_size.x.0, -half_size.y.0),
+            Vec2::new(half_size.x.0, half_size.y.0),
+            Vec2::new(-half_size.x.0, half_size.y.0),
+        ];
+
+        Rect::from_points(corners.map(|corner| transform.apply(corner)))
+            .unwrap_or_else(|| Rect::point(transform.translation))
+    }
+}
+
+impl LevelData {
+    /// Returns the bounding box of all objects in the level, or `None` if there are no objects.
+    #[must_use]
+    pub fn bounds(&self) -> Option<Rect> {
+        self.objects
+            .iter()
+            .zip(self.world_transforms())
+            .map(|(object, transform)| object.bounds_with(&transform))
+            .reduce(Rect::union)
+    }
+
+    /// Returns the transform that places an object in the world, through all the groups around it.
+    #[must_use]
+    pub fn world_transform(&self, object: &Object) -> Affine {
+        world_transform(object, |id| self.object(id))
+    }
+
+    /// Returns the world transforms of all objects, in the same order as [`LevelData::objects`].
+    #[must_use]
+    pub fn world_transforms(&self) -> Vec<Affine> {
+        world_transforms(&self.objects)
+    }
+
+    /// Returns the world-space bounding box of an object.
+    #[must_use]
+    pub fn object_bounds(&self, object: &Object) -> Rect {
+        object.bounds_with(&self.world_transform(object))
+    }
+
+    /// Returns the world-space polygons of a terrain object, see [`Polygon::from_terrain`].
+    #[must_use]
+    pub fn terrain_polygons(&self, object: &Object) -> Option<Vec<Polygon>> {
+        Polygon::from_terrain_with(object, &self.world_transform(object))
+    }
+}
+
+/// A polygon with holes.
+///
+/// Outlines are closed implicitly: the last point connects back to the first one.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Polygon {
+    /// The outer boundary.
+    pub outline: Vec<Vec2>,
+    /// The holes cut out of the polygon.
+    pub holes: Vec<Vec<Vec2>>,
+}
+
+/// Identifies an edge of a [`Polygon`]: the edge from point `index` to the next point of ring `ring`.
+///
+/// Ring 0 is the outline, ring `i + 1` is hole `i`.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Edge {
+    /// The ring the edge is part of.
+    pub ring: usize,
+    /// The index of the first point of the edge in its ring.
+    pub index: usize,
+}
+
+/// A place where two edges of a polygon cross.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct Intersection {
+    /// The first edge.
+    pub first: Edge,
+    /// The second edge.
+    pub second: Edge,
+    /// The point where the edges cross.
+    pub point: Vec2,
+}
+
+impl Polygon {
+    /// Groups a list of rings into polygons. Rings inside an odd number of other rings are holes of the smallest
+    /// ring containing them, all other rings are outlines.
+    ///
+    /// Rings with less than 3 points are ignored.
+    #[must_use]
+    pub fn from_rings(rings: &[Vec<Vec2>]) -> Vec<Self> {
+        let rings = rings
+            .iter()
+            .filter(|ring| ring.len() >= 3)
+            .collect::<Vec<_>>();
+
+        // the rings that contain each ring, by index
+        let containers = rings
+            .iter()
+            .enumerate()
+            .map(|(i, ring)| {
+                rings
+                    .iter()
+                    .enumerate()
+                    .filter(|&(j, other)| i != j && ring_contains_ring(other, ring))
+                    .map(|(j, _)| j)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut polygons = Vec::new();
+        let mut outline_polygon = vec![None; rings.len()];
+
+        for (i, ring) in rings.iter().enumerate() {
+            if containers[i].len() % 2 == 0 {
+                outline_polygon[i] = Some(polygons.len());
+                polygons.push(Self {
+                    outline: (*ring).clone(),
+                    holes: Vec::new(),
+                });
+            }
+        }
+
+        for (i, ring) in rings.iter().enumerate() {
+            if containers[i].len() % 2 == 1 {
+                // the direct parent is the container nested the deepest
+                let parent = containers[i]
+                    .iter()
+                    .copied()
+                    .max_by_key(|&j| containers[j].len())
+                    .and_then(|j| outline_polygon[j]);
+
+                if let Some(parent) = parent {
+                    polygons[parent].holes.push((*ring).clone());
+                }
+            }
+        }
+
+        polygons
+    }
+
+    /// Returns the polygons of a terrain object in the space of its group, or `None` if it has no
+    /// [`TerrainCorners`](Properties::terrain_corners). Use [`LevelData::terrain_polygons`] to get them in the world.
+    ///
+    /// The winding of the polygons is normalized, see [`Polygon::normalize_winding`].
+    #[must_use]
+    pub fn from_terrain(object: &Object) -> Option<Vec<Self>> {
+        Self::from_terrain_with(object, &Transform::of(object).into())
+    }
+
+    /// Returns the polygons of a terrain object placed with a transform, see [`Polygon::from_terrain`].
+    #[must_use]
+    pub fn from_terrain_with(object: &Object, transform: &Affine) -> Option<Vec<Self>> {
+        let rings = object
+            .terrain_corners()?
+            .iter()
+            .map(|ring| ring.iter().map(|&point| transform.apply(point)).collect())
+            .collect::<Vec<_>>();
+
+        let mut polygons = Self::from_rings(&rings);
+        polygons.iter_mut().for_each(Self::normalize_winding);
+
+        Some(polygons)
+    }
+
+    /// Returns all rings of the polygon, the outline followed by the holes.
+    pub fn rings(&self) -> impl Iterator<Item = &[Vec2]> {
+        core::iter::once(self.outline.as_slice()).chain(self.holes.iter().map(Vec::as_slice))
+    }
+
+    /// Returns the area of the polygon, excluding its holes.
+    #[must_use]
+    pub fn area(&self) -> f32 {
+        let holes = self
+            .holes
+            .iter()
+            .map(|hole| signed_area(hole).abs())
+            .sum::<f32>();
+
+        signed_area(&self.outline).abs() - holes
+    }
+
+    /// Returns the bounding box of the polygon.
+    #[must_use]
+    pub fn bounds(&self) -> Rect {
+        Rect::from_points(self.outline.iter().copied()).unwrap_or(Rect::point(Vec2::ZERO))
+    }
+
+    /// Returns whether `point` is inside the polygon and not inside one of its holes.
+    #[must_use]
+    pub fn contains(&self, point: Vec2) -> bool {
+        ring_contains(&self.outline, point)
+            && !self.holes.iter().any(|hole| ring_contains(hole, point))
+    }
+
+    /// Makes the outline counter-clockwise and the holes clockwise.
+    pub fn normalize_winding(&mut self) {
+        if signed_area(&self.outline) < 0.0 {
+            self.outline.reverse();
+        }
+
+        for hole in &mut self.holes {
+            if signed_area(hole) > 0.0 {
+                hole.reverse();
+            }
+        }
+    }
+
+    /// Returns every place where two edges of the polygon cross, including edges of different rings. Edges that
+    /// share a point because they are next to each other in a ring are not reported.
+    #[must_use]
+    pub fn self_intersections(&self) -> Vec<Intersection> {
+        let edges = self
+            .rings()
+            .enumerate()
+            .flat_map(|(ring, points)| {
+                (0..points.len()).map(move |index| {
+                    (
+                        Edge { ring, index },
+                        points[index],
+                        points[(index + 1) % points.len()],
+                        points.len(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut intersections = Vec::new();
+
+        for (i, &(first, a1, a2, len)) in edges.iter().enumerate() {
+            for &(second, b1, b2, _) in &edges[i + 1..] {
+                let adjacent = first.ring == second.ring
+                    && (second.index == first.index + 1
+                        || (first.index == 0 && second.index == len - 1));
+
+                if adjacent {
+                    continue;
+                }
+
+                if let Some(point) = segment_intersection(a1, a2, b1, b2) {
+                    intersections.push(Intersection {
+                        first,
+                        second,
+                        point,
+                    });
+                }
+            }
+        }
+
+        intersections
+    }
+
+    /// Returns whether no edges of the polygon cross, see [`Polygon::self_intersections`].
+    #[must_use]
+    pub fn is_simple(&self) -> bool {
+        self.self_intersections().is_empty()
+    }
+
+    /// Returns a simplified copy of the polygon, removing points that are closer than `tolerance` to the line
+    /// through their neighbours (Douglas-Peucker). Rings that would end up with less than 3 points are kept as
+    /// they are.
+    #[must_use]
+    pub fn simplify(&self, tolerance: f32) -> Self {
+        Self {
+            outline: simplify_ring(&self.outline, tolerance),
+            holes: self
+                .holes
+                .iter()
+                .map(|hole| simplify_ring(hole, tolerance))
+                .collect(),
+        }
+    }
+}
+
+/// Returns the signed area of a ring, positive if it is counter-clockwise.
+#[must_use]
+pub fn signed_area(ring: &[Vec2]) -> f32 {
+    let sum = ring
+        .iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(&a, &b)| a.cross(b))
+        .sum::<f32>();
+
+    sum / 2.0
+}
+
+/// Returns whether `point` is inside a ring, using the even-odd rule.
+#[must_use]
+pub fn ring_contains(ring: &[Vec2], point: Vec2) -> bool {
+    let (px, py) = (point.x.0, point.y.0);
+    let mut inside = false;
+
+    for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+        let (ax, ay, bx, by) = (a.x.0, a.y.0, b.x.0, b.y.0);
+
+        if (ay > py) != (by > py) && px < (bx - ax) * (py - ay) / (by - ay) + ax {
+            inside = !inside;
+        }
+    }
+
+    inside
+}
+
+fn ring_contains_ring(outer: &[Vec2], inner: &[Vec2]) -> bool {
+    signed_area(outer).abs() > signed_area(inner).abs()
+        && inner.iter().all(|&point| ring_contains(outer, point))
+}
+
+fn segment_intersection(a1: Vec2, a2: Vec2, b1: Vec2, b2: Vec2) -> Option<Vec2> {
+    let r = a2 - a1;
+    let s = b2 - b1;
+    let denominator = r.cross(s);
+    let offset = b1 - a1;
+
+    if denominator == 0.0 {
+        // parallel, report an overlap of collinear segments at the first shared point
+        if offset.cross(r) != 0.0 {
+            return None;
+        }
+
+        let length = r.length_squared();
+        let on_a = |point: Vec2| {
+            let t = (point - a1).dot(r) / length;
+            (0.0..=1.0).contains(&t)
+        };
+
+        return [b1, b2].into_iter().find(|&point| on_a(point));
+    }
+
+    let t = offset.cross(s) / denominator;
+    let u = offset.cross(r) / denominator;
+
+    ((0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)).then(|| a1 + r * t)
+}
+
+fn simplify_ring(ring: &[Vec2], tolerance: f32) -> Vec<Vec2> {
+    if ring.len() <= 3 {
+        return ring.to_vec();
+    }
+
+    // split the ring at the point furthest from the first one and simplify both halves as open chains
+    let far = (1..ring.len())
+        .max_by(|&a, &b| {
+            (ring[a] - ring[0])
+                .length_squared()
+                .total_cmp(&(ring[b] - ring[0]).length_squared())
+        })
+        .unwrap_or(1);
+
+    let mut keep = vec![false; ring.len()];
+    keep[0] = true;
+    keep[far] = true;
+
+    let mut closed = ring.to_vec();
+    closed.push(ring[0]);
+    let mut closed_keep = keep.clone();
+    closed_keep.push(true);
+
+    douglas_peucker(&closed, 0, far, tolerance, &mut closed_keep);
+    douglas_peucker(&closed, far, ring.len(), tolerance, &mut closed_keep);
+
+    let simplified = ring
+        .iter()
+        .zip(&closed_keep)
+        .filter(|&(_, &keep)| keep)
+        .map(|(&point, _)| point)
+        .collect::<Vec<_>>();
+
+    if simplified.len() < 3 {
+        ring.to_vec()
+    } else {
+        simplified
+    }
+}
+
+fn douglas_peucker(points: &[Vec2], start: usize, end: usize, tolerance: f32, keep: &mut [bool]) {
+    if end <= start + 1 {
+        return;
+    }
+
+    let (a, b) = (points[start], points[end]);
+    let distance = |point: Vec2| {
+        let line = b - a;
+        let length = line.length();
+
+        if length == 0.0 {
+            (point - a).length()
+        } else {
+            line.cross(point - a).abs() / length
+        }
+    };
+
+    let (index, max) = (start + 1..end)
+        .map(|i| (i, distance(points[i])))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap_or((start, 0.0));
+
+    if max > tolerance {
+        keep[index] = true;
+        douglas_peucker(points, start, index, tolerance, keep);
+        douglas_peucker(points, index, end, tolerance, keep);
+    }
+}
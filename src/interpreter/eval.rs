@@ -0,0 +1,513 @@
+use super::{Context, Hooks, Interpreter, RuntimeError, Value, PLAYER};
+use crate::types::{
+    color::Color, dynamic_type::DynamicType, novascript::nova_value::NovaValue, vec2::Vec2,
+};
+
+impl<'a, H: Hooks> Interpreter<'a, H> {
+    #[allow(
+        clippy::too_many_lines,
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss
+    )]
+    pub(super) fn eval(
+        &mut self,
+        ctx: &Context<'_, 'a>,
+        value: &NovaValue,
+    ) -> Result<Value, RuntimeError> {
+        use DynamicType as D;
+
+        let inner = &value.inner;
+        let ty = value.dynamic_type;
+
+        Ok(match ty {
+            D::BoolConstant => Value::Bool(inner.bool_value),
+            D::IntConstant => Value::Int(inner.int_value),
+            D::FloatConstant => Value::Float(inner.float_value.0),
+            D::StringConstant => Value::String(string(value)),
+            D::ColorConstant => Value::Color(inner.color_value),
+            D::VectorConstant => Value::Vector(inner.vector_value),
+            D::SoundConstant => Value::Sound(string(value)),
+            D::MusicConstant => Value::Music(string(value)),
+            D::SpriteConstant => Value::Sprite(string(value)),
+            D::ObjectConstant => Value::Object(inner.int_value),
+            D::ObjectSetConstant => {
+                Value::ObjectSet(inner.int_list_value.clone().unwrap_or_default())
+            }
+            D::TransitionConstant => Value::Transition(inner.int_value),
+            D::EasingConstant => Value::Easing(inner.int_value),
+            D::ScriptConstant => Value::Script(inner.int_value),
+            D::LayerConstant => Value::Layer(inner.int_value),
+
+            D::BoolVariable
+            | D::IntVariable
+            | D::FloatVariable
+            | D::StringVariable
+            | D::ColorVariable
+            | D::VectorVariable
+            | D::SoundVariable
+            | D::MusicVariable
+            | D::ObjectVariable
+            | D::ObjectSetVariable
+            | D::TransitionVariable
+            | D::EasingVariable
+            | D::SpriteVariable
+            | D::ScriptVariable
+            | D::LayerVariable => ctx
+                .scope
+                .and_then(|scope| scope.variables.get(&inner.int_value))
+                .or_else(|| self.globals.get(&inner.int_value))
+                .cloned()
+                .ok_or(RuntimeError::UnknownVariable(inner.int_value))?,
+
+            D::BoolParameter
+            | D::IntParameter
+            | D::FloatParameter
+            | D::StringParameter
+            | D::ColorParameter
+            | D::VectorParameter
+            | D::SoundParameter
+            | D::MusicParameter
+            | D::ObjectParameter
+            | D::ObjectSetParameter
+            | D::TransitionParameter
+            | D::EasingParameter
+            | D::SpriteParameter
+            | D::ScriptParameter
+            | D::LayerParameter => ctx
+                .scope
+                .and_then(|scope| scope.parameters.get(&inner.int_value))
+                .cloned()
+                .ok_or(RuntimeError::UnknownParameter(inner.int_value))?,
+
+            D::BoolNot => Value::Bool(!self.arg(ctx, value, 0)?.as_bool()?),
+            D::BoolAnd => Value::Bool(
+                self.arg(ctx, value, 0)?.as_bool()? && self.arg(ctx, value, 1)?.as_bool()?,
+            ),
+            D::BoolOr => Value::Bool(
+                self.arg(ctx, value, 0)?.as_bool()? || self.arg(ctx, value, 1)?.as_bool()?,
+            ),
+            D::BoolEqualBool
+            | D::BoolEqualNumber
+            | D::BoolEqualString
+            | D::BoolEqualColor
+            | D::BoolEqualVector
+            | D::BoolEqualObject => Value::Bool(self.equal(ctx, value)?),
+            D::BoolNotEqualBool
+            | D::BoolNotEqualNumber
+            | D::BoolNotEqualString
+            | D::BoolNotEqualColor
+            | D::BoolNotEqualVector
+            | D::BoolNotEqualObject => Value::Bool(!self.equal(ctx, value)?),
+            D::BoolLess | D::BoolLessOrEqual | D::BoolGreater | D::BoolGreaterOrEqual => {
+                let a = self.arg(ctx, value, 0)?.as_number()?;
+                let b = self.arg(ctx, value, 1)?.as_number()?;
+
+                Value::Bool(match ty {
+                    D::BoolLess => a < b,
+                    D::BoolLessOrEqual => a <= b,
+                    D::BoolGreater => a > b,
+                    _ => a >= b,
+                })
+            }
+            D::BoolObjectDead => {
+                let object = self.arg(ctx, value, 0)?.as_object()?;
+                Value::Bool(self.dead.contains(&object))
+            }
+            D::BoolInputPressed
+            | D::BoolInputPressedLeft
+            | D::BoolInputPressedRight
+            | D::BoolInputHeld
+            | D::BoolInputHeldLeft
+            | D::BoolInputHeldRight
+            | D::BoolInputReleased
+            | D::BoolInputReleasedLeft
+            | D::BoolInputReleasedRight
+            | D::BoolPointerDown
+            | D::BoolPointerHeld
+            | D::BoolPointerReleased => Value::Bool(self.hooks.input(ty)),
+
+            D::IntAdd
+            | D::IntSubtract
+            | D::IntMultiply
+            | D::IntDivide
+            | D::IntModulo
+            | D::IntMin
+            | D::IntMax => {
+                let a = self.arg(ctx, value, 0)?.as_int()?;
+                let b = self.arg(ctx, value, 1)?.as_int()?;
+
+                Value::Int(match ty {
+                    D::IntAdd => a.wrapping_add(b),
+                    D::IntSubtract => a.wrapping_sub(b),
+                    D::IntMultiply => a.wrapping_mul(b),
+                    D::IntDivide | D::IntModulo if b == 0 => {
+                        return Err(RuntimeError::DivisionByZero)
+                    }
+                    D::IntDivide => a.wrapping_div(b),
+                    D::IntModulo => a.wrapping_rem(b),
+                    D::IntMin => a.min(b),
+                    _ => a.max(b),
+                })
+            }
+            D::IntAbs => Value::Int(self.arg(ctx, value, 0)?.as_int()?.wrapping_abs()),
+            D::IntSign => Value::Int(self.arg(ctx, value, 0)?.as_int()?.signum()),
+            D::IntRound => Value::Int(self.arg(ctx, value, 0)?.as_float()?.round() as i32),
+            D::IntCeil => Value::Int(self.arg(ctx, value, 0)?.as_float()?.ceil() as i32),
+            D::IntFloor => Value::Int(self.arg(ctx, value, 0)?.as_float()?.floor() as i32),
+            D::IntRandom => {
+                let min = self.arg(ctx, value, 0)?.as_int()?;
+                let max = self.arg(ctx, value, 1)?.as_int()?;
+                let (min, max) = (min.min(max), min.max(max));
+                let span = u64::from(min.abs_diff(max)) + 1;
+                let offset = self.random() % span;
+
+                Value::Int((i64::from(min) + i64::try_from(offset).unwrap_or_default()) as i32)
+            }
+            D::IntRepeatCount => Value::Int(ctx.repeat_count()),
+            D::IntObjectSetCount => {
+                let len = self.arg(ctx, value, 0)?.as_object_set()?.len();
+                Value::Int(i32::try_from(len).unwrap_or(i32::MAX))
+            }
+            D::IntStringLength => {
+                let len = self.arg(ctx, value, 0)?.as_str()?.chars().count();
+                Value::Int(i32::try_from(len).unwrap_or(i32::MAX))
+            }
+
+            D::FloatAdd
+            | D::FloatSubtract
+            | D::FloatMultiply
+            | D::FloatDivide
+            | D::FloatModulo
+            | D::FloatMin
+            | D::FloatMax
+            | D::FloatPow => {
+                let a = self.arg(ctx, value, 0)?.as_float()?;
+                let b = self.arg(ctx, value, 1)?.as_float()?;
+
+                Value::Float(match ty {
+                    D::FloatAdd => a + b,
+                    D::FloatSubtract => a - b,
+                    D::FloatMultiply => a * b,
+                    D::FloatDivide => a / b,
+                    D::FloatModulo => a % b,
+                    D::FloatMin => a.min(b),
+                    D::FloatMax => a.max(b),
+                    _ => a.powf(b),
+                })
+            }
+            D::FloatAbs
+            | D::FloatSign
+            | D::FloatRound
+            | D::FloatCeil
+            | D::FloatFloor
+            | D::FloatCos
+            | D::FloatSin
+            | D::FloatTan
+            | D::FloatAcos
+            | D::FloatAsin
+            | D::FloatAtan
+            | D::FloatSqrt => {
+                let a = self.arg(ctx, value, 0)?.as_float()?;
+
+                Value::Float(match ty {
+                    D::FloatAbs => a.abs(),
+                    D::FloatSign if a == 0.0 => 0.0,
+                    D::FloatSign => a.signum(),
+                    D::FloatRound => a.round(),
+                    D::FloatCeil => a.ceil(),
+                    D::FloatFloor => a.floor(),
+                    D::FloatCos => a.to_radians().cos(),
+                    D::FloatSin => a.to_radians().sin(),
+                    D::FloatTan => a.to_radians().tan(),
+                    D::FloatAcos => a.acos().to_degrees(),
+                    D::FloatAsin => a.asin().to_degrees(),
+                    D::FloatAtan => a.atan().to_degrees(),
+                    _ => a.sqrt(),
+                })
+            }
+            D::FloatRandom => {
+                let min = self.arg(ctx, value, 0)?.as_float()?;
+                let max = self.arg(ctx, value, 1)?.as_float()?;
+                let t = self.rng.unit();
+
+                Value::Float((max - min).mul_add(t, min))
+            }
+            D::FloatTime | D::FloatLevelTime => Value::Float(self.time as f32),
+            D::FloatRoundDecimals => {
+                let a = self.arg(ctx, value, 0)?.as_float()?;
+                let decimals = self.arg(ctx, value, 1)?.as_int()?;
+                let factor = 10f32.powi(decimals);
+
+                Value::Float((a * factor).round() / factor)
+            }
+            D::FloatVectorX => Value::Float(self.arg(ctx, value, 0)?.as_vector()?.x.0),
+            D::FloatVectorY => Value::Float(self.arg(ctx, value, 0)?.as_vector()?.y.0),
+            D::FloatVectorLength => Value::Float(self.arg(ctx, value, 0)?.as_vector()?.length()),
+            D::FloatVectorLengthSqr => {
+                Value::Float(self.arg(ctx, value, 0)?.as_vector()?.length_squared())
+            }
+            D::FloatVectorAngle => {
+                let v = self.arg(ctx, value, 0)?.as_vector()?;
+                Value::Float(v.y.0.atan2(v.x.0).to_degrees())
+            }
+            D::FloatVectorDistance
+            | D::FloatVectorDistanceSqr
+            | D::FloatVectorDot
+            | D::FloatVectorAngleBetween => {
+                let a = self.arg(ctx, value, 0)?.as_vector()?;
+                let b = self.arg(ctx, value, 1)?.as_vector()?;
+
+                Value::Float(match ty {
+                    D::FloatVectorDistance => (a - b).length(),
+                    D::FloatVectorDistanceSqr => (a - b).length_squared(),
+                    D::FloatVectorDot => a.dot(b),
+                    _ => a.cross(b).atan2(a.dot(b)).to_degrees().abs(),
+                })
+            }
+            D::FloatColorR | D::FloatColorG | D::FloatColorB | D::FloatColorA => {
+                let color = self.arg(ctx, value, 0)?.as_color()?;
+
+                Value::Float(match ty {
+                    D::FloatColorR => color.r.0,
+                    D::FloatColorG => color.g.0,
+                    D::FloatColorB => color.b.0,
+                    _ => color.a.0,
+                })
+            }
+            D::FloatObjectRotation => {
+                let object = self.arg(ctx, value, 0)?.as_object()?;
+                Value::Float(self.object(object)?.rotation.0)
+            }
+
+            D::StringFromInt => Value::String(self.arg(ctx, value, 0)?.as_int()?.to_string()),
+            D::StringFromFloat => Value::String(self.arg(ctx, value, 0)?.as_float()?.to_string()),
+            D::StringConcat => {
+                let mut a = self.arg(ctx, value, 0)?.as_str()?.to_owned();
+                a.push_str(self.arg(ctx, value, 1)?.as_str()?);
+                Value::String(a)
+            }
+            D::StringSubstring => {
+                let s = self.arg(ctx, value, 0)?;
+                let start = usize::try_from(self.arg(ctx, value, 1)?.as_int()?).unwrap_or(0);
+                let len = usize::try_from(self.arg(ctx, value, 2)?.as_int()?).unwrap_or(0);
+
+                Value::String(s.as_str()?.chars().skip(start).take(len).collect())
+            }
+            D::StringObjectTag => {
+                let object = self.arg(ctx, value, 0)?.as_object()?;
+                Value::String(self.object(object)?.tag.clone())
+            }
+
+            D::ColorValues => {
+                let r = self.arg(ctx, value, 0)?.as_float()?;
+                let g = self.arg(ctx, value, 1)?.as_float()?;
+                let b = self.arg(ctx, value, 2)?.as_float()?;
+                let a = self.arg(ctx, value, 3)?.as_float()?;
+
+                Value::Color(Color::new(r, g, b, a))
+            }
+
+            D::VectorValues => {
+                let x = self.arg(ctx, value, 0)?.as_float()?;
+                let y = self.arg(ctx, value, 1)?.as_float()?;
+
+                Value::Vector(Vec2::new(x, y))
+            }
+            D::VectorAdd | D::VectorSubtract | D::VectorReflect => {
+                let a = self.arg(ctx, value, 0)?.as_vector()?;
+                let b = self.arg(ctx, value, 1)?.as_vector()?;
+
+                Value::Vector(match ty {
+                    D::VectorAdd => a + b,
+                    D::VectorSubtract => a - b,
+                    _ => {
+                        let normal = b.normalize();
+                        a - normal * (2.0 * a.dot(normal))
+                    }
+                })
+            }
+            D::VectorMultiply | D::VectorDivide | D::VectorRotate => {
+                let a = self.arg(ctx, value, 0)?.as_vector()?;
+                let b = self.arg(ctx, value, 1)?.as_float()?;
+
+                Value::Vector(match ty {
+                    D::VectorMultiply => a * b,
+                    D::VectorDivide => a / b,
+                    _ => a.rotate(b),
+                })
+            }
+            D::VectorNormalize => Value::Vector(self.arg(ctx, value, 0)?.as_vector()?.normalize()),
+            D::VectorPerpendicular => {
+                let v = self.arg(ctx, value, 0)?.as_vector()?;
+                Value::Vector(Vec2::new(-v.y.0, v.x.0))
+            }
+            D::VectorObjectPos => {
+                let object = self.arg(ctx, value, 0)?.as_object()?;
+
+                Value::Vector(
+                    self.position(object)
+                        .ok_or(RuntimeError::UnknownObject(object))?,
+                )
+            }
+            D::VectorObjectScale => {
+                let object = self.arg(ctx, value, 0)?.as_object()?;
+                Value::Vector(self.object(object)?.scale)
+            }
+
+            D::ObjectPlayer => Value::Object(PLAYER),
+            D::ObjectFirstFromSet => {
+                let set = self.arg(ctx, value, 0)?;
+                Value::Object(element(set.as_object_set()?, 0)?)
+            }
+            D::ObjectElementFromSet => {
+                let set = self.arg(ctx, value, 0)?;
+                let index = self.arg(ctx, value, 1)?.as_int()?;
+                Value::Object(element(set.as_object_set()?, index)?)
+            }
+            D::ObjectRandomFromSet => {
+                let set = self.arg(ctx, value, 0)?;
+                let set = set.as_object_set()?;
+                let index = if set.is_empty() {
+                    0
+                } else {
+                    (self.random() % set.len() as u64) as i32
+                };
+                Value::Object(element(set, index)?)
+            }
+            D::ObjectRepeatObject => Value::Object(
+                ctx.repeat_object()
+                    .ok_or(RuntimeError::IndexOutOfRange { index: 0, len: 0 })?,
+            ),
+            D::ObjectParent => {
+                let object = self.arg(ctx, value, 0)?.as_object()?;
+                Value::Object(self.object(object)?.in_group)
+            }
+
+            D::ObjectSetConcat
+            | D::ObjectSetUnion
+            | D::ObjectSetIntersection
+            | D::ObjectSetDifference => {
+                let a = self.arg(ctx, value, 0)?;
+                let b = self.arg(ctx, value, 1)?;
+                let (a, b) = (a.as_object_set()?, b.as_object_set()?);
+
+                Value::ObjectSet(match ty {
+                    D::ObjectSetConcat => a.iter().chain(b).copied().collect(),
+                    D::ObjectSetUnion => {
+                        let mut set = Vec::new();
+                        for object in a.iter().chain(b) {
+                            if !set.contains(object) {
+                                set.push(*object);
+                            }
+                        }
+                        set
+                    }
+                    D::ObjectSetIntersection => {
+                        a.iter().filter(|o| b.contains(o)).copied().collect()
+                    }
+                    _ => a.iter().filter(|o| !b.contains(o)).copied().collect(),
+                })
+            }
+            D::ObjectSetRemoveAtIndex => {
+                let set = self.arg(ctx, value, 0)?;
+                let index = self.arg(ctx, value, 1)?.as_int()?;
+                let mut set = set.as_object_set()?.to_vec();
+
+                element(&set, index)?;
+                set.remove(usize::try_from(index).unwrap_or_default());
+
+                Value::ObjectSet(set)
+            }
+            D::ObjectSetPlayers => Value::ObjectSet(vec![PLAYER]),
+            D::ObjectSetAllObjects => {
+                Value::ObjectSet(self.level.objects.iter().map(|o| o.entity_id).collect())
+            }
+            D::ObjectSetObjectsWithTag => {
+                let tag = self.arg(ctx, value, 0)?;
+                let tag = tag.as_str()?;
+
+                Value::ObjectSet(
+                    self.level
+                        .objects
+                        .iter()
+                        .filter(|o| o.tag == tag)
+                        .map(|o| o.entity_id)
+                        .collect(),
+                )
+            }
+            D::ObjectSetObjectsInLayer => {
+                let layer = self.arg(ctx, value, 0)?.as_id()?;
+
+                Value::ObjectSet(
+                    self.level
+                        .objects
+                        .iter()
+                        .filter(|o| o.in_layer == layer)
+                        .map(|o| o.entity_id)
+                        .collect(),
+                )
+            }
+            D::ObjectSetChildren => {
+                let object = self.arg(ctx, value, 0)?.as_object()?;
+                Value::ObjectSet(self.object(object)?.group_members.clone())
+            }
+
+            other => return Err(RuntimeError::Unsupported(other)),
+        })
+    }
+
+    fn arg(
+        &mut self,
+        ctx: &Context<'_, 'a>,
+        value: &NovaValue,
+        index: usize,
+    ) -> Result<Value, RuntimeError> {
+        let operand = value
+            .inner
+            .sub_values
+            .as_ref()
+            .and_then(|values| values.get(index))
+            .ok_or(RuntimeError::MalformedValue(value.dynamic_type))?;
+
+        self.eval(ctx, operand)
+    }
+
+    #[allow(clippy::float_cmp)]
+    fn equal(&mut self, ctx: &Context<'_, 'a>, value: &NovaValue) -> Result<bool, RuntimeError> {
+        use DynamicType as D;
+
+        let a = self.arg(ctx, value, 0)?;
+        let b = self.arg(ctx, value, 1)?;
+
+        Ok(match value.dynamic_type {
+            D::BoolEqualBool | D::BoolNotEqualBool => a.as_bool()? == b.as_bool()?,
+            D::BoolEqualNumber | D::BoolNotEqualNumber => a.as_number()? == b.as_number()?,
+            D::BoolEqualString | D::BoolNotEqualString => a.as_str()? == b.as_str()?,
+            D::BoolEqualColor | D::BoolNotEqualColor => a.as_color()? == b.as_color()?,
+            D::BoolEqualVector | D::BoolNotEqualVector => a.as_vector()? == b.as_vector()?,
+            _ => a.as_object()? == b.as_object()?,
+        })
+    }
+
+    fn object(&self, entity_id: i32) -> Result<&'a crate::types::object::Object, RuntimeError> {
+        self.objects
+            .get(&entity_id)
+            .copied()
+            .ok_or(RuntimeError::UnknownObject(entity_id))
+    }
+}
+
+fn string(value: &NovaValue) -> String {
+    value.inner.string_value.clone().unwrap_or_default()
+}
+
+fn element(set: &[i32], index: i32) -> Result<i32, RuntimeError> {
+    usize::try_from(index)
+        .ok()
+        .and_then(|i| set.get(i))
+        .copied()
+        .ok_or(RuntimeError::IndexOutOfRange {
+            index,
+            len: set.len(),
+        })
+}
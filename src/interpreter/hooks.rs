@@ -0,0 +1,61 @@
+use super::value::Value;
+use crate::types::{dynamic_type::DynamicType, novascript::easing::Easing, vec2::Vec2};
+
+/// An action performed by a script that affects the game world.
+///
+/// The interpreter doesn't simulate the world itself, it reports these to its [`Hooks`] instead.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Effect {
+    /// A [`Move`](crate::types::action_type::ActionType::Move) action.
+    Move {
+        objects: Vec<i32>,
+        position: Vec2,
+        global: bool,
+        duration: f32,
+        easing: Easing,
+    },
+    /// A [`Kill`](crate::types::action_type::ActionType::Kill) action.
+    Kill { objects: Vec<i32> },
+    /// A [`PlaySound`](crate::types::action_type::ActionType::PlaySound) action.
+    PlaySound {
+        sound: String,
+        volume: f32,
+        pitch: f32,
+    },
+    /// Any other action that isn't handled by the interpreter itself.
+    ///
+    /// `action_type` is the id of the action type as stored in the file and `arguments` are its evaluated
+    /// arguments, in the same order as [`ActionType::values`](crate::types::action_type::ActionType::values).
+    Other {
+        action_type: i32,
+        arguments: Vec<Value>,
+    },
+}
+
+/// Hooks that connect the interpreter to a (simulated) game world.
+pub trait Hooks {
+    /// Called when a script performs an action that affects the world. `time` is the simulated time in seconds.
+    fn effect(&mut self, time: f64, effect: Effect);
+
+    /// Returns whether the given input condition (e.g. [`DynamicType::BoolInputPressed`]) is currently true.
+    ///
+    /// By default no input is ever pressed.
+    fn input(&mut self, condition: DynamicType) -> bool {
+        let _ = condition;
+        false
+    }
+}
+
+/// A [`Hooks`] implementation that records every effect, useful for asserting on them in tests.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Recorder {
+    /// The recorded effects along with the time at which they happened.
+    pub effects: Vec<(f64, Effect)>,
+}
+
+impl Hooks for Recorder {
+    fn effect(&mut self, time: f64, effect: Effect) {
+        self.effects.push((time, effect));
+    }
+}
@@ -0,0 +1,1011 @@
+//! A headless interpreter for `NovaScript`, for testing level logic without running the game.
+//!
+//! The interpreter evaluates [`NovaValue`] expressions and steps through the actions of [`NovaScript`]s on a
+//! simulated clock. It only models the script state (variables, parameters, loops, waits and function calls):
+//! actions that affect the game world, such as moving or killing objects, are reported to a [`Hooks`]
+//! implementation as [`Effect`]s instead.
+//!
+//! ```
+//! use exolvl::interpreter::{Interpreter, Recorder};
+//! use exolvl::types::level_data::LevelData;
+//!
+//! let level = LevelData::default();
+//! let mut interpreter = Interpreter::new(&level, Recorder::default()).unwrap();
+//!
+//! interpreter.advance(1.0).unwrap();
+//!
+//! assert!(interpreter.is_idle());
+//! assert!(interpreter.hooks().effects.is_empty());
+//! ```
+
+mod eval;
+mod hooks;
+mod value;
+
+pub use hooks::{Effect, Hooks, Recorder};
+pub use value::Value;
+
+use crate::{
+    rng::Rng,
+    types::{
+        action_type::ActionType,
+        dynamic_type::DynamicType,
+        level_data::LevelData,
+        novascript::{
+            action::Action, activator::ActivatorType, easing::Easing, nova_value::NovaValue,
+            static_type::StaticType, NovaScript,
+        },
+        object::Object,
+        vec2::Vec2,
+    },
+};
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+/// The entity id the interpreter uses for the player object.
+pub const PLAYER: i32 = -1;
+
+/// Errors that can occur while running a script.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuntimeError {
+    /// The expression type isn't supported by the interpreter.
+    Unsupported(DynamicType),
+    /// The expression doesn't have the operands its type requires.
+    MalformedValue(DynamicType),
+    /// A value had a different type than expected.
+    TypeMismatch {
+        expected: StaticType,
+        found: StaticType,
+    },
+    /// A variable id doesn't refer to a global variable or a variable of the running script.
+    UnknownVariable(i32),
+    /// A parameter id doesn't refer to a parameter of the running function.
+    UnknownParameter(i32),
+    /// A script id doesn't refer to a script in the level.
+    UnknownScript(i32),
+    /// An object id doesn't refer to an object in the level.
+    UnknownObject(i32),
+    /// An index into an object set was out of range.
+    IndexOutOfRange { index: i32, len: usize },
+    /// An integer division or modulo by zero.
+    DivisionByZero,
+    /// A script executed too many actions without waiting, e.g. because of an infinite loop.
+    StepLimitExceeded,
+    /// A duration to advance the clock by was negative or NaN.
+    InvalidDuration,
+}
+
+impl core::fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Unsupported(ty) => write!(f, "unsupported expression: {ty:?}"),
+            Self::MalformedValue(ty) => write!(f, "malformed expression: {ty:?}"),
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "type mismatch: expected {expected:?}, found {found:?}")
+            }
+            Self::UnknownVariable(id) => write!(f, "unknown variable: {id}"),
+            Self::UnknownParameter(id) => write!(f, "unknown parameter: {id}"),
+            Self::UnknownScript(id) => write!(f, "unknown script: {id}"),
+            Self::UnknownObject(id) => write!(f, "unknown object: {id}"),
+            Self::IndexOutOfRange { index, len } => {
+                write!(
+                    f,
+                    "index {index} out of range for object set of length {len}"
+                )
+            }
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::StepLimitExceeded => write!(f, "step limit exceeded"),
+            Self::InvalidDuration => write!(f, "invalid duration"),
+        }
+    }
+}
+
+impl core::error::Error for RuntimeError {}
+
+/// A `NovaScript` interpreter running the scripts of a level.
+#[derive(Debug)]
+pub struct Interpreter<'a, H> {
+    level: &'a LevelData,
+    hooks: H,
+    objects: HashMap<i32, &'a Object>,
+    scripts: HashMap<i32, &'a NovaScript>,
+    globals: HashMap<i32, Value>,
+    positions: HashMap<i32, Vec2>,
+    dead: HashSet<i32>,
+    activations: HashMap<i32, i32>,
+    tasks: Vec<Task<'a>>,
+    stopped: Vec<i32>,
+    time: f64,
+    frame_duration: f64,
+    step_limit: usize,
+    rng: Rng,
+}
+
+impl<'a, H: Hooks> Interpreter<'a, H> {
+    /// Creates a new interpreter for the given level, with all global variables set to their initial values.
+    ///
+    /// No scripts are running initially, use [`Interpreter::trigger`] or [`Interpreter::start_script`] to start them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the initial value of a global variable can't be evaluated.
+    pub fn new(level: &'a LevelData, hooks: H) -> Result<Self, RuntimeError> {
+        let mut interpreter = Self::without_globals(level, hooks);
+
+        for variable in &level.global_variables {
+            let value = interpreter.evaluate(&variable.initial_value)?;
+            interpreter.globals.insert(variable.variable_id, value);
+        }
+
+        Ok(interpreter)
+    }
+
+    /// Creates a new interpreter without evaluating the initial values of the global variables.
+    pub(crate) fn without_globals(level: &'a LevelData, hooks: H) -> Self {
+        Self {
+            level,
+            hooks,
+            objects: level.objects.iter().map(|o| (o.entity_id, o)).collect(),
+            scripts: level
+                .nova_scripts
+                .iter()
+                .map(|s| (s.script_id, s))
+                .collect(),
+            globals: HashMap::new(),
+            positions: HashMap::new(),
+            dead: HashSet::new(),
+            activations: HashMap::new(),
+            tasks: Vec::new(),
+            stopped: Vec::new(),
+            time: 0.0,
+            frame_duration: 1.0 / 60.0,
+            step_limit: 100_000,
+            rng: Rng::new(0),
+        }
+    }
+
+    /// Sets the seed used for random expressions like [`DynamicType::IntRandom`].
+    #[must_use]
+    pub const fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = Rng::new(seed);
+        self
+    }
+
+    /// Sets the duration of a frame in seconds, used by [`ActionType::WaitFrames`]. Defaults to 1/60.
+    #[must_use]
+    pub const fn with_frame_duration(mut self, frame_duration: f64) -> Self {
+        self.frame_duration = frame_duration;
+        self
+    }
+
+    /// Sets how many actions a script may execute in a row without waiting before
+    /// [`RuntimeError::StepLimitExceeded`] is returned. Defaults to 100000.
+    #[must_use]
+    pub const fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    /// Returns the simulated time in seconds.
+    #[must_use]
+    pub const fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Returns the hooks.
+    #[must_use]
+    pub const fn hooks(&self) -> &H {
+        &self.hooks
+    }
+
+    /// Returns the hooks mutably.
+    pub const fn hooks_mut(&mut self) -> &mut H {
+        &mut self.hooks
+    }
+
+    /// Consumes the interpreter and returns the hooks.
+    pub fn into_hooks(self) -> H {
+        self.hooks
+    }
+
+    /// Returns the current value of a global variable.
+    #[must_use]
+    pub fn global(&self, variable_id: i32) -> Option<&Value> {
+        self.globals.get(&variable_id)
+    }
+
+    /// Sets the value of a global variable.
+    pub fn set_global(&mut self, variable_id: i32, value: Value) {
+        self.globals.insert(variable_id, value);
+    }
+
+    /// Returns the current position of an object, taking previous [`Effect::Move`]s into account.
+    #[must_use]
+    pub fn position(&self, entity_id: i32) -> Option<Vec2> {
+        self.positions
+            .get(&entity_id)
+            .copied()
+            .or_else(|| self.objects.get(&entity_id).map(|o| o.position))
+    }
+
+    /// Returns whether an object has been killed by a script.
+    #[must_use]
+    pub fn is_dead(&self, entity_id: i32) -> bool {
+        self.dead.contains(&entity_id)
+    }
+
+    /// Returns whether no scripts are running anymore.
+    #[must_use]
+    pub const fn is_idle(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Returns the ids of the scripts that are currently running, in the order they were started.
+    #[must_use]
+    pub fn running_scripts(&self) -> Vec<i32> {
+        self.tasks.iter().map(|task| task.script_id).collect()
+    }
+
+    /// Evaluates an expression outside of any script, so only global variables are available.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the expression can't be evaluated.
+    pub fn evaluate(&mut self, value: &NovaValue) -> Result<Value, RuntimeError> {
+        self.eval(&Context::default(), value)
+    }
+
+    /// Starts every non-function script that has an activator of the given type.
+    ///
+    /// Scripts whose condition evaluates to false, or that have already been activated `activation_count`
+    /// times, are skipped. An `activation_count` of 0 means the script can be activated any number of times.
+    ///
+    /// The scripts start running on the next call to [`Interpreter::advance`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the condition of a script can't be evaluated.
+    pub fn trigger(&mut self, activator_type: ActivatorType) -> Result<(), RuntimeError> {
+        let level = self.level;
+
+        for script in &level.nova_scripts {
+            if script.is_function
+                || !script
+                    .activation_list
+                    .iter()
+                    .any(|activator| activator.activator_type == activator_type)
+            {
+                continue;
+            }
+
+            let activations = self
+                .activations
+                .get(&script.script_id)
+                .copied()
+                .unwrap_or(0);
+
+            if script.activation_count > 0 && activations >= script.activation_count {
+                continue;
+            }
+
+            if !self.evaluate(&script.condition)?.as_bool()? {
+                continue;
+            }
+
+            self.activations.insert(script.script_id, activations + 1);
+            self.spawn(script, HashMap::new())?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts a script, ignoring its activators and condition.
+    ///
+    /// If the script is a function, its parameters get their default values. The script starts running on the
+    /// next call to [`Interpreter::advance`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no script with the given id or if its variables can't be initialised.
+    pub fn start_script(&mut self, script_id: i32) -> Result<(), RuntimeError> {
+        let script = *self
+            .scripts
+            .get(&script_id)
+            .ok_or(RuntimeError::UnknownScript(script_id))?;
+
+        self.spawn(script, HashMap::new())
+    }
+
+    /// Advances the simulated clock by `seconds`, running every script that becomes ready in the meantime.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `seconds` is negative, NaN or infinite, or if a script fails. The script that failed is
+    /// stopped.
+    pub fn advance(&mut self, seconds: f64) -> Result<(), RuntimeError> {
+        if !seconds.is_finite() || seconds < 0.0 {
+            return Err(RuntimeError::InvalidDuration);
+        }
+
+        let target = self.time + seconds;
+
+        loop {
+            self.run_ready()?;
+
+            let next = self
+                .tasks
+                .iter()
+                .map(Task::next_wake)
+                .fold(f64::INFINITY, f64::min);
+
+            if next > target {
+                break;
+            }
+
+            self.time = next;
+        }
+
+        self.time = target;
+
+        Ok(())
+    }
+
+    /// Advances the simulated clock until no scripts are running anymore, or until `max_seconds` have passed.
+    ///
+    /// Returns whether all scripts finished.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `max_seconds` is negative or NaN, or if a script fails.
+    pub fn run_until_idle(&mut self, max_seconds: f64) -> Result<bool, RuntimeError> {
+        if max_seconds.is_nan() || max_seconds < 0.0 {
+            return Err(RuntimeError::InvalidDuration);
+        }
+
+        let limit = self.time + max_seconds;
+
+        loop {
+            self.run_ready()?;
+
+            if self.is_idle() {
+                return Ok(true);
+            }
+
+            let next = self
+                .tasks
+                .iter()
+                .map(Task::next_wake)
+                .fold(f64::INFINITY, f64::min);
+
+            if next > limit {
+                self.time = limit;
+                return Ok(false);
+            }
+
+            self.time = next;
+        }
+    }
+
+    fn run_ready(&mut self) -> Result<(), RuntimeError> {
+        let mut tasks = core::mem::take(&mut self.tasks);
+        let mut result = Ok(());
+        let mut index = 0;
+
+        while index < tasks.len() {
+            if let Err(err) = self.run_task(&mut tasks[index]) {
+                tasks[index].stop();
+                result = Err(err);
+                break;
+            }
+
+            // functions started without waiting are spawned as separate tasks, which should also run now
+            tasks.append(&mut self.tasks);
+            index += 1;
+        }
+
+        tasks.append(&mut self.tasks);
+
+        for script_id in self.stopped.drain(..) {
+            for task in &mut tasks {
+                if task.script_id == script_id {
+                    task.stop();
+                }
+            }
+        }
+
+        tasks.retain(|task| !task.is_finished());
+        self.tasks = tasks;
+
+        result
+    }
+
+    fn spawn(
+        &mut self,
+        script: &'a NovaScript,
+        arguments: HashMap<i32, Value>,
+    ) -> Result<(), RuntimeError> {
+        let scope = self.scope(script, arguments)?;
+
+        self.tasks.push(Task {
+            script_id: script.script_id,
+            scopes: vec![scope],
+            frames: vec![Frame {
+                actions: &script.actions,
+                pc: 0,
+                kind: FrameKind::Block,
+            }],
+            deferred: Vec::new(),
+            wake_at: self.time,
+        });
+
+        Ok(())
+    }
+
+    fn scope(
+        &mut self,
+        script: &'a NovaScript,
+        mut arguments: HashMap<i32, Value>,
+    ) -> Result<Scope<'a>, RuntimeError> {
+        for parameter in &script.parameters {
+            if let Entry::Vacant(entry) = arguments.entry(parameter.parameter_id) {
+                entry.insert(self.evaluate(&parameter.default_value)?);
+            }
+        }
+
+        let mut variables = HashMap::new();
+
+        for variable in &script.variables {
+            variables.insert(
+                variable.variable_id,
+                self.evaluate(&variable.initial_value)?,
+            );
+        }
+
+        Ok(Scope {
+            script,
+            variables,
+            parameters: arguments,
+        })
+    }
+
+    fn run_task(&mut self, task: &mut Task<'a>) -> Result<(), RuntimeError> {
+        self.apply_deferred(task);
+
+        let mut steps = 0;
+
+        loop {
+            if task.wake_at > self.time {
+                break;
+            }
+
+            let Some(frame) = task.frames.last_mut() else {
+                break;
+            };
+
+            steps += 1;
+
+            if steps > self.step_limit {
+                return Err(RuntimeError::StepLimitExceeded);
+            }
+
+            if let Some(action) = frame.actions.get(frame.pc) {
+                frame.pc += 1;
+                self.execute(task, action)?;
+            } else {
+                self.end_frame(task)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn apply_deferred(&mut self, task: &mut Task<'a>) {
+        let time = self.time;
+        let (ready, waiting) = core::mem::take(&mut task.deferred)
+            .into_iter()
+            .partition(|deferred: &Deferred| deferred.at <= time);
+
+        task.deferred = waiting;
+
+        for deferred in ready {
+            let scope = task.scopes.get_mut(deferred.scope);
+
+            match scope {
+                Some(scope) if scope.variables.contains_key(&deferred.variable) => {
+                    scope.variables.insert(deferred.variable, deferred.value);
+                }
+                _ => {
+                    if let Some(slot) = self.globals.get_mut(&deferred.variable) {
+                        *slot = deferred.value;
+                    }
+                }
+            }
+        }
+    }
+
+    fn end_frame(&mut self, task: &mut Task<'a>) -> Result<(), RuntimeError> {
+        let Some(frame) = task.frames.last_mut() else {
+            return Ok(());
+        };
+
+        let repeat = match &mut frame.kind {
+            FrameKind::Block => false,
+            FrameKind::Call => {
+                task.scopes.pop();
+                false
+            }
+            FrameKind::Repeat { iteration, count } => {
+                *iteration += 1;
+                *iteration < *count
+            }
+            FrameKind::ForEach { objects, index } => {
+                *index += 1;
+                *index < objects.len()
+            }
+            FrameKind::While { condition, .. } => {
+                let condition = *condition;
+                let result = self.eval(&task.context(), condition)?.as_bool()?;
+
+                if let Some(Frame {
+                    kind: FrameKind::While { iteration, .. },
+                    ..
+                }) = task.frames.last_mut()
+                {
+                    *iteration += 1;
+                }
+
+                result
+            }
+        };
+
+        if repeat {
+            if let Some(frame) = task.frames.last_mut() {
+                frame.pc = 0;
+            }
+        } else {
+            task.frames.pop();
+        }
+
+        Ok(())
+    }
+
+    fn set_variable(
+        &mut self,
+        task: &mut Task<'a>,
+        variable: i32,
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        if let Some(slot) = task
+            .scopes
+            .last_mut()
+            .and_then(|scope| scope.variables.get_mut(&variable))
+        {
+            *slot = value;
+        } else if let Some(slot) = self.globals.get_mut(&variable) {
+            *slot = value;
+        } else {
+            return Err(RuntimeError::UnknownVariable(variable));
+        }
+
+        Ok(())
+    }
+
+    fn block(&self, task: &mut Task<'a>, seconds: f32) {
+        if seconds > 0.0 {
+            task.wake_at = self.time + f64::from(seconds);
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn execute(&mut self, task: &mut Task<'a>, action: &'a Action) -> Result<(), RuntimeError> {
+        match &action.action_type {
+            ActionType::Repeat { actions, count } => {
+                let count = self.eval(&task.context(), count)?.as_int()?;
+
+                if count > 0 && !actions.is_empty() {
+                    task.frames.push(Frame {
+                        actions,
+                        pc: 0,
+                        kind: FrameKind::Repeat {
+                            iteration: 0,
+                            count,
+                        },
+                    });
+                }
+            }
+            ActionType::RepeatWhile { actions, condition } => {
+                if self.eval(&task.context(), condition)?.as_bool()? {
+                    task.frames.push(Frame {
+                        actions,
+                        pc: 0,
+                        kind: FrameKind::While {
+                            iteration: 0,
+                            condition,
+                        },
+                    });
+                }
+            }
+            ActionType::ConditionBlock {
+                if_actions,
+                else_actions,
+                condition,
+            } => {
+                let actions = if self.eval(&task.context(), condition)?.as_bool()? {
+                    if_actions
+                } else {
+                    else_actions
+                };
+
+                task.frames.push(Frame {
+                    actions,
+                    pc: 0,
+                    kind: FrameKind::Block,
+                });
+            }
+            ActionType::Wait { duration } => {
+                let duration = self.eval(&task.context(), duration)?.as_float()?;
+                self.block(task, duration);
+            }
+            ActionType::WaitFrames { frames } => {
+                let frames = self.eval(&task.context(), frames)?.as_int()?;
+
+                if frames > 0 {
+                    task.wake_at = self.frame_duration.mul_add(f64::from(frames), self.time);
+                }
+            }
+            ActionType::SetVariable { variable, value } => {
+                if let Some(value) = value {
+                    let value = self.eval(&task.context(), value)?;
+                    self.set_variable(task, *variable, value)?;
+                }
+            }
+            ActionType::ResetVariable { variable } => {
+                let initial = task
+                    .scopes
+                    .last()
+                    .and_then(|scope| {
+                        scope
+                            .script
+                            .variables
+                            .iter()
+                            .find(|v| v.variable_id == *variable)
+                    })
+                    .or_else(|| {
+                        self.level
+                            .global_variables
+                            .iter()
+                            .find(|v| v.variable_id == *variable)
+                    })
+                    .ok_or(RuntimeError::UnknownVariable(*variable))?;
+
+                let value = self.evaluate(&initial.initial_value)?;
+                self.set_variable(task, *variable, value)?;
+            }
+            ActionType::SetVariableOverTime {
+                variable,
+                value,
+                duration,
+                ..
+            } => {
+                let duration = self.eval(&task.context(), duration)?.as_float()?;
+
+                if let Some(value) = value {
+                    let value = self.eval(&task.context(), value)?;
+
+                    if duration > 0.0 {
+                        task.deferred.push(Deferred {
+                            at: self.time + f64::from(duration),
+                            scope: task.scopes.len() - 1,
+                            variable: *variable,
+                            value,
+                        });
+                    } else {
+                        self.set_variable(task, *variable, value)?;
+                    }
+                }
+
+                if action.wait {
+                    self.block(task, duration);
+                }
+            }
+            ActionType::RunFunction { function } => {
+                let script = *self
+                    .scripts
+                    .get(&function.id)
+                    .ok_or(RuntimeError::UnknownScript(function.id))?;
+
+                let mut arguments = HashMap::new();
+
+                for parameter in &function.parameters {
+                    let value = self.eval(&task.context(), &parameter.value)?;
+                    arguments.insert(parameter.parameter_id, value);
+                }
+
+                if action.wait {
+                    let scope = self.scope(script, arguments)?;
+
+                    task.scopes.push(scope);
+                    task.frames.push(Frame {
+                        actions: &script.actions,
+                        pc: 0,
+                        kind: FrameKind::Call,
+                    });
+                } else {
+                    self.spawn(script, arguments)?;
+                }
+            }
+            ActionType::RepeatForEachObject {
+                target_objects,
+                actions,
+            } => {
+                let objects = self
+                    .eval(&task.context(), target_objects)?
+                    .as_object_set()?
+                    .to_vec();
+
+                if !objects.is_empty() && !actions.is_empty() {
+                    task.frames.push(Frame {
+                        actions,
+                        pc: 0,
+                        kind: FrameKind::ForEach { objects, index: 0 },
+                    });
+                }
+            }
+            ActionType::StopScript { script } => {
+                let script = self.eval(&task.context(), script)?.as_id()?;
+
+                if script == task.script_id {
+                    task.stop();
+                } else {
+                    self.stopped.push(script);
+                }
+            }
+            ActionType::Move {
+                target_objects,
+                position,
+                global,
+                duration,
+                easing,
+            } => {
+                let context = task.context();
+                let objects = self
+                    .eval(&context, target_objects)?
+                    .as_object_set()?
+                    .to_vec();
+                let position = self.eval(&context, position)?.as_vector()?;
+                let global = self.eval(&context, global)?.as_bool()?;
+                let duration = self.eval(&context, duration)?.as_float()?;
+                let easing = Easing::from(self.eval(&context, easing)?.as_id()?);
+
+                for object in &objects {
+                    self.positions.insert(*object, position);
+                }
+
+                self.hooks.effect(
+                    self.time,
+                    Effect::Move {
+                        objects,
+                        position,
+                        global,
+                        duration,
+                        easing,
+                    },
+                );
+
+                if action.wait {
+                    self.block(task, duration);
+                }
+            }
+            ActionType::Kill { target_objects } => {
+                let objects = self
+                    .eval(&task.context(), target_objects)?
+                    .as_object_set()?
+                    .to_vec();
+
+                self.dead.extend(objects.iter().copied());
+                self.hooks.effect(self.time, Effect::Kill { objects });
+            }
+            ActionType::PlaySound {
+                sound,
+                volume,
+                pitch,
+            } => {
+                let context = task.context();
+                let sound = match self.eval(&context, sound)? {
+                    Value::Sound(sound) => sound,
+                    other => return Err(other.mismatch(StaticType::Sound)),
+                };
+                let volume = self.eval(&context, volume)?.as_float()?;
+                let pitch = self.eval(&context, pitch)?.as_float()?;
+
+                self.hooks.effect(
+                    self.time,
+                    Effect::PlaySound {
+                        sound,
+                        volume,
+                        pitch,
+                    },
+                );
+            }
+            other => {
+                let context = task.context();
+                let mut arguments = Vec::new();
+
+                for value in other.values() {
+                    arguments.push(self.eval(&context, value)?);
+                }
+
+                let duration = match duration(other) {
+                    Some(duration) => self.eval(&context, duration)?.as_float()?,
+                    None => 0.0,
+                };
+
+                self.hooks.effect(
+                    self.time,
+                    Effect::Other {
+                        action_type: i32::from(other),
+                        arguments,
+                    },
+                );
+
+                if action.wait {
+                    self.block(task, duration);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    const fn random(&mut self) -> u64 {
+        self.rng.next()
+    }
+}
+
+/// Returns the duration argument of an action, if it has one.
+const fn duration(action_type: &ActionType) -> Option<&NovaValue> {
+    match action_type {
+        ActionType::Scale { duration, .. }
+        | ActionType::Rotate { duration, .. }
+        | ActionType::RotateAround { duration, .. }
+        | ActionType::SetColor { duration, .. }
+        | ActionType::SetTransparency { duration, .. }
+        | ActionType::SetSecondaryColor { duration, .. }
+        | ActionType::SetSecondaryTransparency { duration, .. }
+        | ActionType::SetBorderColor { duration, .. }
+        | ActionType::SetBorderTransparency { duration, .. }
+        | ActionType::CameraPan { duration, .. }
+        | ActionType::CameraZoom { duration, .. }
+        | ActionType::CameraZoomReset { duration, .. }
+        | ActionType::CameraOffset { duration, .. }
+        | ActionType::CameraOffsetReset { duration, .. }
+        | ActionType::CameraShake { duration, .. }
+        | ActionType::GameTextShow { duration, .. }
+        | ActionType::TransitionIn { duration, .. }
+        | ActionType::TransitionOut { duration, .. }
+        | ActionType::TimeScale { duration, .. } => Some(duration),
+        _ => None,
+    }
+}
+
+/// A running instance of a script.
+#[derive(Debug)]
+struct Task<'a> {
+    script_id: i32,
+    scopes: Vec<Scope<'a>>,
+    frames: Vec<Frame<'a>>,
+    deferred: Vec<Deferred>,
+    wake_at: f64,
+}
+
+impl<'a> Task<'a> {
+    fn context(&self) -> Context<'_, 'a> {
+        let start = self
+            .frames
+            .iter()
+            .rposition(|frame| matches!(frame.kind, FrameKind::Call))
+            .unwrap_or(0);
+
+        Context {
+            scope: self.scopes.last(),
+            frames: &self.frames[start..],
+        }
+    }
+
+    fn next_wake(&self) -> f64 {
+        let deferred = self
+            .deferred
+            .iter()
+            .map(|deferred| deferred.at)
+            .fold(f64::INFINITY, f64::min);
+
+        if self.frames.is_empty() {
+            deferred
+        } else {
+            self.wake_at.min(deferred)
+        }
+    }
+
+    fn stop(&mut self) {
+        self.frames.clear();
+        self.deferred.clear();
+    }
+
+    const fn is_finished(&self) -> bool {
+        self.frames.is_empty() && self.deferred.is_empty()
+    }
+}
+
+/// The variables and parameters of a script or function call.
+#[derive(Debug)]
+struct Scope<'a> {
+    script: &'a NovaScript,
+    variables: HashMap<i32, Value>,
+    parameters: HashMap<i32, Value>,
+}
+
+/// A list of actions being executed.
+#[derive(Debug)]
+struct Frame<'a> {
+    actions: &'a [Action],
+    pc: usize,
+    kind: FrameKind<'a>,
+}
+
+#[derive(Debug)]
+enum FrameKind<'a> {
+    Block,
+    Call,
+    Repeat {
+        iteration: i32,
+        count: i32,
+    },
+    While {
+        iteration: i32,
+        condition: &'a NovaValue,
+    },
+    ForEach {
+        objects: Vec<i32>,
+        index: usize,
+    },
+}
+
+/// A variable assignment that happens once a [`ActionType::SetVariableOverTime`] finishes.
+#[derive(Debug)]
+struct Deferred {
+    at: f64,
+    scope: usize,
+    variable: i32,
+    value: Value,
+}
+
+/// The state an expression is evaluated in.
+#[derive(Debug, Default)]
+struct Context<'t, 'a> {
+    scope: Option<&'t Scope<'a>>,
+    frames: &'t [Frame<'a>],
+}
+
+impl Context<'_, '_> {
+    fn repeat_count(&self) -> i32 {
+        self.frames
+            .iter()
+            .rev()
+            .find_map(|frame| match frame.kind {
+                FrameKind::Repeat { iteration, .. } | FrameKind::While { iteration, .. } => {
+                    Some(iteration)
+                }
+                _ => None,
+            })
+            .unwrap_or(0)
+    }
+
+    fn repeat_object(&self) -> Option<i32> {
+        self.frames
+            .iter()
+            .rev()
+            .find_map(|frame| match &frame.kind {
+                FrameKind::ForEach { objects, index } => objects.get(*index).copied(),
+                _ => None,
+            })
+    }
+}
@@ -0,0 +1,215 @@
+use super::RuntimeError;
+use crate::types::{
+    color::Color,
+    dynamic_type::DynamicType,
+    novascript::{nova_value::NovaValue, static_type::StaticType},
+    vec2::Vec2,
+};
+
+/// A fully evaluated `NovaScript` value.
+///
+/// Objects are referred to by their entity id. See [`super::PLAYER`] for the id used for the player.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Int(i32),
+    Float(f32),
+    String(String),
+    Color(Color),
+    Vector(Vec2),
+    Sound(String),
+    Music(String),
+    Object(i32),
+    ObjectSet(Vec<i32>),
+    Transition(i32),
+    Easing(i32),
+    Sprite(String),
+    Script(i32),
+    Layer(i32),
+}
+
+impl Value {
+    /// Returns the static type of this value.
+    #[must_use]
+    pub const fn static_type(&self) -> StaticType {
+        match self {
+            Self::Bool(_) => StaticType::Bool,
+            Self::Int(_) => StaticType::Int,
+            Self::Float(_) => StaticType::Float,
+            Self::String(_) => StaticType::String,
+            Self::Color(_) => StaticType::Color,
+            Self::Vector(_) => StaticType::Vector,
+            Self::Sound(_) => StaticType::Sound,
+            Self::Music(_) => StaticType::Music,
+            Self::Object(_) => StaticType::Object,
+            Self::ObjectSet(_) => StaticType::ObjectSet,
+            Self::Transition(_) => StaticType::Transition,
+            Self::Easing(_) => StaticType::Easing,
+            Self::Sprite(_) => StaticType::Sprite,
+            Self::Script(_) => StaticType::Script,
+            Self::Layer(_) => StaticType::Layer,
+        }
+    }
+
+    /// Returns the value as a bool.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a bool.
+    pub const fn as_bool(&self) -> Result<bool, RuntimeError> {
+        match self {
+            Self::Bool(value) => Ok(*value),
+            other => Err(other.mismatch(StaticType::Bool)),
+        }
+    }
+
+    /// Returns the value as an int.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't an int.
+    pub const fn as_int(&self) -> Result<i32, RuntimeError> {
+        match self {
+            Self::Int(value) => Ok(*value),
+            other => Err(other.mismatch(StaticType::Int)),
+        }
+    }
+
+    /// Returns the value as a float. Ints are converted to floats.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a number.
+    #[allow(clippy::cast_precision_loss)]
+    pub const fn as_float(&self) -> Result<f32, RuntimeError> {
+        match self {
+            Self::Float(value) => Ok(*value),
+            Self::Int(value) => Ok(*value as f32),
+            other => Err(other.mismatch(StaticType::Float)),
+        }
+    }
+
+    /// Returns the value as a number with enough precision to compare ints and floats exactly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a number.
+    pub fn as_number(&self) -> Result<f64, RuntimeError> {
+        match self {
+            Self::Float(value) => Ok(f64::from(*value)),
+            Self::Int(value) => Ok(f64::from(*value)),
+            other => Err(other.mismatch(StaticType::Float)),
+        }
+    }
+
+    /// Returns the value as a string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a string.
+    pub fn as_str(&self) -> Result<&str, RuntimeError> {
+        match self {
+            Self::String(value) => Ok(value),
+            other => Err(other.mismatch(StaticType::String)),
+        }
+    }
+
+    /// Returns the value as a color.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a color.
+    pub const fn as_color(&self) -> Result<Color, RuntimeError> {
+        match self {
+            Self::Color(value) => Ok(*value),
+            other => Err(other.mismatch(StaticType::Color)),
+        }
+    }
+
+    /// Returns the value as a vector.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a vector.
+    pub const fn as_vector(&self) -> Result<Vec2, RuntimeError> {
+        match self {
+            Self::Vector(value) => Ok(*value),
+            other => Err(other.mismatch(StaticType::Vector)),
+        }
+    }
+
+    /// Returns the value as an object id.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't an object.
+    pub const fn as_object(&self) -> Result<i32, RuntimeError> {
+        match self {
+            Self::Object(value) => Ok(*value),
+            other => Err(other.mismatch(StaticType::Object)),
+        }
+    }
+
+    /// Returns the value as a list of object ids. A single object is treated as a set containing only that object.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't an object or an object set.
+    pub fn as_object_set(&self) -> Result<&[i32], RuntimeError> {
+        match self {
+            Self::ObjectSet(value) => Ok(value),
+            Self::Object(value) => Ok(core::slice::from_ref(value)),
+            other => Err(other.mismatch(StaticType::ObjectSet)),
+        }
+    }
+
+    /// Returns the id stored in an id-like value (transitions, easings, scripts and layers).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value doesn't hold an id.
+    pub const fn as_id(&self) -> Result<i32, RuntimeError> {
+        match self {
+            Self::Transition(value)
+            | Self::Easing(value)
+            | Self::Script(value)
+            | Self::Layer(value)
+            | Self::Object(value)
+            | Self::Int(value) => Ok(*value),
+            other => Err(other.mismatch(StaticType::Int)),
+        }
+    }
+
+    pub(crate) const fn mismatch(&self, expected: StaticType) -> RuntimeError {
+        RuntimeError::TypeMismatch {
+            expected,
+            found: self.static_type(),
+        }
+    }
+}
+
+impl From<Value> for NovaValue {
+    /// Converts the value into the equivalent constant expression.
+    fn from(value: Value) -> Self {
+        use DynamicType as D;
+
+        match value {
+            Value::Bool(value) => Self::new_bool(D::BoolConstant, value),
+            Value::Int(value) => Self::new_int(D::IntConstant, value),
+            Value::Float(value) => Self::new_float(D::FloatConstant, value),
+            Value::String(value) => Self::new_string(D::StringConstant, value),
+            Value::Color(value) => Self::new_color(D::ColorConstant, value),
+            Value::Vector(value) => Self::new_vector(D::VectorConstant, value),
+            Value::Sound(value) => Self::new_string(D::SoundConstant, value),
+            Value::Music(value) => Self::new_string(D::MusicConstant, value),
+            Value::Object(value) => Self::new_int(D::ObjectConstant, value),
+            Value::ObjectSet(value) => Self::new_int_list(D::ObjectSetConstant, value),
+            Value::Transition(value) => Self::new_int(D::TransitionConstant, value),
+            Value::Easing(value) => Self::new_int(D::EasingConstant, value),
+            Value::Sprite(value) => Self::new_string(D::SpriteConstant, value),
+            Value::Script(value) => Self::new_int(D::ScriptConstant, value),
+            Value::Layer(value) => Self::new_int(D::LayerConstant, value),
+        }
+    }
+}
@@ -2,14 +2,29 @@
 #![cfg_attr(not(target_os = "windows"), doc=include_str!("../README.md"))]
 // #![warn(missing_docs)] // uncomment when writing docs
 
+pub mod builder;
+pub mod catalogue;
+pub mod diff;
+pub mod edit;
 pub mod error;
+pub mod geometry;
 pub mod gzip;
+pub mod interpreter;
+pub mod optimizer;
 mod primitive_impls;
 mod private;
+pub mod references;
+#[cfg(feature = "image")]
+pub mod render;
+mod rng;
+pub mod spatial;
+pub mod stats;
+pub mod svg;
 #[cfg(test)]
 mod tests;
 mod traits;
 pub mod types;
+mod visit;
 
 pub use traits::{Read, ReadContext, ReadVersioned, Write};
 
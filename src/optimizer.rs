@@ -0,0 +1,557 @@
+//! An optimizer for `NovaScript`.
+//!
+//! The optimizer folds constant expressions (e.g. `IntAdd(IntConstant, IntConstant)`), simplifies boolean logic,
+//! removes unreachable branches and loops that do nothing, and drops variables that are never read.
+//!
+//! Constant expressions are folded using the [`interpreter`](crate::interpreter), so the optimized scripts behave
+//! exactly like the original ones when they're run. The only exception is malformed scripts: an action that would
+//! fail to evaluate (e.g. because of a type mismatch) might be removed if it doesn't have any effect anyway.
+//!
+//! ```
+//! use exolvl::optimizer::optimize_value;
+//! use exolvl::types::{dynamic_type::DynamicType, novascript::nova_value::NovaValue};
+//!
+//! let mut value = NovaValue::new_sub_values(
+//!     DynamicType::IntAdd,
+//!     vec![
+//!         NovaValue::new_int(DynamicType::IntConstant, 1),
+//!         NovaValue::new_int(DynamicType::IntConstant, 2),
+//!     ],
+//! );
+//!
+//! optimize_value(&mut value);
+//!
+//! assert_eq!(value, NovaValue::new_int(DynamicType::IntConstant, 3));
+//! ```
+
+use crate::{
+    interpreter::{Interpreter, Recorder},
+    types::{
+        action_type::ActionType,
+        dynamic_type::DynamicType,
+        level_data::LevelData,
+        novascript::{action::Action, nova_value::NovaValue, NovaScript},
+    },
+};
+use std::collections::HashSet;
+
+/// Optimizes all `NovaScript` scripts and global variables of a level.
+///
+/// Global variables that aren't read by any script are removed, along with the actions that set them.
+pub fn optimize_level(level: &mut LevelData) {
+    let empty = LevelData::default();
+    let mut folder = Folder::new(&empty);
+
+    for variable in &mut level.global_variables {
+        folder.value(&mut variable.initial_value);
+    }
+
+    for script in &mut level.nova_scripts {
+        folder.script(script);
+    }
+
+    let read = level
+        .nova_scripts
+        .iter()
+        .fold(HashSet::new(), |mut read, script| {
+            read_variables_in_script(script, &mut read);
+            read
+        });
+
+    let unused = level
+        .global_variables
+        .iter()
+        .map(|variable| variable.variable_id)
+        .filter(|id| {
+            !read.contains(id)
+                && level
+                    .nova_scripts
+                    .iter()
+                    .all(|script| writes_removable(&script.actions, *id))
+        })
+        .collect::<HashSet<_>>();
+
+    if unused.is_empty() {
+        return;
+    }
+
+    level
+        .global_variables
+        .retain(|variable| !unused.contains(&variable.variable_id));
+
+    for script in &mut level.nova_scripts {
+        remove_writes(&mut script.actions, &unused);
+    }
+}
+
+/// Optimizes a single script.
+///
+/// Local variables that aren't read by the script are removed, along with the actions that set them.
+pub fn optimize_script(script: &mut NovaScript) {
+    let empty = LevelData::default();
+    Folder::new(&empty).script(script);
+}
+
+/// Optimizes a list of actions, e.g. the actions of a script.
+///
+/// Actions that are removed or inlined (e.g. a [`ActionType::ConditionBlock`] with a constant condition) are removed
+/// from the list.
+pub fn optimize_actions(actions: &mut Vec<Action>) {
+    let empty = LevelData::default();
+    Folder::new(&empty).actions(actions);
+}
+
+/// Optimizes a single expression.
+pub fn optimize_value(value: &mut NovaValue) {
+    let empty = LevelData::default();
+    Folder::new(&empty).value(value);
+}
+
+struct Folder<'a> {
+    interpreter: Interpreter<'a, Recorder>,
+}
+
+impl<'a> Folder<'a> {
+    fn new(empty: &'a LevelData) -> Self {
+        Self {
+            interpreter: Interpreter::without_globals(empty, Recorder::default()),
+        }
+    }
+
+    fn script(&mut self, script: &mut NovaScript) {
+        self.value(&mut script.condition);
+
+        for parameter in &mut script.parameters {
+            self.value(&mut parameter.default_value);
+        }
+
+        for variable in &mut script.variables {
+            self.value(&mut variable.initial_value);
+        }
+
+        self.actions(&mut script.actions);
+
+        let mut read = HashSet::new();
+        read_variables_in_script(script, &mut read);
+
+        let unused = script
+            .variables
+            .iter()
+            .map(|variable| variable.variable_id)
+            .filter(|id| !read.contains(id) && writes_removable(&script.actions, *id))
+            .collect::<HashSet<_>>();
+
+        if !unused.is_empty() {
+            script
+                .variables
+                .retain(|variable| !unused.contains(&variable.variable_id));
+
+            remove_writes(&mut script.actions, &unused);
+        }
+    }
+
+    fn actions(&mut self, actions: &mut Vec<Action>) {
+        let mut optimized = Vec::with_capacity(actions.len());
+
+        for mut action in actions.drain(..) {
+            for value in action.action_type.values_mut() {
+                self.value(value);
+            }
+
+            for nested in action.action_type.actions_mut() {
+                self.actions(nested);
+            }
+
+            match action.action_type {
+                ActionType::ConditionBlock {
+                    if_actions,
+                    else_actions,
+                    condition,
+                } => match bool_constant(&condition) {
+                    Some(true) => optimized.extend(if_actions),
+                    Some(false) => optimized.extend(else_actions),
+                    None if if_actions.is_empty()
+                        && else_actions.is_empty()
+                        && !has_side_effects(&condition) => {}
+                    None => optimized.push(Action {
+                        action_type: ActionType::ConditionBlock {
+                            if_actions,
+                            else_actions,
+                            condition,
+                        },
+                        ..action
+                    }),
+                },
+                ActionType::Repeat {
+                    ref actions,
+                    ref count,
+                } if (actions.is_empty() && !has_side_effects(count))
+                    || int_constant(count).is_some_and(|count| count <= 0) => {}
+                ActionType::RepeatWhile { ref condition, .. }
+                    if bool_constant(condition) == Some(false) => {}
+                ActionType::RepeatForEachObject {
+                    ref actions,
+                    ref target_objects,
+                } if actions.is_empty() && !has_side_effects(target_objects) => {}
+                _ => optimized.push(action),
+            }
+        }
+
+        *actions = optimized;
+    }
+
+    fn value(&mut self, value: &mut NovaValue) {
+        use DynamicType as D;
+
+        if let Some(sub_values) = &mut value.inner.sub_values {
+            for sub_value in sub_values.iter_mut() {
+                self.value(sub_value);
+            }
+        }
+
+        if let Some(simplified) = simplify(value) {
+            *value = simplified;
+            return;
+        }
+
+        let foldable = is_foldable(value.dynamic_type)
+            && value
+                .inner
+                .sub_values
+                .as_ref()
+                .is_some_and(|sub_values| sub_values.iter().all(is_constant));
+
+        if foldable {
+            if let Ok(folded) = self.interpreter.evaluate(value) {
+                *value = folded.into();
+            }
+        } else if value.dynamic_type == D::BoolNot {
+            // `!!a` is `a`, and `!(a == b)` is `a != b`
+            if let Some([operand]) = value.inner.sub_values.as_deref_mut() {
+                let negated = match operand.dynamic_type {
+                    D::BoolNot => operand
+                        .inner
+                        .sub_values
+                        .as_mut()
+                        .filter(|sub_values| sub_values.len() == 1)
+                        .and_then(Vec::pop),
+                    ty => negate_comparison(ty).map(|ty| {
+                        let mut operand = take(operand);
+                        operand.dynamic_type = ty;
+                        operand
+                    }),
+                };
+
+                if let Some(negated) = negated {
+                    *value = negated;
+                }
+            }
+        }
+    }
+}
+
+/// Simplifies `and` and `or` with a constant operand. The right operand is only evaluated if the left one doesn't
+/// decide the result, so it can't be dropped if it comes first.
+fn simplify(value: &mut NovaValue) -> Option<NovaValue> {
+    use DynamicType as D;
+
+    let ty = value.dynamic_type;
+
+    if !matches!(ty, D::BoolAnd | D::BoolOr) {
+        return None;
+    }
+
+    let Some([a, b]) = value.inner.sub_values.as_deref_mut() else {
+        return None;
+    };
+
+    // the value that decides the result on its own: `false` for `and`, `true` for `or`
+    let decisive = ty == D::BoolOr;
+
+    match (bool_constant(a), bool_constant(b)) {
+        (Some(a), _) if a == decisive => Some(NovaValue::new_bool(D::BoolConstant, decisive)),
+        (Some(_), _) => Some(take(b)),
+        (None, Some(b)) if b != decisive => Some(take(a)),
+        _ => None,
+    }
+}
+
+fn take(value: &mut NovaValue) -> NovaValue {
+    core::mem::replace(value, NovaValue::new_bool(DynamicType::BoolConstant, false))
+}
+
+const fn negate_comparison(ty: DynamicType) -> Option<DynamicType> {
+    use DynamicType as D;
+
+    Some(match ty {
+        D::BoolEqualBool => D::BoolNotEqualBool,
+        D::BoolEqualNumber => D::BoolNotEqualNumber,
+        D::BoolEqualString => D::BoolNotEqualString,
+        D::BoolEqualColor => D::BoolNotEqualColor,
+        D::BoolEqualVector => D::BoolNotEqualVector,
+        D::BoolEqualObject => D::BoolNotEqualObject,
+        D::BoolNotEqualBool => D::BoolEqualBool,
+        D::BoolNotEqualNumber => D::BoolEqualNumber,
+        D::BoolNotEqualString => D::BoolEqualString,
+        D::BoolNotEqualColor => D::BoolEqualColor,
+        D::BoolNotEqualVector => D::BoolEqualVector,
+        D::BoolNotEqualObject => D::BoolEqualObject,
+        _ => return None,
+    })
+}
+
+/// Returns whether the expression only depends on its operands, so it can be evaluated ahead of time.
+const fn is_foldable(ty: DynamicType) -> bool {
+    use DynamicType as D;
+
+    matches!(
+        ty,
+        D::BoolNot
+            | D::BoolAnd
+            | D::BoolOr
+            | D::BoolEqualBool
+            | D::BoolEqualNumber
+            | D::BoolEqualString
+            | D::BoolEqualColor
+            | D::BoolEqualVector
+            | D::BoolEqualObject
+            | D::BoolNotEqualBool
+            | D::BoolNotEqualNumber
+            | D::BoolNotEqualString
+            | D::BoolNotEqualColor
+            | D::BoolNotEqualVector
+            | D::BoolNotEqualObject
+            | D::BoolLess
+            | D::BoolLessOrEqual
+            | D::BoolGreater
+            | D::BoolGreaterOrEqual
+            | D::IntAdd
+            | D::IntSubtract
+            | D::IntMultiply
+            | D::IntDivide
+            | D::IntModulo
+            | D::IntMin
+            | D::IntMax
+            | D::IntAbs
+            | D::IntSign
+            | D::IntRound
+            | D::IntCeil
+            | D::IntFloor
+            | D::IntObjectSetCount
+            | D::IntStringLength
+            | D::FloatAdd
+            | D::FloatSubtract
+            | D::FloatMultiply
+            | D::FloatDivide
+            | D::FloatModulo
+            | D::FloatMin
+            | D::FloatMax
+            | D::FloatPow
+            | D::FloatAbs
+            | D::FloatSign
+            | D::FloatRound
+            | D::FloatCeil
+            | D::FloatFloor
+            | D::FloatCos
+            | D::FloatSin
+            | D::FloatTan
+            | D::FloatAcos
+            | D::FloatAsin
+            | D::FloatAtan
+            | D::FloatSqrt
+            | D::FloatRoundDecimals
+            | D::FloatVectorX
+            | D::FloatVectorY
+            | D::FloatVectorLength
+            | D::FloatVectorLengthSqr
+            | D::FloatVectorAngle
+            | D::FloatVectorDistance
+            | D::FloatVectorDistanceSqr
+            | D::FloatVectorDot
+            | D::FloatVectorAngleBetween
+            | D::FloatColorR
+            | D::FloatColorG
+            | D::FloatColorB
+            | D::FloatColorA
+            | D::StringFromInt
+            | D::StringFromFloat
+            | D::StringConcat
+            | D::StringSubstring
+            | D::ColorValues
+            | D::VectorValues
+            | D::VectorAdd
+            | D::VectorSubtract
+            | D::VectorReflect
+            | D::VectorMultiply
+            | D::VectorDivide
+            | D::VectorRotate
+            | D::VectorNormalize
+            | D::VectorPerpendicular
+            | D::ObjectFirstFromSet
+            | D::ObjectElementFromSet
+            | D::ObjectSetConcat
+            | D::ObjectSetUnion
+            | D::ObjectSetIntersection
+            | D::ObjectSetDifference
+            | D::ObjectSetRemoveAtIndex
+    )
+}
+
+const fn is_constant(value: &NovaValue) -> bool {
+    use DynamicType as D;
+
+    matches!(
+        value.dynamic_type,
+        D::BoolConstant
+            | D::IntConstant
+            | D::FloatConstant
+            | D::StringConstant
+            | D::ColorConstant
+            | D::VectorConstant
+            | D::SoundConstant
+            | D::MusicConstant
+            | D::ObjectConstant
+            | D::ObjectSetConstant
+            | D::TransitionConstant
+            | D::EasingConstant
+            | D::SpriteConstant
+            | D::ScriptConstant
+            | D::LayerConstant
+    )
+}
+
+fn bool_constant(value: &NovaValue) -> Option<bool> {
+    (value.dynamic_type == DynamicType::BoolConstant).then_some(value.inner.bool_value)
+}
+
+fn int_constant(value: &NovaValue) -> Option<i32> {
+    (value.dynamic_type == DynamicType::IntConstant).then_some(value.inner.int_value)
+}
+
+/// Returns whether evaluating the expression does anything besides computing its result, i.e. whether it uses
+/// random numbers or input.
+fn has_side_effects(value: &NovaValue) -> bool {
+    use DynamicType as D;
+
+    matches!(
+        value.dynamic_type,
+        D::IntRandom
+            | D::FloatRandom
+            | D::ObjectRandomFromSet
+            | D::BoolInputPressed
+            | D::BoolInputPressedLeft
+            | D::BoolInputPressedRight
+            | D::BoolInputHeld
+            | D::BoolInputHeldLeft
+            | D::BoolInputHeldRight
+            | D::BoolInputReleased
+            | D::BoolInputReleasedLeft
+            | D::BoolInputReleasedRight
+            | D::BoolPointerDown
+            | D::BoolPointerHeld
+            | D::BoolPointerReleased
+    ) || value
+        .inner
+        .sub_values
+        .iter()
+        .flatten()
+        .any(has_side_effects)
+}
+
+const fn is_variable(ty: DynamicType) -> bool {
+    use DynamicType as D;
+
+    matches!(
+        ty,
+        D::BoolVariable
+            | D::IntVariable
+            | D::FloatVariable
+            | D::StringVariable
+            | D::ColorVariable
+            | D::VectorVariable
+            | D::SoundVariable
+            | D::MusicVariable
+            | D::ObjectVariable
+            | D::ObjectSetVariable
+            | D::TransitionVariable
+            | D::EasingVariable
+            | D::SpriteVariable
+            | D::ScriptVariable
+            | D::LayerVariable
+    )
+}
+
+fn read_variables_in_script(script: &NovaScript, read: &mut HashSet<i32>) {
+    read_variables(&script.condition, read);
+
+    for parameter in &script.parameters {
+        read_variables(&parameter.default_value, read);
+    }
+
+    for variable in &script.variables {
+        read_variables(&variable.initial_value, read);
+    }
+
+    read_variables_in_actions(&script.actions, read);
+}
+
+fn read_variables_in_actions(actions: &[Action], read: &mut HashSet<i32>) {
+    for action in actions {
+        for value in action.action_type.values() {
+            read_variables(value, read);
+        }
+
+        for nested in action.action_type.actions() {
+            read_variables_in_actions(nested, read);
+        }
+    }
+}
+
+fn read_variables(value: &NovaValue, read: &mut HashSet<i32>) {
+    if is_variable(value.dynamic_type) {
+        read.insert(value.inner.int_value);
+    }
+
+    for sub_value in value.inner.sub_values.iter().flatten() {
+        read_variables(sub_value, read);
+    }
+}
+
+/// Returns whether all actions writing to the variable can be removed without changing the behaviour of the
+/// script, apart from the variable's value.
+fn writes_removable(actions: &[Action], variable: i32) -> bool {
+    actions.iter().all(|action| {
+        let removable = match &action.action_type {
+            ActionType::SetVariable {
+                variable: target,
+                value,
+            } => *target != variable || !value.as_ref().is_some_and(has_side_effects),
+            ActionType::SetVariableOverTime {
+                variable: target, ..
+            } => *target != variable,
+            _ => true,
+        };
+
+        removable
+            && action
+                .action_type
+                .actions()
+                .into_iter()
+                .all(|nested| writes_removable(nested, variable))
+    })
+}
+
+fn remove_writes(actions: &mut Vec<Action>, variables: &HashSet<i32>) {
+    actions.retain(|action| match &action.action_type {
+        ActionType::SetVariable { variable, .. } | ActionType::ResetVariable { variable } => {
+            !variables.contains(variable)
+        }
+        _ => true,
+    });
+
+    for action in actions {
+        for nested in action.action_type.actions_mut() {
+            remove_writes(nested, variables);
+        }
+    }
+}
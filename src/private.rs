@@ -1,27 +1,47 @@
-use crate::types::{
-    action_type::ActionType,
-    author_replay::AuthorReplay,
-    brush::{Brush, BrushGrid, BrushObject},
-    color::Color,
-    dynamic_type::DynamicType,
-    exolvl::Exolvl,
-    function_call::{CallParameter, FunctionCall},
-    image::Image,
-    layer::Layer,
-    level_data::LevelData,
-    local_level::LocalLevel,
-    novascript::{
-        action::Action, activator::Activator, nova_value::NovaValue, parameter::Parameter,
-        static_type::StaticType, variable::Variable, NovaScript,
+use crate::{
+    builder::{
+        Easing, Layer as LayerMarker, Music, Object as ObjectMarker, ObjectSet,
+        Script as ScriptMarker, Sound, Sprite, Transition,
+    },
+    types::{
+        action_type::ActionType,
+        author_replay::AuthorReplay,
+        blending::Blending,
+        brush::{Brush, BrushGrid, BrushObject},
+        color::Color,
+        dynamic_type::DynamicType,
+        emitter_shape::EmitterShape,
+        exolvl::Exolvl,
+        fill_mode::FillMode,
+        foreground_type::ForegroundType,
+        function_call::{CallParameter, FunctionCall},
+        image::Image,
+        layer::Layer,
+        level_data::LevelData,
+        local_level::LocalLevel,
+        novascript::{
+            action::Action,
+            activator::{Activator, ActivatorType},
+            color_channel::ColorChannel,
+            easing::Easing as EasingId,
+            nova_value::NovaValue,
+            parameter::Parameter,
+            static_type::StaticType,
+            transition::Transition as TransitionId,
+            variable::Variable,
+            NovaScript,
+        },
+        object::Object,
+        object_property::ObjectProperty,
+        old_script::{OldAction, OldActionProperty, OldActionType, Script},
+        pattern::Pattern,
+        physics_type::PhysicsType,
+        prefab::Prefab,
+        simulation_space::SimulationSpace,
+        theme::Theme,
+        varint::Varint,
+        vec2::Vec2,
     },
-    object::Object,
-    object_property::ObjectProperty,
-    old_script::{OldAction, OldActionProperty, OldActionType, Script},
-    pattern::Pattern,
-    prefab::Prefab,
-    theme::Theme,
-    varint::Varint,
-    vec2::Vec2,
 };
 use chrono::{DateTime, Utc};
 #[cfg(feature = "image")]
@@ -40,6 +60,25 @@ macro_rules! impl_sealed {
 }
 
 impl_sealed!(
+    Blending,
+    EmitterShape,
+    FillMode,
+    ForegroundType,
+    PhysicsType,
+    SimulationSpace,
+    ActivatorType,
+    ColorChannel,
+    EasingId,
+    TransitionId,
+    Sound,
+    Music,
+    ObjectMarker,
+    ObjectSet,
+    Transition,
+    Easing,
+    Sprite,
+    ScriptMarker,
+    LayerMarker,
     Varint,
     String,
     &str,
@@ -0,0 +1,385 @@
+//! Finding where objects, layers, scripts, variables, patterns and prefabs are used.
+//!
+//! A [`ReferenceIndex`] is built by walking all script values and actions, the initial values of global variables
+//! and the properties of objects, prefab items and brush objects once. Every use is recorded with the path to it, so
+//! the use can be found again in the level.
+//!
+//! Membership in layers and groups isn't a use, see [`Layer::children`](crate::types::layer::Layer::children) and
+//! [`Object::group_members`](crate::types::object::Object::group_members) for those.
+//!
+//! ```
+//! use exolvl::references::Target;
+//! use exolvl::types::level_data::LevelData;
+//!
+//! let level = LevelData::default();
+//! let index = level.reference_index();
+//!
+//! assert!(index.uses(Target::Script(1)).is_empty());
+//! ```
+
+use crate::{
+    diff::Location,
+    types::{
+        action_type::ActionType,
+        dynamic_type::DynamicType,
+        level_data::LevelData,
+        novascript::{action::Action, nova_value::NovaValue, NovaScript},
+        object_property::{ObjectProperty, PropertyKey},
+    },
+};
+use core::fmt::{self, Display, Formatter};
+use std::collections::{HashMap, HashSet};
+
+/// Something in a level that can be used elsewhere in it.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Target {
+    /// The object with an entity id.
+    Object(i32),
+    /// The layer with an id.
+    Layer(i32),
+    /// The script or function with an id.
+    Script(i32),
+    /// The global variable with an id.
+    GlobalVariable(i32),
+    /// A local variable of a script. Local variables hide global variables with the same id in their script.
+    LocalVariable {
+        /// The id of the script.
+        script: i32,
+        /// The id of the variable.
+        variable: i32,
+    },
+    /// The pattern with an id.
+    Pattern(i32),
+    /// The prefab with an id.
+    Prefab(i32),
+}
+
+/// One step of the path from a [`Location`] to a use.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum Step {
+    /// The property with a key.
+    Property(PropertyKey),
+    /// The [`prefab_id`](crate::types::object::Object::prefab_id) of an object.
+    PrefabId,
+    /// The prefab item at an index.
+    Item(usize),
+    /// The brush object at an index.
+    BrushObject(usize),
+    /// The initial value of a global variable.
+    InitialValue,
+    /// The condition of a script.
+    Condition,
+    /// The parameter at an index of the activator at an index.
+    Activator {
+        /// The index of the activator.
+        activator: usize,
+        /// The index of the parameter.
+        parameter: usize,
+    },
+    /// The default value of the script parameter at an index.
+    Parameter(usize),
+    /// The initial value of the local variable at an index.
+    LocalVariable(usize),
+    /// The action at an index.
+    Action(usize),
+    /// The list of nested actions at an index, see [`ActionType::actions`].
+    Nested(usize),
+    /// The value at an index of an action, see [`ActionType::values`].
+    Value(usize),
+    /// The variable an action sets.
+    Variable,
+    /// The function an action runs.
+    Function,
+    /// The sub value at an index.
+    SubValue(usize),
+    /// The object at an index of an object set.
+    Element(usize),
+}
+
+/// A use of a [`Target`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Reference {
+    /// Where the use is.
+    pub location: Location,
+    /// The path from the location to the use.
+    pub path: Vec<Step>,
+}
+
+/// An index of all uses of everything in a level, see [`LevelData::reference_index`].
+///
+/// The index isn't updated when the level changes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReferenceIndex {
+    uses: HashMap<Target, Vec<Reference>>,
+}
+
+impl LevelData {
+    /// Returns an index of all uses of everything in the level.
+    #[must_use]
+    pub fn reference_index(&self) -> ReferenceIndex {
+        let mut walker = Walker {
+            location: Location::Settings,
+            path: Vec::new(),
+            locals: HashSet::new(),
+            uses: HashMap::new(),
+        };
+
+        for object in &self.objects {
+            walker.location = Location::Object(object.entity_id);
+
+            if object.prefab_id != 0 {
+                walker.at(Step::PrefabId, |walker| {
+                    walker.add(Target::Prefab(object.prefab_id));
+                });
+            }
+
+            walker.properties(&object.properties);
+        }
+
+        for prefab in &self.prefabs {
+            walker.location = Location::Prefab(prefab.prefab_id);
+
+            for (index, item) in prefab.items.iter().enumerate() {
+                // items refer to each other with their own ids, so only patterns are uses
+                walker.at(Step::Item(index), |walker| {
+                    for property in &item.properties {
+                        if let ObjectProperty::Pattern(pattern) = property {
+                            walker.at(Step::Property(property.key()), |walker| {
+                                walker.add(Target::Pattern(*pattern));
+                            });
+                        }
+                    }
+                });
+            }
+        }
+
+        for brush in &self.brushes {
+            walker.location = Location::Brush(brush.brush_id);
+
+            for (index, object) in brush.objects.iter().enumerate() {
+                walker.at(Step::BrushObject(index), |walker| {
+                    walker.properties(&object.properties);
+                });
+            }
+        }
+
+        for variable in &self.global_variables {
+            walker.location = Location::GlobalVariable(variable.variable_id);
+            walker.at(Step::InitialValue, |walker| {
+                walker.value(&variable.initial_value);
+            });
+        }
+
+        for script in &self.nova_scripts {
+            walker.location = Location::Script(script.script_id);
+            walker.script(script);
+        }
+
+        ReferenceIndex { uses: walker.uses }
+    }
+}
+
+impl ReferenceIndex {
+    /// Returns all uses of a target, in the order they appear in the level.
+    #[must_use]
+    pub fn uses(&self, target: Target) -> &[Reference] {
+        self.uses.get(&target).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns whether a target is used anywhere.
+    #[must_use]
+    pub fn is_used(&self, target: Target) -> bool {
+        self.uses.contains_key(&target)
+    }
+
+    /// Returns all targets that are used, with their uses, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Target, &[Reference])> {
+        self.uses
+            .iter()
+            .map(|(target, references)| (*target, references.as_slice()))
+    }
+}
+
+struct Walker {
+    location: Location,
+    path: Vec<Step>,
+    locals: HashSet<i32>,
+    uses: HashMap<Target, Vec<Reference>>,
+}
+
+impl Walker {
+    fn add(&mut self, target: Target) {
+        self.uses.entry(target).or_default().push(Reference {
+            location: self.location,
+            path: self.path.clone(),
+        });
+    }
+
+    fn at(&mut self, step: Step, f: impl FnOnce(&mut Self)) {
+        self.path.push(step);
+        f(self);
+        self.path.pop();
+    }
+
+    fn variable(&mut self, variable: i32) {
+        let target = match self.location {
+            Location::Script(script) if self.locals.contains(&variable) => {
+                Target::LocalVariable { script, variable }
+            }
+            _ => Target::GlobalVariable(variable),
+        };
+
+        self.add(target);
+    }
+
+    fn properties(&mut self, properties: &[ObjectProperty]) {
+        for property in properties {
+            self.at(Step::Property(property.key()), |walker| match property {
+                ObjectProperty::LinkedObjects(linked) => {
+                    for (index, &id) in linked.iter().enumerate() {
+                        walker.at(Step::Element(index), |walker| {
+                            walker.add(Target::Object(id));
+                        });
+                    }
+                }
+                ObjectProperty::Pattern(pattern) => walker.add(Target::Pattern(*pattern)),
+                _ => {}
+            });
+        }
+    }
+
+    fn script(&mut self, script: &NovaScript) {
+        self.locals = script
+            .variables
+            .iter()
+            .map(|variable| variable.variable_id)
+            .collect();
+
+        self.at(Step::Condition, |walker| walker.value(&script.condition));
+
+        for (activator_index, activator) in script.activation_list.iter().enumerate() {
+            for (index, parameter) in activator.parameters.iter().enumerate() {
+                let step = Step::Activator {
+                    activator: activator_index,
+                    parameter: index,
+                };
+
+                self.at(step, |walker| walker.value(parameter));
+            }
+        }
+
+        for (index, parameter) in script.parameters.iter().enumerate() {
+            self.at(Step::Parameter(index), |walker| {
+                walker.value(&parameter.default_value);
+            });
+        }
+
+        for (index, variable) in script.variables.iter().enumerate() {
+            self.at(Step::LocalVariable(index), |walker| {
+                walker.value(&variable.initial_value);
+            });
+        }
+
+        self.actions(&script.actions);
+        self.locals.clear();
+    }
+
+    fn actions(&mut self, actions: &[Action]) {
+        for (index, action) in actions.iter().enumerate() {
+            self.at(Step::Action(index), |walker| {
+                walker.action(&action.action_type);
+            });
+        }
+    }
+
+    fn action(&mut self, action: &ActionType) {
+        match action {
+            ActionType::SetVariable { variable, .. }
+            | ActionType::ResetVariable { variable }
+            | ActionType::SetVariableOverTime { variable, .. } => {
+                self.at(Step::Variable, |walker| walker.variable(*variable));
+            }
+            ActionType::RunFunction { function } => {
+                self.at(Step::Function, |walker| {
+                    walker.add(Target::Script(function.id));
+                });
+            }
+            _ => {}
+        }
+
+        for (index, value) in action.values().into_iter().enumerate() {
+            self.at(Step::Value(index), |walker| walker.value(value));
+        }
+
+        for (index, nested) in action.actions().into_iter().enumerate() {
+            self.at(Step::Nested(index), |walker| walker.actions(nested));
+        }
+    }
+
+    fn value(&mut self, value: &NovaValue) {
+        let inner = &value.inner;
+
+        match value.dynamic_type {
+            // 0 doesn't refer to any object
+            DynamicType::ObjectConstant if inner.int_value != 0 => {
+                self.add(Target::Object(inner.int_value));
+            }
+            DynamicType::ObjectSetConstant => {
+                for (index, &id) in inner.int_list_value.iter().flatten().enumerate() {
+                    self.at(Step::Element(index), |walker| {
+                        walker.add(Target::Object(id));
+                    });
+                }
+            }
+            DynamicType::LayerConstant => self.add(Target::Layer(inner.int_value)),
+            DynamicType::ScriptConstant => self.add(Target::Script(inner.int_value)),
+            ty if ty.is_variable() => self.variable(inner.int_value),
+            _ => {}
+        }
+
+        for (index, sub_value) in inner.sub_values.iter().flatten().enumerate() {
+            self.at(Step::SubValue(index), |walker| walker.value(sub_value));
+        }
+    }
+}
+
+impl Display for Reference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.location)?;
+
+        for step in &self.path {
+            write!(f, " > {step}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Property(key) => write!(f, "property {key:?}"),
+            Self::PrefabId => f.write_str("prefab id"),
+            Self::Item(index) => write!(f, "item {index}"),
+            Self::BrushObject(index) => write!(f, "brush object {index}"),
+            Self::InitialValue => f.write_str("initial value"),
+            Self::Condition => f.write_str("condition"),
+            Self::Activator {
+                activator,
+                parameter,
+            } => write!(f, "activator {activator} parameter {parameter}"),
+            Self::Parameter(index) => write!(f, "parameter {index}"),
+            Self::LocalVariable(index) => write!(f, "local variable {index}"),
+            Self::Action(index) => write!(f, "action {index}"),
+            Self::Nested(index) => write!(f, "nested actions {index}"),
+            Self::Value(index) => write!(f, "value {index}"),
+            Self::Variable => f.write_str("variable"),
+            Self::Function => f.write_str("function"),
+            Self::SubValue(index) => write!(f, "sub value {index}"),
+            Self::Element(index) => write!(f, "element {index}"),
+        }
+    }
+}
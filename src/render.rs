@@ -0,0 +1,417 @@
+//! A simple CPU renderer for previews of levels.
+//!
+//! The renderer draws terrain, shapes and colored objects from above, in the order of their layers. It doesn't
+//! try to look like the game: patterns, sprites, text and particles aren't drawn, and objects with a
+//! [`Pattern`](FillMode::Pattern) fill are drawn with the average of their primary and secondary colors.
+//!
+//! ```
+//! use exolvl::render::{render, RenderOptions};
+//! use exolvl::types::level_data::LevelData;
+//!
+//! let level = LevelData::default();
+//! let image = render(&level, &RenderOptions::new(64, 32));
+//!
+//! assert_eq!(image.dimensions(), (64, 32));
+//! ```
+
+use crate::{
+    catalogue::{self, Category, DEFAULT_SHAPE_COLOR, DEFAULT_TERRAIN_COLOR},
+    error::Error,
+    geometry::{Affine, Polygon, Rect},
+    types::{
+        color::Color, fill_mode::FillMode, foreground_type::ForegroundType, level_data::LevelData,
+        object::Object, properties::Properties, vec2::Vec2,
+    },
+    Write,
+};
+use core::f32::consts::TAU;
+use image::{Rgba, RgbaImage};
+use std::collections::{HashMap, HashSet};
+
+/// The number of points used for a full circle.
+const CIRCLE_SEGMENTS: u16 = 48;
+
+/// Options for [`render`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderOptions {
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// The part of the level to draw, or `None` to draw the whole level with a small margin.
+    ///
+    /// The area is scaled to fit the image while keeping its aspect ratio, and centered.
+    pub area: Option<Rect>,
+    /// The background color, or `None` to use [`LevelData::custom_background_color`].
+    pub background: Option<Color>,
+}
+
+impl RenderOptions {
+    /// Returns options for rendering the whole level into an image of the given size.
+    #[must_use]
+    pub const fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            area: None,
+            background: None,
+        }
+    }
+}
+
+/// Renders a level into an image.
+#[must_use]
+pub fn render(level: &LevelData, options: &RenderOptions) -> RgbaImage {
+    let background = options.background.unwrap_or(level.custom_background_color);
+    let mut canvas = Canvas {
+        image: RgbaImage::from_pixel(options.width, options.height, rgba(background)),
+        view: View::new(level, options),
+    };
+
+    let transforms = level.world_transforms();
+
+    for index in draw_order(level) {
+        canvas.draw_object(&level.objects[index], &transforms[index]);
+    }
+
+    canvas.image
+}
+
+/// Renders a level and writes it as a PNG image.
+///
+/// # Errors
+///
+/// Returns an error if encoding or writing the image fails.
+pub fn render_png(
+    level: &LevelData,
+    options: &RenderOptions,
+    output: &mut impl std::io::Write,
+) -> Result<(), Error> {
+    render(level, options).write(output)
+}
+
+/// Returns the indices of the visible objects of a level, back to front.
+///
+/// Background layers are drawn before foreground layers, and layers of the same kind in the order they are listed
+/// in. Objects in layers that don't exist are drawn below everything else. Within a layer, objects are drawn in the
+/// order of the [children](crate::types::layer::Layer::children) of the layer, and objects in a group in the order
+/// of its [members](Object::group_members), where the group is.
+fn draw_order(level: &LevelData) -> Vec<usize> {
+    let layer_rank = |layer_id: i32| {
+        level
+            .layers
+            .iter()
+            .position(|layer| layer.layer_id == layer_id)
+            .map_or(Some((0, false, 0)), |index| {
+                let layer = &level.layers[index];
+                let foreground = layer.foreground_type == ForegroundType::Foreground;
+
+                (!layer.invisible).then_some((1, foreground, index))
+            })
+    };
+
+    let by_id = level
+        .objects
+        .iter()
+        .rev()
+        .map(|object| (object.entity_id, object))
+        .collect::<HashMap<_, _>>();
+
+    let mut objects = level
+        .objects
+        .iter()
+        .enumerate()
+        .filter_map(|(index, object)| {
+            let rank = layer_rank(object.in_layer)?;
+
+            Some(((rank, stacking_path(level, &by_id, object)), index))
+        })
+        .collect::<Vec<_>>();
+
+    // stable, so objects that aren't listed anywhere keep their order
+    objects.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    objects.into_iter().map(|(_, index)| index).collect()
+}
+
+/// Returns the position of an object in its layer: its index in the children of the layer, followed by the index in
+/// the members of each group down to the object. Objects that aren't listed go after the listed ones.
+fn stacking_path(level: &LevelData, by_id: &HashMap<i32, &Object>, object: &Object) -> Vec<usize> {
+    let position = |list: &[i32], id: i32| list.iter().position(|&other| other == id);
+
+    let mut path = Vec::new();
+    let mut visited = HashSet::new();
+    let mut current = object;
+
+    loop {
+        let group = by_id
+            .get(&current.in_group)
+            .filter(|_| current.in_group != 0 && visited.insert(current.in_group));
+
+        if let Some(group) = group {
+            path.push(position(&group.group_members, current.entity_id).unwrap_or(usize::MAX));
+            current = group;
+        } else {
+            let children = level
+                .layers
+                .iter()
+                .find(|layer| layer.layer_id == current.in_layer)
+                .map_or(&[][..], |layer| &layer.children);
+
+            path.push(position(children, current.entity_id).unwrap_or(usize::MAX));
+            break;
+        }
+    }
+
+    path.reverse();
+    path
+}
+
+/// Maps world coordinates to pixel coordinates.
+#[derive(Clone, Copy, Debug)]
+struct View {
+    /// The world position at the center of the image.
+    center: Vec2,
+    /// Pixels per world unit.
+    scale: f32,
+    /// The center of the image in pixels.
+    image_center: Vec2,
+}
+
+impl View {
+    #[allow(clippy::cast_precision_loss)]
+    fn new(level: &LevelData, options: &RenderOptions) -> Self {
+        let area = options.area.unwrap_or_else(|| {
+            let bounds = level.bounds().unwrap_or_else(|| Rect::point(Vec2::ZERO));
+            let margin = Vec2::new(1.0, 1.0);
+
+            Rect {
+                min: bounds.min - margin,
+                max: bounds.max + margin,
+            }
+        });
+
+        let (width, height) = (options.width as f32, options.height as f32);
+        let scale =
+            (width / area.width().max(f32::EPSILON)).min(height / area.height().max(f32::EPSILON));
+
+        Self {
+            center: area.center(),
+            scale,
+            image_center: Vec2::new(width / 2.0, height / 2.0),
+        }
+    }
+
+    /// World y points up, image y points down.
+    fn to_pixels(self, point: Vec2) -> Vec2 {
+        let offset = (point - self.center) * self.scale;
+
+        Vec2::new(
+            self.image_center.x.0 + offset.x.0,
+            self.image_center.y.0 - offset.y.0,
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Canvas {
+    image: RgbaImage,
+    view: View,
+}
+
+impl Canvas {
+    fn draw_object(&mut self, object: &Object, transform: &Affine) {
+        let is_shape = object
+            .tile()
+            .is_some_and(|tile| tile.category == Category::Shape);
+
+        let (rings, default_color) =
+            if let Some(polygons) = Polygon::from_terrain_with(object, transform) {
+                let rings = polygons
+                    .iter()
+                    .flat_map(|polygon| polygon.rings().map(<[Vec2]>::to_vec))
+                    .collect();
+
+                (rings, DEFAULT_TERRAIN_COLOR)
+            } else if object.tile_id == catalogue::CIRCLE {
+                let radius = object.radius().unwrap_or(0.5);
+                let points = ellipse(Vec2::ZERO, Vec2::new(radius, radius), CIRCLE_SEGMENTS);
+
+                let ring = points.into_iter().map(|p| transform.apply(p)).collect();
+
+                (vec![ring], DEFAULT_SHAPE_COLOR)
+            } else if is_shape || object.color().is_some() {
+                let size = Vec2::new(
+                    object.width().unwrap_or(1.0),
+                    object.height().unwrap_or(1.0),
+                );
+                let radius = object.corner_radius().unwrap_or(0.0);
+                let points = rounded_rect(size, radius);
+
+                let ring = points.into_iter().map(|p| transform.apply(p)).collect();
+
+                (vec![ring], DEFAULT_SHAPE_COLOR)
+            } else {
+                return;
+            };
+
+        let fill = fill_color(object, default_color);
+        self.fill(&rings, fill);
+
+        let border = object.border_thickness().unwrap_or(0.0) * transform.x_axis.length();
+
+        if let (Some(color), true) = (object.border_color(), border > 0.0) {
+            for ring in &rings {
+                for (&a, &b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                    self.fill(&[thick_line(a, b, border)], color);
+                }
+            }
+        }
+    }
+
+    /// Fills the area inside an odd number of rings (given in world coordinates) with a color.
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    fn fill(&mut self, rings: &[Vec<Vec2>], color: Color) {
+        let rings = rings
+            .iter()
+            .map(|ring| {
+                ring.iter()
+                    .map(|&point| self.view.to_pixels(point))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let Some(bounds) = Rect::from_points(rings.iter().flatten().copied()) else {
+            return;
+        };
+
+        let (width, height) = self.image.dimensions();
+        let y_start = bounds.min.y.0.floor().max(0.0) as u32;
+        let y_end = (bounds.max.y.0.ceil().max(0.0) as u32).min(height);
+
+        let mut crossings = Vec::new();
+
+        for y in y_start..y_end {
+            let center = y as f32 + 0.5;
+            crossings.clear();
+
+            for ring in &rings {
+                for (a, b) in ring.iter().zip(ring.iter().cycle().skip(1)) {
+                    let (ay, by) = (a.y.0, b.y.0);
+
+                    if (ay > center) != (by > center) {
+                        crossings.push(((center - ay) / (by - ay)).mul_add(b.x.0 - a.x.0, a.x.0));
+                    }
+                }
+            }
+
+            crossings.sort_by(f32::total_cmp);
+
+            for span in crossings.chunks_exact(2) {
+                // pixels whose center is inside the span
+                let x_start = (span[0] - 0.5).ceil().max(0.0) as u32;
+                let x_end = ((span[1] - 0.5).ceil().max(0.0) as u32).min(width);
+
+                for x in x_start..x_end {
+                    blend(self.image.get_pixel_mut(x, y), color);
+                }
+            }
+        }
+    }
+}
+
+fn fill_color(object: &Object, default: Color) -> Color {
+    let color = object.color().unwrap_or(default);
+
+    match (object.fill_mode(), object.secondary_color()) {
+        (Some(FillMode::Pattern), Some(secondary)) => Color::new(
+            f32::midpoint(color.r.0, secondary.r.0),
+            f32::midpoint(color.g.0, secondary.g.0),
+            f32::midpoint(color.b.0, secondary.b.0),
+            f32::midpoint(color.a.0, secondary.a.0),
+        ),
+        _ => color,
+    }
+}
+
+/// Returns the points of an axis-aligned rectangle of the given size centered on the origin, with rounded corners.
+fn rounded_rect(size: Vec2, radius: f32) -> Vec<Vec2> {
+    let half = size / 2.0;
+    let radius = radius.min(half.x.0).min(half.y.0).max(0.0);
+
+    if radius == 0.0 {
+        return vec![
+            Vec2::new(-half.x.0, -half.y.0),
+            Vec2::new(half.x.0, -half.y.0),
+            Vec2::new(half.x.0, half.y.0),
+            Vec2::new(-half.x.0, half.y.0),
+        ];
+    }
+
+    let inner = Vec2::new(half.x.0 - radius, half.y.0 - radius);
+    let corners = [
+        Vec2::new(inner.x.0, inner.y.0),
+        Vec2::new(-inner.x.0, inner.y.0),
+        Vec2::new(-inner.x.0, -inner.y.0),
+        Vec2::new(inner.x.0, -inner.y.0),
+    ];
+    let steps = CIRCLE_SEGMENTS / 4;
+
+    (0_u16..)
+        .zip(corners)
+        .flat_map(|(quarter, corner)| {
+            (0..=steps).map(move |step| {
+                let angle = (f32::from(quarter) + f32::from(step) / f32::from(steps)) * TAU / 4.0;
+                corner + Vec2::new(angle.cos(), angle.sin()) * radius
+            })
+        })
+        .collect()
+}
+
+fn ellipse(center: Vec2, radius: Vec2, segments: u16) -> Vec<Vec2> {
+    (0..segments)
+        .map(|i| {
+            let angle = f32::from(i) / f32::from(segments) * TAU;
+            center + Vec2::new(angle.cos() * radius.x.0, angle.sin() * radius.y.0)
+        })
+        .collect()
+}
+
+/// Returns a rectangle around the line from `a` to `b` with the given thickness.
+fn thick_line(a: Vec2, b: Vec2, thickness: f32) -> Vec<Vec2> {
+    let direction = (b - a).normalize();
+    let normal = Vec2::new(-direction.y.0, direction.x.0) * (thickness / 2.0);
+
+    vec![a + normal, b + normal, b - normal, a - normal]
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn rgba(color: Color) -> Rgba<u8> {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    Rgba([
+        channel(color.r.0),
+        channel(color.g.0),
+        channel(color.b.0),
+        channel(color.a.0),
+    ])
+}
+
+/// Draws `color` over a pixel.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn blend(pixel: &mut Rgba<u8>, color: Color) {
+    let alpha = color.a.0.clamp(0.0, 1.0);
+    let source = rgba(color);
+
+    for (destination, &source) in pixel.0.iter_mut().zip(&source.0).take(3) {
+        let mixed = f32::from(source).mul_add(alpha, f32::from(*destination) * (1.0 - alpha));
+        *destination = mixed.round() as u8;
+    }
+
+    let destination_alpha = f32::from(pixel.0[3]) / 255.0;
+    pixel.0[3] = (alpha.mul_add(1.0 - destination_alpha, destination_alpha) * 255.0).round() as u8;
+}
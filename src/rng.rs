@@ -0,0 +1,34 @@
+//! The random number generator used by the [`interpreter`](crate::interpreter) and brushes.
+
+/// A xorshift64 generator. The same seed always gives the same numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub const fn new(seed: u64) -> Self {
+        // xorshift gets stuck on a zero state
+        Self(if seed == 0 {
+            0x2545_f491_4f6c_dd1d
+        } else {
+            seed
+        })
+    }
+
+    pub const fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a number in `0.0..1.0`.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn unit(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns a number in `-1.0..1.0`.
+    pub fn signed(&mut self) -> f32 {
+        self.unit().mul_add(2.0, -1.0)
+    }
+}
@@ -0,0 +1,320 @@
+//! A spatial index over the objects of a level.
+//!
+//! ```
+//! use exolvl::geometry::Rect;
+//! use exolvl::spatial::SpatialIndex;
+//! use exolvl::types::level_data::LevelData;
+//! use exolvl::types::vec2::Vec2;
+//!
+//! let level = LevelData::default();
+//! let index = SpatialIndex::new(&level.objects);
+//!
+//! assert!(index.query_rect(Rect::point(Vec2::ZERO)).is_empty());
+//! assert!(index.nearest(Vec2::ZERO).is_none());
+//! ```
+
+use crate::{
+    geometry::{self, Rect},
+    types::{object::Object, vec2::Vec2},
+};
+use std::collections::HashMap;
+
+/// The default size of the cells of a [`SpatialIndex`], in world units.
+pub const DEFAULT_CELL_SIZE: f32 = 8.0;
+
+/// Objects that cover more cells than this are kept out of the grid.
+const MAX_OBJECT_CELLS: i64 = 1024;
+
+/// Cell coordinates further from the origin than this are kept out of the grid, so cell arithmetic can't overflow.
+const MAX_CELL: f32 = 16_777_216.0;
+
+/// A uniform grid over the world-space [bounds](Object::bounds) of a list of objects, for finding the objects in an
+/// area without looking at every object.
+///
+/// Objects in a group are placed through the groups around them, which are looked up in the same list, see
+/// [`geometry::world_transforms`]. Objects that are very large, very far away or have bounds that aren't finite are
+/// kept in a list that every query checks, instead of in the grid.
+///
+/// The index borrows the objects, so it has to be rebuilt after they are changed.
+#[derive(Clone, Debug)]
+pub struct SpatialIndex<'a> {
+    objects: &'a [Object],
+    bounds: Vec<Rect>,
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    /// The objects that aren't in the grid.
+    outside: Vec<usize>,
+    /// The range of cells that contain objects, as (min, max).
+    extent: Option<((i32, i32), (i32, i32))>,
+}
+
+impl<'a> SpatialIndex<'a> {
+    /// Builds an index over `objects` with the [default cell size](DEFAULT_CELL_SIZE).
+    #[must_use]
+    pub fn new(objects: &'a [Object]) -> Self {
+        Self::with_cell_size(objects, DEFAULT_CELL_SIZE)
+    }
+
+    /// Builds an index over `objects` with cells of the given size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cell_size` isn't positive and finite.
+    #[must_use]
+    pub fn with_cell_size(objects: &'a [Object], cell_size: f32) -> Self {
+        assert!(
+            cell_size > 0.0 && cell_size.is_finite(),
+            "the cell size must be positive and finite"
+        );
+
+        let mut index = Self {
+            objects,
+            bounds: objects
+                .iter()
+                .zip(geometry::world_transforms(objects))
+                .map(|(object, transform)| object.bounds_with(&transform))
+                .collect(),
+            cell_size,
+            cells: HashMap::new(),
+            outside: Vec::new(),
+            extent: None,
+        };
+
+        for (i, &bounds) in index.bounds.iter().enumerate() {
+            let range = index
+                .cell(bounds.min)
+                .zip(index.cell(bounds.max))
+                .filter(|&(min, max)| cell_count(min, max) <= MAX_OBJECT_CELLS);
+
+            let Some((min, max)) = range else {
+                index.outside.push(i);
+                continue;
+            };
+
+            for x in min.0..=max.0 {
+                for y in min.1..=max.1 {
+                    index.cells.entry((x, y)).or_default().push(i);
+                }
+            }
+
+            index.extent = Some(index.extent.map_or((min, max), |(emin, emax)| {
+                (
+                    (emin.0.min(min.0), emin.1.min(min.1)),
+                    (emax.0.max(max.0), emax.1.max(max.1)),
+                )
+            }));
+        }
+
+        index
+    }
+
+    /// Returns the objects whose bounds overlap `rect`, in the order they are stored in.
+    #[must_use]
+    pub fn query_rect(&self, rect: Rect) -> Vec<&'a Object> {
+        self.query(rect, |bounds| bounds.intersects(rect))
+    }
+
+    /// Returns the objects whose bounds are at most `radius` away from `center`, in the order they are stored in.
+    #[must_use]
+    pub fn query_radius(&self, center: Vec2, radius: f32) -> Vec<&'a Object> {
+        let offset = Vec2::new(radius, radius);
+        let rect = Rect {
+            min: center - offset,
+            max: center + offset,
+        };
+
+        self.query(rect, |bounds| bounds.distance_to(center) <= radius)
+    }
+
+    /// Returns the object whose bounds are the closest to `point`. Of objects at the same distance, the one stored
+    /// first is returned.
+    #[must_use]
+    pub fn nearest(&self, point: Vec2) -> Option<&'a Object> {
+        self.nearest_index(point).map(|i| &self.objects[i])
+    }
+
+    fn nearest_index(&self, point: Vec2) -> Option<usize> {
+        let mut best = None;
+
+        for &i in &self.outside {
+            self.consider(&mut best, point, i);
+        }
+
+        let Some((min, max)) = self.extent else {
+            return best.map(|(_, i)| i);
+        };
+
+        let center = self.cell(point).filter(|center| {
+            (min.0..=max.0).contains(&center.0) && (min.1..=max.1).contains(&center.1)
+        });
+
+        let Some(center) = center else {
+            // searching outwards from far away cells would visit a lot of empty cells
+            return self.nearest_brute_force(point);
+        };
+
+        // the furthest ring of cells around `center` that can contain objects
+        let max_ring = [
+            center.0 - min.0,
+            max.0 - center.0,
+            center.1 - min.1,
+            max.1 - center.1,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+
+        // with objects spread far apart, most rings are empty, and looking at every object is faster
+        let budget = self.cells.len().saturating_mul(4).saturating_add(64);
+        let mut visited = 0;
+
+        for ring in 0..=max_ring {
+            // objects in rings further out are at least this far away
+            #[allow(clippy::cast_precision_loss)]
+            let ring_distance = (ring - 1).max(0) as f32 * self.cell_size;
+
+            if best.is_some_and(|(distance, _)| distance < ring_distance) {
+                break;
+            }
+
+            let cells = ring_cells(center, ring);
+            visited += cells.len();
+
+            if visited > budget {
+                return self.nearest_brute_force(point);
+            }
+
+            for cell in cells {
+                for &i in self.cells.get(&cell).into_iter().flatten() {
+                    self.consider(&mut best, point, i);
+                }
+            }
+        }
+
+        best.map(|(_, i)| i)
+    }
+
+    fn nearest_brute_force(&self, point: Vec2) -> Option<usize> {
+        self.bounds
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.distance_to(point).total_cmp(&b.distance_to(point)))
+            .map(|(i, _)| i)
+    }
+
+    /// Replaces `best` with object `i` if it's closer to `point`, or as close and stored first.
+    fn consider(&self, best: &mut Option<(f32, usize)>, point: Vec2, i: usize) {
+        let distance = self.bounds[i].distance_to(point);
+
+        if best.is_none_or(|(best_distance, best_index)| {
+            distance
+                .total_cmp(&best_distance)
+                .then(i.cmp(&best_index))
+                .is_lt()
+        }) {
+            *best = Some((distance, i));
+        }
+    }
+
+    fn query(&self, rect: Rect, filter: impl Fn(Rect) -> bool) -> Vec<&'a Object> {
+        let mut found = self.outside.clone();
+
+        if let Some((min, max)) = self.occupied_range(rect) {
+            let occupied = i64::try_from(self.cells.len()).unwrap_or(i64::MAX);
+
+            if cell_count(min, max) <= occupied {
+                for x in min.0..=max.0 {
+                    for y in min.1..=max.1 {
+                        found.extend(self.cells.get(&(x, y)).into_iter().flatten().copied());
+                    }
+                }
+            } else {
+                // the range has more cells than there are occupied cells
+                for (&(x, y), cell) in &self.cells {
+                    if (min.0..=max.0).contains(&x) && (min.1..=max.1).contains(&y) {
+                        found.extend(cell);
+                    }
+                }
+            }
+        }
+
+        found.sort_unstable();
+        found.dedup();
+
+        found
+            .into_iter()
+            .filter(|&i| filter(self.bounds[i]))
+            .map(|i| &self.objects[i])
+            .collect()
+    }
+
+    /// Returns the cell containing a point, or `None` if it's too far away for the grid or isn't finite.
+    fn cell(&self, point: Vec2) -> Option<(i32, i32)> {
+        let cell = |value: f32| {
+            let cell = (value / self.cell_size).floor();
+
+            #[allow(clippy::cast_possible_truncation)]
+            (cell.abs() <= MAX_CELL).then_some(cell as i32)
+        };
+
+        Some((cell(point.x.0)?, cell(point.y.0)?))
+    }
+
+    /// Returns the range of cells of `rect` that lie within the extent of the grid, or `None` if there are none.
+    fn occupied_range(&self, rect: Rect) -> Option<((i32, i32), (i32, i32))> {
+        let (emin, emax) = self.extent?;
+
+        let axis = |min: f32, max: f32, emin: i32, emax: i32| {
+            let (min, max) = (
+                (min / self.cell_size).floor(),
+                (max / self.cell_size).floor(),
+            );
+
+            #[allow(clippy::cast_precision_loss)]
+            let (emin, emax) = (emin as f32, emax as f32);
+
+            // false for NaN as well
+            if !(min <= max && min <= emax && max >= emin) {
+                return None;
+            }
+
+            #[allow(clippy::cast_possible_truncation)]
+            Some((min.max(emin) as i32, max.min(emax) as i32))
+        };
+
+        let (min_x, max_x) = axis(rect.min.x.0, rect.max.x.0, emin.0, emax.0)?;
+        let (min_y, max_y) = axis(rect.min.y.0, rect.max.y.0, emin.1, emax.1)?;
+
+        Some(((min_x, min_y), (max_x, max_y)))
+    }
+}
+
+/// Returns the number of cells in a range.
+fn cell_count(min: (i32, i32), max: (i32, i32)) -> i64 {
+    let width = i64::from(max.0) - i64::from(min.0) + 1;
+    let height = i64::from(max.1) - i64::from(min.1) + 1;
+
+    width.saturating_mul(height)
+}
+
+/// Returns the cells at a Chebyshev distance of exactly `ring` from `center`.
+fn ring_cells(center: (i32, i32), ring: i32) -> Vec<(i32, i32)> {
+    if ring == 0 {
+        return vec![center];
+    }
+
+    let (cx, cy) = center;
+    let mut cells = Vec::new();
+
+    for d in -ring..=ring {
+        cells.push((cx + d, cy - ring));
+        cells.push((cx + d, cy + ring));
+    }
+
+    for d in -ring + 1..ring {
+        cells.push((cx - ring, cy + d));
+        cells.push((cx + ring, cy + d));
+    }
+
+    cells
+}
@@ -0,0 +1,237 @@
+//! Statistics about the contents of a level.
+//!
+//! [`LevelData::stats`] counts the things that make a level expensive to load and run: objects, terrain vertices,
+//! particle systems, script actions and loops, and the images of patterns and prefabs.
+//!
+//! ```
+//! use exolvl::types::level_data::LevelData;
+//!
+//! let stats = LevelData::default().stats();
+//!
+//! assert_eq!(stats.objects, 0);
+//! assert_eq!(stats.max_action_depth, 0);
+//! ```
+
+use crate::{
+    catalogue,
+    types::{
+        action_type::ActionType,
+        image::Image,
+        level_data::LevelData,
+        novascript::action::Action,
+        object_property::{ObjectProperty, PropertyKey},
+    },
+};
+use core::fmt::{self, Display, Formatter};
+use std::collections::BTreeMap;
+
+/// Statistics about a level, see [`LevelData::stats`].
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LevelStats {
+    /// The number of objects.
+    pub objects: usize,
+    /// The number of objects with each tile id.
+    pub objects_by_tile: BTreeMap<i32, usize>,
+    /// The number of objects in each layer, by layer id.
+    pub objects_by_layer: BTreeMap<i32, usize>,
+    /// The number of objects that have each kind of property.
+    pub objects_by_property: BTreeMap<PropertyKey, usize>,
+    /// The number of corners of all terrain.
+    pub terrain_vertices: usize,
+    /// The number of particle systems, which are objects with particle or emitter properties.
+    pub particle_systems: usize,
+    /// The number of scripts, including functions.
+    pub scripts: usize,
+    /// The number of actions in all scripts, including nested actions.
+    pub actions: usize,
+    /// The deepest nesting of actions. Actions directly in a script have a depth of 1.
+    pub max_action_depth: usize,
+    /// The number of `Repeat` and `RepeatWhile` actions.
+    pub loops: usize,
+    /// The number of global variables.
+    pub global_variables: usize,
+    /// The number of local variables of all scripts.
+    pub local_variables: usize,
+    /// Statistics about each pattern, in the order they are stored in.
+    pub patterns: Vec<PatternStats>,
+    /// The number of bytes the frames of all patterns and the images of all prefabs take up when they are decoded,
+    /// see [`PatternStats::image_bytes`].
+    pub image_bytes: u64,
+    /// The number of bytes the frames of all patterns and the images of all prefabs take up in the level, see
+    /// [`PatternStats::encoded_bytes`].
+    pub encoded_image_bytes: u64,
+}
+
+/// Statistics about a pattern.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct PatternStats {
+    /// The id of the pattern.
+    pub pattern_id: i32,
+    /// The number of frames.
+    pub frames: usize,
+    /// The number of bytes the frames take up when they are decoded to RGBA, with 4 bytes per pixel.
+    ///
+    /// Frames whose size is [unknown](crate::types::image::Image::dimensions) aren't counted.
+    pub image_bytes: u64,
+    /// The number of bytes the encoded frames take up in the level, see
+    /// [`Image::encoded_len`](crate::types::image::Image::encoded_len).
+    pub encoded_bytes: u64,
+}
+
+impl LevelData {
+    /// Returns statistics about the contents of the level.
+    #[must_use]
+    pub fn stats(&self) -> LevelStats {
+        let mut stats = LevelStats {
+            objects: self.objects.len(),
+            scripts: self.nova_scripts.len(),
+            global_variables: self.global_variables.len(),
+            ..LevelStats::default()
+        };
+
+        for object in &self.objects {
+            *stats.objects_by_tile.entry(object.tile_id).or_default() += 1;
+            *stats.objects_by_layer.entry(object.in_layer).or_default() += 1;
+
+            let mut particle_system = false;
+
+            for property in &object.properties {
+                *stats.objects_by_property.entry(property.key()).or_default() += 1;
+
+                match property {
+                    ObjectProperty::TerrainCorners(rings) => {
+                        stats.terrain_vertices += rings.iter().map(Vec::len).sum::<usize>();
+                    }
+                    ObjectProperty::ParticleTexture(_) | ObjectProperty::EmitterShape(_) => {
+                        particle_system = true;
+                    }
+                    _ => {}
+                }
+            }
+
+            stats.particle_systems += usize::from(particle_system);
+        }
+
+        for script in &self.nova_scripts {
+            stats.local_variables += script.variables.len();
+            count_actions(&script.actions, 1, &mut stats);
+        }
+
+        stats.patterns = self
+            .patterns
+            .iter()
+            .map(|pattern| PatternStats {
+                pattern_id: pattern.pattern_id,
+                frames: pattern.pattern_frames.len(),
+                image_bytes: pattern.pattern_frames.iter().map(decoded_len).sum(),
+                encoded_bytes: pattern.pattern_frames.iter().map(encoded_len).sum(),
+            })
+            .collect();
+
+        let prefab_images = self.prefabs.iter().map(|prefab| &prefab.prefab_image_data);
+
+        stats.image_bytes = stats
+            .patterns
+            .iter()
+            .map(|pattern| pattern.image_bytes)
+            .chain(prefab_images.clone().map(decoded_len))
+            .sum();
+        stats.encoded_image_bytes = stats
+            .patterns
+            .iter()
+            .map(|pattern| pattern.encoded_bytes)
+            .chain(prefab_images.map(encoded_len))
+            .sum();
+
+        stats
+    }
+}
+
+/// Returns the number of bytes an image takes up when it's decoded to RGBA, or 0 if its size is unknown.
+fn decoded_len(image: &Image) -> u64 {
+    image.dimensions().map_or(0, |(width, height)| {
+        u64::from(width) * u64::from(height) * 4
+    })
+}
+
+fn encoded_len(image: &Image) -> u64 {
+    image
+        .encoded_len()
+        .map_or(0, |len| u64::try_from(len).unwrap_or(u64::MAX))
+}
+
+fn count_actions(actions: &[Action], depth: usize, stats: &mut LevelStats) {
+    if !actions.is_empty() {
+        stats.max_action_depth = stats.max_action_depth.max(depth);
+    }
+
+    for action in actions {
+        stats.actions += 1;
+
+        if matches!(
+            action.action_type,
+            ActionType::Repeat { .. } | ActionType::RepeatWhile { .. }
+        ) {
+            stats.loops += 1;
+        }
+
+        for nested in action.action_type.actions() {
+            count_actions(nested, depth + 1, stats);
+        }
+    }
+}
+
+impl Display for LevelStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "objects: {}", self.objects)?;
+        writeln!(f, "objects by tile:")?;
+
+        let mut tiles = self.objects_by_tile.iter().collect::<Vec<_>>();
+        tiles.sort_by_key(|&(_, count)| core::cmp::Reverse(*count));
+
+        for (tile_id, count) in tiles {
+            match catalogue::lookup(*tile_id) {
+                Some(tile) => writeln!(f, "    {}: {count}", tile.name)?,
+                None => writeln!(f, "    tile {tile_id}: {count}")?,
+            }
+        }
+
+        writeln!(f, "objects by layer:")?;
+
+        for (layer_id, count) in &self.objects_by_layer {
+            writeln!(f, "    layer {layer_id}: {count}")?;
+        }
+
+        writeln!(f, "objects by property:")?;
+
+        for (key, count) in &self.objects_by_property {
+            writeln!(f, "    {key:?}: {count}")?;
+        }
+
+        writeln!(f, "terrain vertices: {}", self.terrain_vertices)?;
+        writeln!(f, "particle systems: {}", self.particle_systems)?;
+        writeln!(f, "scripts: {}", self.scripts)?;
+        writeln!(f, "actions: {}", self.actions)?;
+        writeln!(f, "deepest action nesting: {}", self.max_action_depth)?;
+        writeln!(f, "loops: {}", self.loops)?;
+        writeln!(f, "global variables: {}", self.global_variables)?;
+        writeln!(f, "local variables: {}", self.local_variables)?;
+        writeln!(f, "patterns: {}", self.patterns.len())?;
+
+        for pattern in &self.patterns {
+            writeln!(
+                f,
+                "    pattern {}: {} frames, {} bytes ({} encoded)",
+                pattern.pattern_id, pattern.frames, pattern.image_bytes, pattern.encoded_bytes
+            )?;
+        }
+
+        writeln!(
+            f,
+            "image bytes: {} ({} encoded)",
+            self.image_bytes, self.encoded_image_bytes
+        )
+    }
+}
@@ -0,0 +1,519 @@
+//! Import of SVG paths and polygons as terrain.
+
+use super::path::{self, Tokens};
+use crate::{
+    catalogue::{self, TileInfo},
+    error::Error,
+    geometry::Rect,
+    types::{color::Color, level_data::LevelData, properties::Properties, vec2::Vec2},
+};
+
+/// Options for [`import_svg`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImportOptions {
+    /// The id of the layer the terrain is added to.
+    pub layer_id: i32,
+    /// How far flattened curves may be from the original curves, in SVG units.
+    pub tolerance: f32,
+    /// The number of SVG units per level unit.
+    pub scale: f32,
+    /// The color of terrain whose path doesn't have a fill color, or `None` to leave the color unset.
+    pub default_color: Option<Color>,
+}
+
+impl ImportOptions {
+    /// Returns the default options for importing into the given layer.
+    #[must_use]
+    pub const fn new(layer_id: i32) -> Self {
+        Self {
+            layer_id,
+            tolerance: 0.05,
+            scale: 1.0,
+            default_color: None,
+        }
+    }
+}
+
+/// A terrain shape to import, in level coordinates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shape {
+    /// The outlines of the shape. Outlines inside other outlines are holes.
+    pub rings: Vec<Vec<Vec2>>,
+    /// The color of the terrain, if it has one.
+    pub color: Option<Color>,
+}
+
+/// Imports every `<path>` and `<polygon>` in an SVG document as a terrain object, and returns the entity ids of the
+/// new objects.
+///
+/// Transforms on the elements and their groups are applied, and the y-axis is flipped. Elements inside `<defs>` and
+/// similar elements that aren't drawn directly are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the document or the path data is malformed, or if the level doesn't have the layer. Nothing is
+/// added to the level in that case.
+pub fn import_svg(
+    level: &mut LevelData,
+    svg: &str,
+    options: &ImportOptions,
+) -> Result<Vec<i32>, Error> {
+    let mut shapes = parse_svg(svg, options)?;
+
+    if let Some(color) = options.default_color {
+        for shape in &mut shapes {
+            shape.color.get_or_insert(color);
+        }
+    }
+
+    import_shapes(level, shapes, options.layer_id)
+}
+
+/// Adds a terrain object for every shape to a layer of the level, and returns the entity ids of the new objects.
+///
+/// Each object gets a new entity id, is placed at the center of its shape and starts with the
+/// [default properties](TileInfo::default_properties) and [physics defaults](TileInfo::physics_defaults) of terrain.
+/// Shapes without a ring of at least 3 points are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the level doesn't have the layer. Nothing is added to the level in that case.
+pub fn import_shapes(
+    level: &mut LevelData,
+    shapes: impl IntoIterator<Item = Shape>,
+    layer_id: i32,
+) -> Result<Vec<i32>, Error> {
+    if !level.layers.iter().any(|layer| layer.layer_id == layer_id) {
+        return Err(Error::LayerNotFound(layer_id));
+    }
+
+    let mut ids = Vec::new();
+
+    for shape in shapes {
+        let rings = shape
+            .rings
+            .into_iter()
+            .filter(|ring| ring.len() >= 3)
+            .collect::<Vec<_>>();
+
+        let Some(bounds) = Rect::from_points(rings.iter().flatten().copied()) else {
+            continue;
+        };
+        let center = bounds.center();
+
+        let tile = terrain_tile();
+        let mut object = tile.new_object(0, center, layer_id);
+
+        for property in tile.physics_defaults {
+            object.set_property(property.clone());
+        }
+
+        object.set_terrain_corners(
+            rings
+                .into_iter()
+                .map(|ring| ring.into_iter().map(|point| point - center).collect())
+                .collect(),
+        );
+
+        if let Some(color) = shape.color {
+            object.set_color(color);
+        }
+
+        ids.push(level.add_object(object)?);
+    }
+
+    Ok(ids)
+}
+
+fn terrain_tile() -> &'static TileInfo {
+    catalogue::lookup(catalogue::TERRAIN).expect("the catalogue contains terrain")
+}
+
+/// Parses the `<path>` and `<polygon>` elements of an SVG document into shapes in level coordinates.
+///
+/// # Errors
+///
+/// Returns an error if the document or the path data is malformed.
+pub fn parse_svg(svg: &str, options: &ImportOptions) -> Result<Vec<Shape>, Error> {
+    // the transform and fill color of every open element
+    let mut stack: Vec<(Transform, Option<Color>)> = Vec::new();
+    // how many elements whose content isn't drawn are open
+    let mut hidden = 0_usize;
+    let mut shapes = Vec::new();
+
+    for tag in Tags::new(svg) {
+        let tag = tag?;
+
+        if tag.kind == TagKind::Close {
+            if stack.pop().is_none() {
+                return Err(invalid(&format!("unexpected closing tag '{}'", tag.name)));
+            }
+            if NOT_DRAWN.contains(&tag.name) {
+                hidden = hidden.saturating_sub(1);
+            }
+            continue;
+        }
+
+        let (parent_transform, parent_fill) =
+            stack.last().copied().unwrap_or((Transform::IDENTITY, None));
+
+        let transform = match tag.attribute("transform") {
+            Some(transform) => parent_transform.then(Transform::parse(transform)?),
+            None => parent_transform,
+        };
+        let fill = parse_fill(&tag).or(parent_fill);
+
+        if hidden == 0 {
+            let rings = match tag.name {
+                "path" => tag
+                    .attribute("d")
+                    .map(|data| path::parse(data, options.tolerance))
+                    .transpose()?,
+                "polygon" => tag.attribute("points").map(parse_points).transpose()?,
+                _ => None,
+            };
+
+            if let Some(rings) = rings {
+                let to_level = |point: Vec2| {
+                    let point = transform.apply(point);
+                    Vec2::new(point.x.0 / options.scale, -point.y.0 / options.scale)
+                };
+
+                shapes.push(Shape {
+                    rings: rings
+                        .into_iter()
+                        .map(|ring| ring.into_iter().map(to_level).collect())
+                        .collect(),
+                    color: fill,
+                });
+            }
+        }
+
+        if tag.kind == TagKind::Open {
+            stack.push((transform, fill));
+
+            if NOT_DRAWN.contains(&tag.name) {
+                hidden += 1;
+            }
+        }
+    }
+
+    Ok(shapes)
+}
+
+/// Elements whose content isn't drawn where it is defined.
+const NOT_DRAWN: &[&str] = &[
+    "defs", "clipPath", "mask", "marker", "pattern", "symbol", "metadata",
+];
+
+fn parse_points(points: &str) -> Result<Vec<Vec<Vec2>>, Error> {
+    let mut tokens = Tokens::new(points);
+    let mut ring = Vec::new();
+
+    while !tokens.is_empty() {
+        ring.push(Vec2::new(tokens.number()?, tokens.number()?));
+    }
+
+    Ok(vec![ring])
+}
+
+fn parse_fill(tag: &Tag) -> Option<Color> {
+    let style = tag.attribute("style").unwrap_or_default();
+    let style_property = |name: &str| {
+        style.split(';').find_map(|declaration| {
+            let (key, value) = declaration.split_once(':')?;
+            (key.trim() == name).then(|| value.trim())
+        })
+    };
+
+    let fill = style_property("fill").or_else(|| tag.attribute("fill"))?;
+    let mut color = parse_color(fill)?;
+
+    if let Some(opacity) = style_property("fill-opacity")
+        .or_else(|| tag.attribute("fill-opacity"))
+        .and_then(|opacity| opacity.parse::<f32>().ok())
+    {
+        color.a = (color.a.0 * opacity.clamp(0.0, 1.0)).into();
+    }
+
+    Some(color)
+}
+
+/// Parses a color in `#rgb`, `#rrggbb` or `rgb(r, g, b)` notation, or a few common color names.
+fn parse_color(color: &str) -> Option<Color> {
+    let color = color.trim();
+
+    let rgb = |r: f32, g: f32, b: f32| Some(Color::new(r / 255.0, g / 255.0, b / 255.0, 1.0));
+    let hex = |digits: &str| u8::from_str_radix(digits, 16).ok().map(f32::from);
+
+    if let Some(digits) = color.strip_prefix('#') {
+        // the digits are sliced by byte offsets
+        if !digits.is_ascii() {
+            return None;
+        }
+
+        return match digits.len() {
+            3 => {
+                let digit = |i: usize| hex(&digits[i..=i]).map(|value| value * 17.0);
+                rgb(digit(0)?, digit(1)?, digit(2)?)
+            }
+            6 => rgb(
+                hex(&digits[0..2])?,
+                hex(&digits[2..4])?,
+                hex(&digits[4..6])?,
+            ),
+            _ => None,
+        };
+    }
+
+    if let Some(arguments) = color
+        .strip_prefix("rgb(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let mut channels = arguments.split(',').map(|channel| {
+            let channel = channel.trim();
+
+            channel.strip_suffix('%').map_or_else(
+                || channel.parse::<f32>().ok(),
+                |percent| percent.trim().parse::<f32>().ok().map(|p| p * 2.55),
+            )
+        });
+
+        return rgb(channels.next()??, channels.next()??, channels.next()??);
+    }
+
+    match color {
+        "black" => rgb(0.0, 0.0, 0.0),
+        "white" => rgb(255.0, 255.0, 255.0),
+        "red" => rgb(255.0, 0.0, 0.0),
+        "green" => rgb(0.0, 128.0, 0.0),
+        "blue" => rgb(0.0, 0.0, 255.0),
+        "yellow" => rgb(255.0, 255.0, 0.0),
+        "gray" | "grey" => rgb(128.0, 128.0, 128.0),
+        _ => None,
+    }
+}
+
+/// A 2D affine transform, as the SVG matrix `[a b c d e f]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Transform([f32; 6]);
+
+impl Transform {
+    const IDENTITY: Self = Self([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+
+    /// Parses the value of a `transform` attribute.
+    fn parse(value: &str) -> Result<Self, Error> {
+        let mut transform = Self::IDENTITY;
+        let mut rest = value;
+
+        while let Some((name, after_name)) = rest.split_once('(') {
+            let (arguments, after) = after_name
+                .split_once(')')
+                .ok_or_else(|| invalid(&format!("invalid transform '{value}'")))?;
+
+            let mut tokens = Tokens::new(arguments);
+            let mut numbers = Vec::new();
+            while !tokens.is_empty() {
+                numbers.push(tokens.number()?);
+            }
+
+            let argument = |i: usize| numbers.get(i).copied();
+            let name = name.trim_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+
+            let next = match (name, numbers.len()) {
+                ("matrix", 6) => Self([
+                    numbers[0], numbers[1], numbers[2], numbers[3], numbers[4], numbers[5],
+                ]),
+                ("translate", 1 | 2) => {
+                    Self([1.0, 0.0, 0.0, 1.0, numbers[0], argument(1).unwrap_or(0.0)])
+                }
+                ("scale", 1 | 2) => {
+                    let x = numbers[0];
+                    Self([x, 0.0, 0.0, argument(1).unwrap_or(x), 0.0, 0.0])
+                }
+                ("rotate", 1 | 3) => {
+                    let (sin, cos) = numbers[0].to_radians().sin_cos();
+                    let (cx, cy) = (argument(1).unwrap_or(0.0), argument(2).unwrap_or(0.0));
+
+                    Self([1.0, 0.0, 0.0, 1.0, cx, cy])
+                        .then(Self([cos, sin, -sin, cos, 0.0, 0.0]))
+                        .then(Self([1.0, 0.0, 0.0, 1.0, -cx, -cy]))
+                }
+                ("skewX", 1) => Self([1.0, 0.0, numbers[0].to_radians().tan(), 1.0, 0.0, 0.0]),
+                ("skewY", 1) => Self([1.0, numbers[0].to_radians().tan(), 0.0, 1.0, 0.0, 0.0]),
+                _ => return Err(invalid(&format!("invalid transform '{value}'"))),
+            };
+
+            transform = transform.then(next);
+            rest = after;
+        }
+
+        Ok(transform)
+    }
+
+    /// Returns the transform that applies `inner` first and then `self`.
+    fn then(self, inner: Self) -> Self {
+        let (outer, inner) = (self.0, inner.0);
+
+        Self([
+            outer[0].mul_add(inner[0], outer[2] * inner[1]),
+            outer[1].mul_add(inner[0], outer[3] * inner[1]),
+            outer[0].mul_add(inner[2], outer[2] * inner[3]),
+            outer[1].mul_add(inner[2], outer[3] * inner[3]),
+            outer[0].mul_add(inner[4], outer[2].mul_add(inner[5], outer[4])),
+            outer[1].mul_add(inner[4], outer[3].mul_add(inner[5], outer[5])),
+        ])
+    }
+
+    const fn apply(self, point: Vec2) -> Vec2 {
+        let matrix = self.0;
+        let (x, y) = (point.x.0, point.y.0);
+
+        Vec2::new(
+            matrix[0].mul_add(x, matrix[2].mul_add(y, matrix[4])),
+            matrix[1].mul_add(x, matrix[3].mul_add(y, matrix[5])),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TagKind {
+    Open,
+    Close,
+    SelfClosing,
+}
+
+#[derive(Debug)]
+struct Tag<'a> {
+    name: &'a str,
+    kind: TagKind,
+    attributes: Vec<(&'a str, String)>,
+}
+
+impl Tag<'_> {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A minimal XML tokenizer that returns the tags of a document and skips everything else.
+#[derive(Debug)]
+struct Tags<'a> {
+    input: &'a str,
+}
+
+impl<'a> Tags<'a> {
+    const fn new(input: &'a str) -> Self {
+        Self { input }
+    }
+
+    fn skip_past(&mut self, end: &str) -> Result<(), Error> {
+        let index = self
+            .input
+            .find(end)
+            .ok_or_else(|| invalid(&format!("missing '{end}'")))?;
+        self.input = &self.input[index + end.len()..];
+
+        Ok(())
+    }
+
+    fn tag(&mut self) -> Result<Tag<'a>, Error> {
+        let is_name_end = |c: char| c.is_ascii_whitespace() || c == '/' || c == '>';
+
+        let closing = self.input.starts_with('/');
+        if closing {
+            self.input = &self.input[1..];
+        }
+
+        let name_end = self.input.find(is_name_end).unwrap_or(self.input.len());
+        let (name, rest) = self.input.split_at(name_end);
+        self.input = rest;
+
+        let mut attributes = Vec::new();
+
+        loop {
+            self.input = self.input.trim_start();
+
+            if let Some(rest) = self.input.strip_prefix("/>") {
+                self.input = rest;
+                return Ok(Tag {
+                    name,
+                    kind: TagKind::SelfClosing,
+                    attributes,
+                });
+            }
+
+            if let Some(rest) = self.input.strip_prefix('>') {
+                self.input = rest;
+                return Ok(Tag {
+                    name,
+                    kind: if closing {
+                        TagKind::Close
+                    } else {
+                        TagKind::Open
+                    },
+                    attributes,
+                });
+            }
+
+            let (key, rest) = self
+                .input
+                .split_once('=')
+                .ok_or_else(|| invalid(&format!("invalid attributes in '{name}'")))?;
+            let rest = rest.trim_start();
+
+            let quote = rest
+                .chars()
+                .next()
+                .filter(|&c| c == '"' || c == '\'')
+                .ok_or_else(|| invalid(&format!("unquoted attribute in '{name}'")))?;
+            let (value, rest) = rest[1..]
+                .split_once(quote)
+                .ok_or_else(|| invalid(&format!("unterminated attribute in '{name}'")))?;
+
+            attributes.push((key.trim(), unescape(value)));
+            self.input = rest;
+        }
+    }
+}
+
+impl<'a> Iterator for Tags<'a> {
+    type Item = Result<Tag<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let start = self.input.find('<')?;
+            self.input = &self.input[start + 1..];
+
+            let skipped = if self.input.starts_with("!--") {
+                self.skip_past("-->")
+            } else if self.input.starts_with("![CDATA[") {
+                self.skip_past("]]>")
+            } else if self.input.starts_with('?') || self.input.starts_with('!') {
+                self.skip_past(">")
+            } else {
+                return Some(self.tag());
+            };
+
+            if let Err(err) = skipped {
+                return Some(Err(err));
+            }
+        }
+    }
+}
+
+fn unescape(value: &str) -> String {
+    value
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidSvg(message.to_string())
+}
@@ -0,0 +1,233 @@
+//! Export of level geometry to SVG, and import of terrain from SVG.
+//!
+//! Every layer becomes a `<g>` element named after the layer, which vector editors like Inkscape show as a layer.
+//! Terrain is exported as paths, shapes as rectangles and circles and text objects as `<text>` elements. Every
+//! element has `data-entity-id` and `data-tile-id` attributes, and a `data-tag` attribute if the object has a tag.
+//!
+//! The y-axis is flipped, so the level looks the same as in the game. One unit in the level is one unit in the SVG.
+//!
+//! Paths and polygons can be imported back as terrain with [`import_svg`].
+//!
+//! ```
+//! use exolvl::svg::to_svg;
+//! use exolvl::types::level_data::LevelData;
+//!
+//! let svg = to_svg(&LevelData::default());
+//!
+//! assert!(svg.starts_with("<svg"));
+//! ```
+
+mod import;
+mod path;
+
+pub use import::{import_shapes, import_svg, parse_svg, ImportOptions, Shape};
+
+use crate::{
+    catalogue::{self, DEFAULT_SHAPE_COLOR, DEFAULT_TERRAIN_COLOR},
+    error::Error,
+    geometry::{Affine, Rect},
+    types::{
+        color::Color, level_data::LevelData, object::Object, properties::Properties, vec2::Vec2,
+    },
+};
+use core::fmt::Write as _;
+
+/// Exports a level to an SVG document.
+#[must_use]
+pub fn to_svg(level: &LevelData) -> String {
+    let bounds = level.bounds().unwrap_or_else(|| Rect::point(Vec2::ZERO));
+    let (x, y) = (bounds.min.x.0 - 1.0, -bounds.max.y.0 - 1.0);
+    let (width, height) = (bounds.width() + 2.0, bounds.height() + 2.0);
+
+    let transforms = level.world_transforms();
+    let mut svg = String::new();
+
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" viewBox="{x} {y} {width} {height}">"#
+    );
+    let _ = writeln!(
+        svg,
+        r#"<rect x="{x}" y="{y}" width="{width}" height="{height}" {}/>"#,
+        fill(level.custom_background_color)
+    );
+
+    // objects in layers that don't exist go below all layers
+    for (object, transform) in level.objects.iter().zip(&transforms).filter(|(object, _)| {
+        !level
+            .layers
+            .iter()
+            .any(|layer| layer.layer_id == object.in_layer)
+    }) {
+        write_object(&mut svg, object, transform);
+    }
+
+    for layer in &level.layers {
+        let _ = writeln!(
+            svg,
+            r#"<g id="layer-{id}" inkscape:groupmode="layer" inkscape:label="{name}" data-layer-id="{id}"{hidden}>"#,
+            id = layer.layer_id,
+            name = escape(&layer.layer_name),
+            hidden = if layer.invisible {
+                r#" style="display:none""#
+            } else {
+                ""
+            },
+        );
+
+        for (object, transform) in level
+            .objects
+            .iter()
+            .zip(&transforms)
+            .filter(|(object, _)| object.in_layer == layer.layer_id)
+        {
+            write_object(&mut svg, object, transform);
+        }
+
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+/// Exports a level to SVG and writes it to `output`.
+///
+/// # Errors
+///
+/// Returns an error if writing fails.
+pub fn write_svg(level: &LevelData, output: &mut impl std::io::Write) -> Result<(), Error> {
+    output.write_all(to_svg(level).as_bytes())?;
+
+    Ok(())
+}
+
+fn write_object(svg: &mut String, object: &Object, transform: &Affine) {
+    let attributes = attributes(object);
+    let transform = matrix(transform);
+
+    if let Some(rings) = object.terrain_corners() {
+        let mut path = String::new();
+
+        for ring in rings {
+            for (i, point) in ring.iter().enumerate() {
+                let command = if i == 0 { 'M' } else { 'L' };
+                let _ = write!(path, "{command}{} {} ", point.x.0, -point.y.0);
+            }
+
+            path.push('Z');
+        }
+
+        let _ = writeln!(
+            svg,
+            r#"<path d="{}" fill-rule="evenodd" transform="{transform}" {} {attributes}/>"#,
+            path.trim_end(),
+            paint(object, DEFAULT_TERRAIN_COLOR),
+        );
+    } else if object.tile_id == catalogue::TEXT || object.text().is_some() {
+        let _ = writeln!(
+            svg,
+            r#"<text text-anchor="middle" dominant-baseline="middle" font-size="{}" transform="{transform}" {} {attributes}>{}</text>"#,
+            object.font_size().unwrap_or(1.0),
+            fill(object.color().unwrap_or(DEFAULT_SHAPE_COLOR)),
+            escape(object.text().unwrap_or_default()),
+        );
+    } else if object.tile_id == catalogue::CIRCLE {
+        let _ = writeln!(
+            svg,
+            r#"<circle r="{}" transform="{transform}" {} {attributes}/>"#,
+            object.radius().unwrap_or(0.5),
+            paint(object, DEFAULT_SHAPE_COLOR),
+        );
+    } else if object.tile_id == catalogue::SQUARE || object.color().is_some() {
+        let (width, height) = (
+            object.width().unwrap_or(1.0),
+            object.height().unwrap_or(1.0),
+        );
+        let radius = object.corner_radius().unwrap_or(0.0);
+
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{width}" height="{height}" rx="{radius}" transform="{transform}" {} {attributes}/>"#,
+            -width / 2.0,
+            -height / 2.0,
+            paint(object, DEFAULT_SHAPE_COLOR),
+        );
+    }
+}
+
+/// Returns the SVG transform of an object's world transform. The shapes are drawn with the y-axis flipped, so the
+/// transform is flipped on both sides.
+fn matrix(transform: &Affine) -> String {
+    format!(
+        "matrix({} {} {} {} {} {})",
+        transform.x_axis.x.0,
+        -transform.x_axis.y.0,
+        -transform.y_axis.x.0,
+        transform.y_axis.y.0,
+        transform.translation.x.0,
+        -transform.translation.y.0,
+    )
+}
+
+fn attributes(object: &Object) -> String {
+    let mut attributes = format!(
+        r#"data-entity-id="{}" data-tile-id="{}""#,
+        object.entity_id, object.tile_id
+    );
+
+    if !object.tag.is_empty() {
+        let _ = write!(attributes, r#" data-tag="{}""#, escape(&object.tag));
+    }
+
+    attributes
+}
+
+fn paint(object: &Object, default: Color) -> String {
+    let mut paint = fill(object.color().unwrap_or(default));
+
+    if let (Some(color), Some(thickness)) = (object.border_color(), object.border_thickness()) {
+        let _ = write!(
+            paint,
+            r#" stroke="{}" stroke-opacity="{}" stroke-width="{thickness}""#,
+            rgb(color),
+            color.a.0,
+        );
+    }
+
+    paint
+}
+
+fn fill(color: Color) -> String {
+    format!(r#"fill="{}" fill-opacity="{}""#, rgb(color), color.a.0)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn rgb(color: Color) -> String {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color.r.0),
+        channel(color.g.0),
+        channel(color.b.0)
+    )
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
@@ -0,0 +1,347 @@
+//! Parsing and flattening of SVG path data.
+
+use crate::{error::Error, types::vec2::Vec2};
+use core::f32::consts::PI;
+
+/// Parses the `d` attribute of a path into its subpaths, with curves and arcs replaced by straight lines that are at
+/// most `tolerance` away from them.
+pub fn parse(data: &str, tolerance: f32) -> Result<Vec<Vec<Vec2>>, Error> {
+    let mut parser = Parser {
+        tokens: Tokens::new(data),
+        tolerance: tolerance.max(f32::EPSILON),
+        subpaths: Vec::new(),
+        current: Vec::new(),
+        position: Vec2::ZERO,
+        start: Vec2::ZERO,
+        last_control: None,
+    };
+
+    parser.run()?;
+    parser.finish_subpath();
+
+    Ok(parser.subpaths)
+}
+
+#[derive(Debug)]
+struct Parser<'a> {
+    tokens: Tokens<'a>,
+    tolerance: f32,
+    subpaths: Vec<Vec<Vec2>>,
+    current: Vec<Vec2>,
+    position: Vec2,
+    start: Vec2,
+    /// The last control point of the previous command, with whether it was a cubic curve, for smooth curves.
+    last_control: Option<(Vec2, bool)>,
+}
+
+impl Parser<'_> {
+    fn run(&mut self) -> Result<(), Error> {
+        let mut command = None;
+
+        while let Some(token) = self.tokens.peek() {
+            let current = match token {
+                Token::Command(new) => {
+                    self.tokens.next_command();
+                    new
+                }
+                // commands can be repeated by just giving more arguments
+                Token::Number => match command {
+                    Some('M') => 'L',
+                    Some('m') => 'l',
+                    Some(command) if !matches!(command, 'Z' | 'z') => command,
+                    _ => return Err(invalid("expected a path command")),
+                },
+            };
+
+            command = Some(current);
+            self.command(current)?;
+        }
+
+        Ok(())
+    }
+
+    fn command(&mut self, command: char) -> Result<(), Error> {
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative { self.position } else { Vec2::ZERO };
+
+        let mut control = None;
+
+        match command.to_ascii_uppercase() {
+            'M' => {
+                self.finish_subpath();
+                let point = origin + self.point()?;
+                self.start = point;
+                self.line_to(point);
+            }
+            'L' => {
+                let point = origin + self.point()?;
+                self.line_to(point);
+            }
+            'H' => {
+                let x = self.tokens.number()? + origin.x.0;
+                self.line_to(Vec2::new(x, self.position.y.0));
+            }
+            'V' => {
+                let y = self.tokens.number()? + origin.y.0;
+                self.line_to(Vec2::new(self.position.x.0, y));
+            }
+            'C' | 'S' => {
+                let first = if command.eq_ignore_ascii_case(&'C') {
+                    origin + self.point()?
+                } else {
+                    self.reflected_control(true)
+                };
+                let second = origin + self.point()?;
+                let end = origin + self.point()?;
+
+                self.cubic_to(first, second, end);
+                control = Some((second, true));
+            }
+            'Q' | 'T' => {
+                let handle = if command.eq_ignore_ascii_case(&'Q') {
+                    origin + self.point()?
+                } else {
+                    self.reflected_control(false)
+                };
+                let end = origin + self.point()?;
+
+                self.quadratic_to(handle, end);
+                control = Some((handle, false));
+            }
+            'A' => {
+                let radius = Vec2::new(self.tokens.number()?.abs(), self.tokens.number()?.abs());
+                let rotation = self.tokens.number()?;
+                let large_arc = self.tokens.flag()?;
+                let sweep = self.tokens.flag()?;
+                let end = origin + self.point()?;
+
+                self.arc_to(radius, rotation, large_arc, sweep, end);
+            }
+            'Z' => {
+                self.finish_subpath();
+                self.position = self.start;
+            }
+            _ => return Err(invalid(&format!("unknown path command '{command}'"))),
+        }
+
+        self.last_control = control;
+
+        Ok(())
+    }
+
+    fn point(&mut self) -> Result<Vec2, Error> {
+        Ok(Vec2::new(self.tokens.number()?, self.tokens.number()?))
+    }
+
+    fn reflected_control(&self, cubic: bool) -> Vec2 {
+        match self.last_control {
+            Some((control, was_cubic)) if was_cubic == cubic => self.position * 2.0 - control,
+            _ => self.position,
+        }
+    }
+
+    fn line_to(&mut self, point: Vec2) {
+        if self.current.last() != Some(&point) {
+            self.current.push(point);
+        }
+
+        self.position = point;
+    }
+
+    fn cubic_to(&mut self, first: Vec2, second: Vec2, end: Vec2) {
+        let start = self.position;
+        let dd = (start - first * 2.0 + second)
+            .length()
+            .max((first - second * 2.0 + end).length());
+        let segments = segment_count((0.75 * dd / self.tolerance).sqrt());
+
+        for i in 1..=segments {
+            let t = step(i, segments);
+            let u = 1.0 - t;
+
+            let point = start * (u * u * u)
+                + first * (3.0 * u * u * t)
+                + second * (3.0 * u * t * t)
+                + end * (t * t * t);
+            self.line_to(point);
+        }
+    }
+
+    fn quadratic_to(&mut self, handle: Vec2, end: Vec2) {
+        let start = self.position;
+        let dd = (start - handle * 2.0 + end).length();
+        let segments = segment_count((0.25 * dd / self.tolerance).sqrt());
+
+        for i in 1..=segments {
+            let t = step(i, segments);
+            let u = 1.0 - t;
+
+            self.line_to(start * (u * u) + handle * (2.0 * u * t) + end * (t * t));
+        }
+    }
+
+    /// Flattens an elliptical arc, using the endpoint to center conversion from the SVG specification.
+    fn arc_to(&mut self, radius: Vec2, rotation: f32, large_arc: bool, sweep: bool, end: Vec2) {
+        let start = self.position;
+        let (mut rx, mut ry) = (radius.x.0, radius.y.0);
+
+        if rx == 0.0 || ry == 0.0 || start == end {
+            self.line_to(end);
+            return;
+        }
+
+        let half = (start - end).rotate(-rotation) / 2.0;
+        let (x1, y1) = (half.x.0, half.y.0);
+
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let denominator = (rx * rx).mul_add(y1 * y1, ry * ry * x1 * x1);
+        let numerator = (rx * ry).mul_add(rx * ry, -denominator);
+        let mut factor = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            factor = -factor;
+        }
+
+        let center_prime = Vec2::new(factor * rx * y1 / ry, -factor * ry * x1 / rx);
+        let center = center_prime.rotate(rotation) + (start + end) / 2.0;
+
+        let angle = |x: f32, y: f32| y.atan2(x);
+        let start_angle = angle((x1 - center_prime.x.0) / rx, (y1 - center_prime.y.0) / ry);
+        let end_angle = angle((-x1 - center_prime.x.0) / rx, (-y1 - center_prime.y.0) / ry);
+
+        let mut delta = end_angle - start_angle;
+        if sweep && delta < 0.0 {
+            delta += 2.0 * PI;
+        } else if !sweep && delta > 0.0 {
+            delta -= 2.0 * PI;
+        }
+
+        let max_step = 2.0 * (1.0 - self.tolerance / rx.max(ry)).clamp(-1.0, 1.0).acos();
+        let segments = segment_count(delta.abs() / max_step.max(f32::EPSILON));
+
+        for i in 1..=segments {
+            let theta = step(i, segments).mul_add(delta, start_angle);
+            let point = Vec2::new(rx * theta.cos(), ry * theta.sin()).rotate(rotation) + center;
+            self.line_to(point);
+        }
+    }
+
+    fn finish_subpath(&mut self) {
+        let mut subpath = core::mem::take(&mut self.current);
+
+        if subpath.len() > 1 && subpath.first() == subpath.last() {
+            subpath.pop();
+        }
+
+        if subpath.len() >= 3 {
+            self.subpaths.push(subpath);
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+const fn segment_count(estimate: f32) -> u16 {
+    estimate.ceil().clamp(1.0, 1000.0) as u16
+}
+
+fn step(i: u16, segments: u16) -> f32 {
+    f32::from(i) / f32::from(segments)
+}
+
+fn invalid(message: &str) -> Error {
+    Error::InvalidSvg(message.to_string())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token {
+    Command(char),
+    Number,
+}
+
+/// Splits path data into commands, numbers and flags.
+#[derive(Debug)]
+pub struct Tokens<'a> {
+    input: &'a str,
+}
+
+impl<'a> Tokens<'a> {
+    pub const fn new(input: &'a str) -> Self {
+        Self { input }
+    }
+
+    fn skip_separators(&mut self) {
+        self.input = self
+            .input
+            .trim_start_matches(|c: char| c.is_ascii_whitespace() || c == ',');
+    }
+
+    /// Returns the kind of the next token, or `None` at the end of the input.
+    fn peek(&mut self) -> Option<Token> {
+        self.skip_separators();
+
+        let c = self.input.chars().next()?;
+
+        Some(if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            Token::Command(c)
+        } else {
+            Token::Number
+        })
+    }
+
+    fn next_command(&mut self) {
+        self.input = &self.input[1..];
+    }
+
+    /// Reads a number.
+    pub fn number(&mut self) -> Result<f32, Error> {
+        self.skip_separators();
+
+        let bytes = self.input.as_bytes();
+        let mut end = 0;
+        let mut seen_dot = false;
+        let mut seen_exponent = false;
+
+        while let Some(&b) = bytes.get(end) {
+            match b {
+                b'+' | b'-' if end == 0 || matches!(bytes[end - 1], b'e' | b'E') => {}
+                b'0'..=b'9' => {}
+                b'.' if !seen_dot && !seen_exponent => seen_dot = true,
+                b'e' | b'E' if !seen_exponent && end > 0 => seen_exponent = true,
+                _ => break,
+            }
+
+            end += 1;
+        }
+
+        let (number, rest) = self.input.split_at(end);
+        self.input = rest;
+
+        number
+            .parse()
+            .map_err(|_| invalid(&format!("invalid number '{number}'")))
+    }
+
+    /// Reads an arc flag, which can be written without a separator before the next number.
+    fn flag(&mut self) -> Result<bool, Error> {
+        self.skip_separators();
+
+        let flag = match self.input.as_bytes().first() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(invalid("expected an arc flag")),
+        };
+        self.input = &self.input[1..];
+
+        Ok(flag)
+    }
+
+    /// Returns whether all input has been read.
+    pub fn is_empty(&mut self) -> bool {
+        self.skip_separators();
+        self.input.is_empty()
+    }
+}
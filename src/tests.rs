@@ -34,3 +34,1945 @@ fn inner(in_bytes: &[u8]) {
 
     assert_diff!(&in_str, &out_str, ", ", 0);
 }
+
+#[test]
+fn interpreter_input_script() {
+    use crate::{
+        interpreter::{Interpreter, Recorder, Value},
+        types::novascript::activator::Input,
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/3.exolvl")).unwrap();
+    let file = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap();
+
+    let mut interpreter = Interpreter::new(&file.level_data, Recorder::default()).unwrap();
+    assert_eq!(interpreter.global(1_293_117_489), Some(&Value::Int(10)));
+
+    interpreter.trigger(Input::Pressed.into()).unwrap();
+    assert!(interpreter.run_until_idle(60.0).unwrap());
+
+    assert_eq!(interpreter.global(1_293_117_489), Some(&Value::Int(9)));
+    assert!(!interpreter.hooks().effects.is_empty());
+
+    for seconds in [f64::NAN, f64::INFINITY, -1.0] {
+        assert_eq!(
+            interpreter.advance(seconds),
+            Err(crate::interpreter::RuntimeError::InvalidDuration)
+        );
+    }
+    assert_eq!(
+        interpreter.run_until_idle(f64::NAN),
+        Err(crate::interpreter::RuntimeError::InvalidDuration)
+    );
+}
+
+#[test]
+fn optimizer_preserves_behaviour() {
+    use crate::{
+        interpreter::{Interpreter, Recorder},
+        optimizer::optimize_level,
+    };
+
+    let files: [&[u8]; 4] = [
+        include_bytes!("test_files/1.exolvl"),
+        include_bytes!("test_files/2.exolvl"),
+        include_bytes!("test_files/3.exolvl"),
+        include_bytes!("test_files/4.exolvl"),
+    ];
+
+    for bytes in files {
+        let in_bytes = extract(bytes).unwrap();
+        let original = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+        let mut optimized = original.clone();
+        optimize_level(&mut optimized);
+
+        let mut activators = original
+            .nova_scripts
+            .iter()
+            .flat_map(|script| &script.activation_list)
+            .map(|activator| activator.activator_type)
+            .collect::<Vec<_>>();
+        activators.sort_unstable();
+        activators.dedup();
+
+        for activator in activators {
+            let run = |level| {
+                let mut interpreter = Interpreter::new(level, Recorder::default()).unwrap();
+                let result = interpreter
+                    .trigger(activator)
+                    .and_then(|()| interpreter.run_until_idle(60.0));
+                let globals = optimized
+                    .global_variables
+                    .iter()
+                    .map(|v| interpreter.global(v.variable_id).cloned())
+                    .collect::<Vec<_>>();
+
+                (result, globals, interpreter.into_hooks())
+            };
+
+            assert_eq!(run(&original), run(&optimized));
+        }
+    }
+}
+
+#[test]
+fn builder_script_runs() {
+    use crate::{
+        builder::{action, activator, expr},
+        interpreter::{Interpreter, Recorder, Value},
+        types::{level_data::LevelData, novascript::activator::Input},
+    };
+
+    let mut level = LevelData::default();
+    let counter = level.add_global::<i32>("counter", 0);
+
+    let mut script = level.new_script("count");
+    let step = script.variable::<i32>("step", 2);
+    script
+        .on(activator::input(Input::Pressed))
+        .action(action::repeat(
+            3,
+            [action::set(counter, counter.get() + step.get())],
+        ))
+        .action(action::game_text_show(
+            expr::string("count: ") + counter.get().into_string(),
+        ));
+    let script_id = script.finish();
+
+    assert_ne!(script_id, counter.id());
+    assert_ne!(script_id, step.id());
+
+    let mut interpreter = Interpreter::new(&level, Recorder::default()).unwrap();
+    interpreter.trigger(Input::Pressed.into()).unwrap();
+    assert!(interpreter.run_until_idle(60.0).unwrap());
+
+    assert_eq!(interpreter.global(counter.id()), Some(&Value::Int(6)));
+    assert_eq!(interpreter.hooks().effects.len(), 1);
+}
+
+#[test]
+fn enums_match_test_levels() {
+    use crate::types::{
+        foreground_type::ForegroundType,
+        novascript::activator::{ActivatorType, Input},
+        object_property::ObjectProperty,
+        physics_type::PhysicsType,
+    };
+
+    let levels = [
+        &include_bytes!("test_files/1.exolvl")[..],
+        include_bytes!("test_files/2.exolvl"),
+        include_bytes!("test_files/3.exolvl"),
+        include_bytes!("test_files/4.exolvl"),
+    ]
+    .map(|bytes| {
+        let in_bytes = extract(bytes).unwrap();
+        Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data
+    });
+
+    let activators = levels
+        .iter()
+        .flat_map(|level| &level.nova_scripts)
+        .flat_map(|script| &script.activation_list)
+        .collect::<Vec<_>>();
+
+    let activator_types = activators
+        .iter()
+        .map(|activator| activator.activator_type)
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        activator_types,
+        [
+            ActivatorType::Other(39),
+            ActivatorType::EveryFrame,
+            ActivatorType::Other(38),
+            ActivatorType::Input(Input::Pressed),
+            ActivatorType::CollisionEnter,
+            ActivatorType::LevelStart,
+        ]
+    );
+
+    for activator in &activators {
+        let types = activator.activator_type.parameter_types();
+
+        if !types.is_empty() {
+            let actual = activator
+                .parameters
+                .iter()
+                .map(|parameter| parameter.dynamic_type.static_type())
+                .collect::<Vec<_>>();
+
+            assert_eq!(actual, types);
+        }
+    }
+
+    for level in &levels {
+        for layer in &level.layers {
+            assert!(matches!(
+                layer.foreground_type,
+                ForegroundType::Background | ForegroundType::Foreground
+            ));
+        }
+
+        for property in level.objects.iter().flat_map(|object| &object.properties) {
+            if let ObjectProperty::PhysicsType(physics_type) = property {
+                assert_eq!(*physics_type, PhysicsType::Decoration);
+            }
+        }
+    }
+}
+
+#[test]
+fn properties_keep_their_order() {
+    use crate::types::{
+        object_property::{ObjectProperty, PropertyKey},
+        properties::Properties,
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let mut file = Exolvl::read(&mut Cursor::new(&in_bytes)).unwrap();
+
+    for object in &mut file.level_data.objects {
+        assert!(object.duplicate_properties().is_empty());
+
+        for property in object.properties.clone() {
+            assert_eq!(object.set_property(property.clone()), Some(property));
+        }
+    }
+
+    let mut out_bytes = Vec::new();
+    file.write(&mut out_bytes).unwrap();
+    assert_eq!(in_bytes, out_bytes);
+
+    let object = &mut file.level_data.objects[0];
+    object.properties = vec![
+        ObjectProperty::Killer(true),
+        ObjectProperty::Health(2.0.into()),
+    ];
+    object.set_friction(0.5);
+    object.set_killer(false);
+
+    assert_eq!(object.friction(), Some(0.5));
+    assert_eq!(object.killer(), Some(false));
+    assert_eq!(object.color(), None);
+    assert_eq!(
+        object
+            .properties
+            .iter()
+            .map(ObjectProperty::key)
+            .collect::<Vec<_>>(),
+        [
+            PropertyKey::Friction,
+            PropertyKey::Killer,
+            PropertyKey::Health
+        ]
+    );
+
+    object.properties.push(ObjectProperty::Killer(true));
+    assert_eq!(object.duplicate_properties(), [PropertyKey::Killer]);
+    assert_eq!(object.killer(), Some(true));
+    assert_eq!(
+        object.remove_property(PropertyKey::Killer),
+        Some(ObjectProperty::Killer(true))
+    );
+    assert_eq!(object.killer(), None);
+}
+
+#[test]
+fn catalogue_matches_test_levels() {
+    use crate::catalogue::{self, Category};
+
+    let files: [&[u8]; 4] = [
+        include_bytes!("test_files/1.exolvl"),
+        include_bytes!("test_files/2.exolvl"),
+        include_bytes!("test_files/3.exolvl"),
+        include_bytes!("test_files/4.exolvl"),
+    ];
+
+    for bytes in files {
+        let in_bytes = extract(bytes).unwrap();
+        let level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+        for object in &level.objects {
+            assert_eq!(object.invalid_properties().count(), 0, "{object:?}");
+
+            // the two decorative shapes that aren't identified yet
+            let unknown = [-1_718_767_673, 2_044_847_310].contains(&object.tile_id);
+            assert_eq!(object.tile().is_some(), !unknown);
+        }
+    }
+
+    let start = catalogue::lookup(catalogue::START).unwrap();
+    assert_eq!(start.category, Category::Special);
+
+    let terrain = catalogue::lookup(catalogue::TERRAIN).unwrap();
+    let object = terrain.new_object(1, crate::types::vec2::Vec2::ZERO, 1);
+    assert_eq!(object.tile(), Some(terrain));
+    assert!(!terrain.allows(crate::types::object_property::PropertyKey::EmitterShape));
+    assert!(terrain.allows(crate::types::object_property::PropertyKey::BorderColor));
+    assert!(object
+        .properties
+        .iter()
+        .all(|property| !terrain.physics_defaults.contains(property)));
+}
+
+#[test]
+fn terrain_geometry() {
+    use crate::{
+        catalogue,
+        geometry::{signed_area, Polygon},
+        types::vec2::Vec2,
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let terrain = level
+        .objects
+        .iter()
+        .find(|object| object.tile_id == catalogue::TERRAIN)
+        .unwrap();
+    let polygons = Polygon::from_terrain(terrain).unwrap();
+
+    assert_eq!(polygons.len(), 1);
+    assert_eq!(polygons[0].holes.len(), 1);
+    assert!((polygons[0].area() - 52.0).abs() < 1e-4);
+    assert!(polygons[0].is_simple());
+    assert!(signed_area(&polygons[0].outline) > 0.0);
+    assert!(signed_area(&polygons[0].holes[0]) < 0.0);
+    assert!(polygons[0].contains(Vec2::new(0.5, 0.5)));
+    assert!(!polygons[0].contains(Vec2::new(1.0, 3.0)));
+
+    let bounds = polygons[0].bounds();
+    assert_eq!(
+        (bounds.min, bounds.max),
+        (Vec2::new(-5.0, -1.0), Vec2::new(9.0, 5.0))
+    );
+
+    let mut bowtie = Polygon {
+        outline: vec![
+            Vec2::new(0.0, 0.0),
+            Vec2::new(2.0, 2.0),
+            Vec2::new(2.0, 1.0),
+            Vec2::new(2.0, 0.0),
+            Vec2::new(0.0, 2.0),
+        ],
+        holes: Vec::new(),
+    };
+    assert_eq!(bowtie.self_intersections().len(), 1);
+    assert_eq!(bowtie.self_intersections()[0].point, Vec2::new(1.0, 1.0));
+
+    bowtie = bowtie.simplify(0.01);
+    assert_eq!(bowtie.outline.len(), 4);
+}
+
+#[test]
+fn spatial_index_matches_brute_force() {
+    use crate::{
+        geometry::Rect,
+        spatial::SpatialIndex,
+        types::{object::Object, vec2::Vec2},
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/2.exolvl")).unwrap();
+    let level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let bounds = level.bounds().unwrap();
+    assert!(level.objects.iter().all(|object| {
+        let object_bounds = level.object_bounds(object);
+        bounds.contains(object_bounds.min) && bounds.contains(object_bounds.max)
+    }));
+
+    let index = SpatialIndex::with_cell_size(&level.objects, 4.0);
+    let ids = |objects: Vec<&Object>| {
+        objects
+            .into_iter()
+            .map(|object| object.entity_id)
+            .collect::<Vec<_>>()
+    };
+
+    for point in [
+        Vec2::ZERO,
+        bounds.center(),
+        bounds.min,
+        Vec2::new(1000.0, -1000.0),
+    ] {
+        let rect = Rect {
+            min: point - Vec2::new(10.0, 5.0),
+            max: point + Vec2::new(10.0, 5.0),
+        };
+        let expected = level
+            .objects
+            .iter()
+            .filter(|object| level.object_bounds(object).intersects(rect))
+            .collect();
+        assert_eq!(ids(index.query_rect(rect)), ids(expected));
+
+        let expected = level
+            .objects
+            .iter()
+            .filter(|object| level.object_bounds(object).distance_to(point) <= 6.0)
+            .collect();
+        assert_eq!(ids(index.query_radius(point, 6.0)), ids(expected));
+
+        let nearest = level.objects.iter().min_by(|a, b| {
+            level
+                .object_bounds(a)
+                .distance_to(point)
+                .total_cmp(&level.object_bounds(b).distance_to(point))
+        });
+        assert_eq!(
+            index.nearest(point).map(|object| object.entity_id),
+            nearest.map(|object| object.entity_id)
+        );
+    }
+}
+
+#[test]
+fn spatial_index_extreme_objects() {
+    use crate::{
+        catalogue,
+        geometry::Rect,
+        spatial::SpatialIndex,
+        types::{object_property::ObjectProperty, vec2::Vec2},
+    };
+
+    let square = catalogue::lookup(catalogue::SQUARE).unwrap();
+
+    let mut huge = square.new_object(1, Vec2::ZERO, 0);
+    huge.scale = Vec2::new(1e30, 1e30);
+
+    let mut broken = square.new_object(2, Vec2::new(f32::NAN, 0.0), 0);
+    broken
+        .properties
+        .push(ObjectProperty::Width(f32::INFINITY.into()));
+
+    let objects = [
+        huge,
+        broken,
+        square.new_object(3, Vec2::new(-1e9, 0.0), 0),
+        square.new_object(4, Vec2::new(1e9, 1e9), 0),
+        square.new_object(5, Vec2::new(2.0, 2.0), 0),
+        square.new_object(6, Vec2::new(3e7, -3e7), 0),
+    ];
+
+    let index = SpatialIndex::with_cell_size(&objects, 0.5);
+    let ids = |objects: Vec<&crate::types::object::Object>| {
+        objects
+            .into_iter()
+            .map(|object| object.entity_id)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        ids(index.query_radius(Vec2::ZERO, f32::INFINITY)),
+        [1, 2, 3, 4, 5, 6]
+    );
+    assert_eq!(ids(index.query_radius(Vec2::ZERO, f32::NAN)), []);
+    assert_eq!(ids(index.query_radius(Vec2::new(2.0, 2.0), 0.1)), [1, 5]);
+    assert_eq!(
+        ids(index.query_rect(Rect {
+            min: Vec2::new(f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: Vec2::new(f32::INFINITY, f32::INFINITY),
+        })),
+        [1, 3, 4, 5, 6]
+    );
+
+    assert_eq!(index.nearest(Vec2::new(1e9, 1e9)).unwrap().entity_id, 1);
+
+    // without the huge object, the nearest object is far away from everything else
+    let index = SpatialIndex::with_cell_size(&objects[2..], 0.5);
+    assert_eq!(index.nearest(Vec2::new(1e9, 1e9)).unwrap().entity_id, 4);
+    assert_eq!(index.nearest(Vec2::new(2.6, 2.6)).unwrap().entity_id, 5);
+    assert_eq!(
+        index.nearest(Vec2::new(2.9e7, -2.9e7)).unwrap().entity_id,
+        6
+    );
+}
+
+#[test]
+fn group_world_transforms() {
+    use crate::{
+        geometry::{Affine, Transform},
+        spatial::SpatialIndex,
+        types::vec2::Vec2,
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let mut level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let group = level.object(27).unwrap().clone();
+    assert_eq!(group.group_members, [7, 9, 10, 11]);
+
+    for &id in &group.group_members {
+        let member = level.object(id).unwrap();
+        let bounds = level.object_bounds(member);
+
+        let center = group.position + member.position * 1.3;
+        assert!((bounds.center() - center).length() < 1e-5);
+        assert!(member.scale.x.0.mul_add(1.3, -bounds.width()).abs() < 1e-5);
+
+        // relative to the group, the member is near the origin
+        assert!(member.bounds().center().length() < 0.3);
+    }
+
+    let index = SpatialIndex::new(&level.objects);
+    let found = index
+        .query_radius(group.position, 0.3)
+        .into_iter()
+        .map(|object| object.entity_id)
+        .collect::<Vec<_>>();
+    assert!(group.group_members.iter().all(|id| found.contains(id)));
+
+    // a rotated group inside the group, with a member that's moved through both groups
+    let mut inner = level.object(9).unwrap().clone();
+    inner.entity_id = 100;
+    inner.tile_id = crate::catalogue::GROUP;
+    inner.rotation = 90.0.into();
+    inner.scale = Vec2::new(2.0, 1.0);
+    inner.in_group = 27;
+
+    let mut member = level.object(10).unwrap().clone();
+    member.entity_id = 101;
+    member.in_group = 100;
+
+    level.objects.push(inner.clone());
+    level.objects.push(member.clone());
+
+    let expected = Transform::of(&group)
+        .apply(Transform::of(&inner).apply(Transform::of(&member).apply(Vec2::new(1.0, 1.0))));
+    let transform = level.world_transform(&member);
+    assert!((transform.apply(Vec2::new(1.0, 1.0)) - expected).length() < 1e-5);
+    assert_eq!(level.world_transforms()[level.objects.len() - 1], transform);
+
+    let top_level = &level.objects[0];
+    assert_eq!(top_level.in_group, 0);
+    assert_eq!(
+        level.world_transform(top_level),
+        Affine::from(Transform::of(top_level))
+    );
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn render_preview() {
+    use crate::{
+        catalogue,
+        geometry::Rect,
+        render::{render, RenderOptions},
+        types::{color::Color, properties::Properties, vec2::Vec2},
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let mut level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let options = RenderOptions {
+        area: Some(Rect {
+            min: Vec2::new(-10.0, -5.0),
+            max: Vec2::new(10.0, 5.0),
+        }),
+        ..RenderOptions::new(200, 100)
+    };
+
+    // the background, and a pixel inside the terrain
+    let image = render(&level, &options);
+    assert_eq!(image.get_pixel(0, 0).0, [0, 0, 0, 255]);
+    assert_eq!(image.get_pixel(105, 50).0, [140, 140, 153, 255]);
+
+    for layer in &mut level.layers {
+        layer.invisible = true;
+    }
+
+    let image = render(&level, &options);
+    assert_eq!(image.get_pixel(105, 50).0, [0, 0, 0, 255]);
+
+    // objects are stacked in the order of the children of their layer, not in the order they are stored in
+    for layer in &mut level.layers {
+        layer.invisible = false;
+    }
+
+    let square = catalogue::lookup(catalogue::SQUARE).unwrap();
+    let layer = level.layers[0].layer_id;
+
+    let mut red = square.new_object(0, Vec2::new(-9.0, -4.0), layer);
+    red.set_color(Color::new(1.0, 0.0, 0.0, 1.0));
+    let mut blue = square.new_object(0, Vec2::new(-9.0, -4.0), layer);
+    blue.set_color(Color::new(0.0, 0.0, 1.0, 1.0));
+
+    let red = level.add_object(red).unwrap();
+    level.add_object(blue).unwrap();
+
+    let image = render(&level, &options);
+    assert_eq!(image.get_pixel(10, 90).0, [0, 0, 255, 255]);
+
+    let children = &mut level.layers[0].children;
+    children.retain(|&id| id != red);
+    children.push(red);
+
+    let image = render(&level, &options);
+    assert_eq!(image.get_pixel(10, 90).0, [255, 0, 0, 255]);
+}
+
+#[test]
+fn svg_export() {
+    use crate::svg::to_svg;
+
+    let in_bytes = extract(include_bytes!("test_files/2.exolvl")).unwrap();
+    let level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let svg = to_svg(&level);
+
+    assert_eq!(svg.matches("<g ").count(), level.layers.len());
+    assert_eq!(svg.matches("</g>").count(), level.layers.len());
+    assert_eq!(svg.matches("<path ").count(), 2);
+    assert_eq!(svg.matches("<text ").count(), 2);
+    assert!(svg.contains(
+        r#"<g id="layer-2" inkscape:groupmode="layer" inkscape:label="" data-layer-id="2">"#
+    ));
+    assert!(svg.contains(r#"data-entity-id="15" data-tile-id="1078723247""#));
+}
+
+#[test]
+fn svg_import_round_trip() {
+    use crate::{
+        geometry::Polygon,
+        svg::{import_svg, to_svg, ImportOptions},
+        types::{level_data::LevelData, physics_type::PhysicsType, properties::Properties},
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/2.exolvl")).unwrap();
+    let level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let terrain_area = |level: &LevelData| -> f32 {
+        level
+            .objects
+            .iter()
+            .filter_map(Polygon::from_terrain)
+            .flatten()
+            .map(|polygon| polygon.area())
+            .sum()
+    };
+
+    let mut imported = LevelData {
+        layers: level.layers.clone(),
+        ..Default::default()
+    };
+    for layer in &mut imported.layers {
+        layer.children.clear();
+    }
+    let layer_id = imported.layers[0].layer_id;
+    let ids = import_svg(
+        &mut imported,
+        &to_svg(&level),
+        &ImportOptions::new(layer_id),
+    )
+    .unwrap();
+
+    assert_eq!(ids.len(), 2);
+    assert_eq!(imported.layers[0].children, ids);
+    assert!((terrain_area(&imported) - terrain_area(&level)).abs() < 1e-3);
+
+    // a 4 by 2 rectangle with a half circle on top
+    let svg = r##"<svg><g transform="translate(10 0)"><path d="M0 0 h4 v-2 a2 2 0 0 0 -4 0 z" fill="#ff0000"/></g></svg>"##;
+    let ids = import_svg(&mut imported, svg, &ImportOptions::new(layer_id)).unwrap();
+    let object = imported.objects.last().unwrap();
+    let polygons = Polygon::from_terrain(object).unwrap();
+
+    assert_eq!(ids, [object.entity_id]);
+    assert_eq!(object.entity_id, 3);
+    assert!((polygons[0].area() - (8.0 + core::f32::consts::TAU)).abs() < 0.25);
+    assert!((object.position.x.0 - 12.0).abs() < 1e-3);
+    assert!(object
+        .color()
+        .is_some_and(|color| (color.r.0 - 1.0).abs() < 1e-3 && color.g.0 == 0.0));
+    assert_eq!(object.physics_type(), Some(PhysicsType::Static));
+    assert_eq!(object.killer(), Some(false));
+
+    // colors with characters that take up more than one byte are ignored
+    let svg = r##"<svg><polygon points="0,0 1,0 0,1" fill="#é1"/></svg>"##;
+    import_svg(&mut imported, svg, &ImportOptions::new(layer_id)).unwrap();
+    assert_eq!(imported.objects.last().unwrap().color(), None);
+}
+
+#[test]
+fn tile_layer_round_trip() {
+    use crate::types::tile_layer::{TileLayer, TileLayerKind, EMPTY};
+
+    for file in [
+        &include_bytes!("test_files/1.exolvl")[..],
+        include_bytes!("test_files/2.exolvl"),
+    ] {
+        let level = Exolvl::read(&mut Cursor::new(extract(file).unwrap()))
+            .unwrap()
+            .level_data;
+
+        for kind in TileLayerKind::ALL {
+            let layer = level.tile_layer(kind).unwrap();
+            assert_eq!(&layer.encode(), level.tiles(kind));
+        }
+    }
+
+    // a 3x2 layer at (-1, 4)
+    let values = [3, 2, -1, 4, 5, EMPTY, 6, EMPTY, EMPTY, 7];
+    let mut layer = TileLayer::decode(&values).unwrap();
+
+    assert_eq!(layer.encode(), values);
+    assert_eq!(layer.get(-1, 4), Some(5));
+    assert_eq!(layer.get(1, 5), Some(7));
+    assert_eq!(layer.get(0, 4), None);
+    assert_eq!(layer.get(2, 4), None);
+    assert_eq!(
+        layer.iter().collect::<Vec<_>>(),
+        [((-1, 4), 5), ((1, 4), 6), ((1, 5), 7)]
+    );
+
+    assert_eq!(layer.set(3, 3, 8).unwrap(), None);
+    assert_eq!(
+        (layer.origin(), layer.width(), layer.height()),
+        ((-1, 3), 5, 3)
+    );
+    assert_eq!(layer.get(-1, 4), Some(5));
+    assert_eq!(layer.get(3, 3), Some(8));
+
+    assert_eq!(layer.clear(-1, 4), Some(5));
+    layer.shrink_to_fit();
+    assert_eq!(
+        (layer.origin(), layer.width(), layer.height()),
+        ((1, 3), 3, 3)
+    );
+    assert_eq!(layer.iter().count(), 3);
+
+    assert!(TileLayer::decode(&[2, 2, 0, 0, 1]).is_err());
+    assert!(TileLayer::decode(&[-1, 0, 0, 0]).is_err());
+    assert!(TileLayer::decode(&[2, 1, i32::MAX, 0, 1, 1]).is_err());
+
+    // a header without cells is kept
+    for values in [[0, 0, 0, 0], [0, 0, 5, -3]] {
+        assert_eq!(TileLayer::decode(&values).unwrap().encode(), values);
+    }
+    assert_eq!(TileLayer::default().encode(), []);
+
+    // a layer can't span every column
+    let mut layer = TileLayer::default();
+    layer.set(i32::MIN, 0, 1).unwrap();
+    assert!(layer.set(i32::MAX, 0, 2).is_err());
+    assert_eq!(layer.iter().collect::<Vec<_>>(), [((i32::MIN, 0), 1)]);
+}
+
+#[test]
+fn editing_keeps_references_consistent() {
+    use crate::types::{
+        dynamic_type::DynamicType, novascript::nova_value::NovaValue, properties::Properties,
+        vec2::Vec2,
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let mut level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let world_position = |level: &crate::types::level_data::LevelData, id: i32| {
+        let object = level.object(id).unwrap();
+        let group = level.object(object.in_group);
+
+        group.map_or(object.position, |group| {
+            crate::geometry::Transform::of(group).apply(object.position)
+        })
+    };
+    let close = |a: Vec2, b: Vec2| (a - b).length() < 1e-4;
+
+    // ungrouping keeps members in place, where the group was
+    let before = [7, 9, 10, 11].map(|id| world_position(&level, id));
+    assert_eq!(level.ungroup(27).unwrap(), [7, 9, 10, 11]);
+    assert!(level.object(27).is_none());
+    assert_eq!(level.layers[0].children[13..], [7, 9, 10, 11]);
+
+    for (id, before) in [7, 9, 10, 11].into_iter().zip(before) {
+        assert_eq!(level.object(id).unwrap().in_group, 0);
+        assert!(close(world_position(&level, id), before));
+    }
+
+    // grouping puts them back together
+    let group = level.group(&[9, 10]).unwrap();
+    assert_eq!(group, level.next_entity_id() - 1);
+    assert_eq!(level.layers[0].children[13..], [7, group, 11]);
+    assert_eq!(level.object(group).unwrap().group_members, [9, 10]);
+    assert!(close(world_position(&level, 9), before[1]));
+
+    assert!(level.group(&[9, 11]).is_err());
+    assert!(level.group(&[7, 7]).is_err());
+
+    // moving a member out of its group keeps it in place
+    level.move_to_layer(10, 3).unwrap();
+    assert_eq!(level.object(group).unwrap().group_members, [9]);
+    assert_eq!(level.layers[2].children, [32, 10]);
+    assert!(close(world_position(&level, 10), before[2]));
+
+    // removing a group removes its members and all references to them
+    level.object_mut(7).unwrap().set_linked_objects(vec![9, 11]);
+    level.global_variables[0].initial_value =
+        NovaValue::new_int_list(DynamicType::ObjectSetConstant, vec![9, 11]);
+    level.global_variables[1].initial_value = NovaValue::new_int(DynamicType::ObjectConstant, 9);
+
+    let removed = level.remove_object(group);
+    assert_eq!(
+        removed
+            .iter()
+            .map(|object| object.entity_id)
+            .collect::<Vec<_>>(),
+        [group, 9]
+    );
+    assert_eq!(level.layers[0].children[13..], [7, 11]);
+    assert_eq!(level.object(7).unwrap().linked_objects(), Some(&[11][..]));
+    assert_eq!(
+        level.global_variables[0].initial_value,
+        NovaValue::new_int_list(DynamicType::ObjectSetConstant, vec![11])
+    );
+    assert_eq!(
+        level.global_variables[1].initial_value,
+        NovaValue::new_int(DynamicType::ObjectConstant, 0)
+    );
+
+    // new objects get fresh ids
+    let copy = level.object(11).unwrap().clone();
+    let id = level.add_object(copy).unwrap();
+    assert_eq!(
+        level
+            .objects
+            .iter()
+            .filter(|object| object.entity_id == id)
+            .count(),
+        1
+    );
+    assert_eq!(level.layers[0].children.last(), Some(&id));
+
+    // objects can only be added to groups
+    let mut copy = level.object(11).unwrap().clone();
+    copy.in_group = id;
+    assert!(matches!(
+        level.add_object(copy.clone()),
+        Err(crate::error::Error::InvalidEdit(_))
+    ));
+
+    // objects outside of groups have to be added to a layer that exists
+    let mut orphan = copy.clone();
+    orphan.in_group = 0;
+    orphan.in_layer = -1;
+    let count = level.objects.len();
+    assert!(matches!(
+        level.add_object(orphan),
+        Err(crate::error::Error::LayerNotFound(-1))
+    ));
+    assert_eq!(level.objects.len(), count);
+
+    // the largest id doesn't stop new ids from being handed out
+    level.object_mut(id).unwrap().entity_id = i32::MAX;
+    copy.in_group = 0;
+    let id = level.add_object(copy).unwrap();
+    assert!(id > 0 && id < i32::MAX);
+}
+
+#[test]
+fn prefab_instances() {
+    use crate::{
+        catalogue,
+        types::{image::Image, prefab::Prefab, properties::Properties, vec2::Vec2},
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let mut level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let square = catalogue::lookup(catalogue::SQUARE).unwrap();
+    let group = catalogue::lookup(catalogue::GROUP).unwrap();
+
+    // a group containing a square linked to a second square
+    let mut items = vec![
+        group.new_object(10, Vec2::new(1.0, 0.0), 0),
+        square.new_object(11, Vec2::new(0.5, 0.0), 0),
+        square.new_object(12, Vec2::new(-1.0, 0.0), 0),
+    ];
+    items[0].group_members = vec![11];
+    items[1].in_group = 10;
+    items[1].set_linked_objects(vec![12]);
+
+    // the type of the image depends on the image feature
+    #[allow(clippy::default_trait_access)]
+    level.prefabs.push(Prefab {
+        prefab_id: 5,
+        prefab_image_data: Image(Default::default()),
+        items,
+    });
+
+    let first = level.instantiate_prefab(5, Vec2::new(3.0, 4.0), 1).unwrap();
+    let second = level
+        .instantiate_prefab(5, Vec2::new(-3.0, 4.0), 1)
+        .unwrap();
+
+    assert_eq!(level.prefab_instances(5), [first, second]);
+    assert!(level.layers[0].children.ends_with(&[first, second]));
+
+    let copy = |level: &crate::types::level_data::LevelData, root: i32, item: i32| {
+        level
+            .with_members(root)
+            .into_iter()
+            .find(|&id| level.object(id).unwrap().prefab_entity_id == item)
+            .unwrap()
+    };
+
+    let (group_copy, linked, target) = (
+        copy(&level, first, 10),
+        copy(&level, first, 11),
+        copy(&level, first, 12),
+    );
+    assert_eq!(
+        level.object(first).unwrap().group_members,
+        [group_copy, target]
+    );
+    assert_eq!(level.object(group_copy).unwrap().group_members, [linked]);
+    assert_eq!(
+        level.object(linked).unwrap().linked_objects(),
+        Some(&[target][..])
+    );
+    assert_ne!(copy(&level, second, 11), linked);
+
+    // remove the second square and change the first one
+    let prefab = &mut level.prefabs[0];
+    prefab.items.pop();
+    prefab.items[1].set_linked_objects(Vec::new());
+    prefab.items[1].set_border_thickness(0.25);
+
+    assert_eq!(level.sync_prefab_instances(5).unwrap(), 2);
+    assert!(level.object(target).is_none());
+    assert_eq!(level.object(first).unwrap().group_members, [group_copy]);
+    assert_eq!(level.object(linked).unwrap().border_thickness(), Some(0.25));
+
+    let objects = level.objects.len();
+    assert_eq!(level.detach_prefab_instance(first).unwrap(), [group_copy]);
+    assert_eq!(level.objects.len(), objects - 1);
+    assert_eq!(level.object(linked).unwrap().prefab_id, 0);
+    assert_eq!(level.prefab_instances(5), [second]);
+}
+
+#[test]
+fn merge_levels() {
+    use crate::{
+        edit::{Conflict, MergeOptions},
+        geometry::Rect,
+        types::{
+            dynamic_type::DynamicType, level_data::LevelData, novascript::nova_value::NovaValue,
+            vec2::Vec2,
+        },
+    };
+    use std::collections::HashSet;
+
+    fn check_references(level: &LevelData) {
+        let ids = level
+            .objects
+            .iter()
+            .map(|object| object.entity_id)
+            .collect::<HashSet<_>>();
+        assert_eq!(ids.len(), level.objects.len());
+
+        for object in &level.objects {
+            assert!(object.in_group == 0 || ids.contains(&object.in_group));
+            assert!(object.group_members.iter().all(|id| ids.contains(id)));
+            assert!(level
+                .layers
+                .iter()
+                .any(|layer| layer.layer_id == object.in_layer));
+        }
+
+        for layer in &level.layers {
+            assert!(layer.children.iter().all(|id| ids.contains(id)));
+        }
+    }
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let original = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let offset = Vec2::new(100.0, 0.0);
+    let mut level = original.clone();
+    let map = level.merge(
+        &original,
+        &MergeOptions {
+            offset,
+            variables: Conflict::Merge,
+            ..Default::default()
+        },
+    );
+
+    check_references(&level);
+    assert_eq!(level.objects.len(), original.objects.len() * 2);
+    assert_eq!(level.layers.len(), original.layers.len() * 2);
+    assert_eq!(level.global_variables, original.global_variables);
+    assert_eq!(level.nova_scripts.len(), original.nova_scripts.len() * 2);
+
+    let copy = level.object(map.entities[&1]).unwrap();
+    let object = original.object(1).unwrap();
+    assert_eq!(copy.position, object.position + offset);
+    assert_eq!(copy.in_layer, map.layers[&object.in_layer]);
+
+    // scripts refer to the copies
+    let mut constants = Vec::new();
+    level.for_each_value_mut(|value| {
+        if value.dynamic_type == DynamicType::ObjectConstant {
+            constants.push(value.clone());
+        }
+    });
+    assert!(constants.contains(&NovaValue::new_int(DynamicType::ObjectConstant, 27)));
+    assert!(constants.contains(&NovaValue::new_int(
+        DynamicType::ObjectConstant,
+        map.entities[&27]
+    )));
+
+    // importing the area around a group imports the whole group
+    let mut level = original.clone();
+    let region = Rect::point(original.object(27).unwrap().position);
+    let map = level.merge(
+        &original,
+        &MergeOptions {
+            region: Some(region),
+            ..Default::default()
+        },
+    );
+
+    check_references(&level);
+    for id in [27, 7, 9, 10, 11] {
+        assert!(map.entities.contains_key(&id));
+    }
+    assert_eq!(
+        level.objects.len(),
+        original.objects.len() + map.entities.len()
+    );
+
+    // the largest id in the target doesn't stop the merged objects from getting new ids
+    let mut level = original.clone();
+    let id = level.objects[0].entity_id;
+    level.objects[0].entity_id = i32::MAX;
+    for layer in &mut level.layers {
+        for child in &mut layer.children {
+            if *child == id {
+                *child = i32::MAX;
+            }
+        }
+    }
+    let map = level.merge(&original, &MergeOptions::default());
+
+    check_references(&level);
+    assert_eq!(level.objects.len(), original.objects.len() * 2);
+    assert!(map.entities.values().all(|&id| id > 0 && id < i32::MAX));
+}
+
+#[test]
+fn merge_keeps_variables_visible() {
+    use crate::{
+        builder::{action, expr},
+        edit::MergeOptions,
+        interpreter::{Interpreter, Recorder, Value},
+        types::level_data::LevelData,
+    };
+
+    // a global variable that gets a new id isn't hidden by a local variable of a merged script
+    let mut level = LevelData::default();
+    level.add_global::<i32>("first", 0);
+
+    let mut other = LevelData::default();
+    let counter = other.add_global::<i32>("counter", 0);
+    let mut script = other.new_script("count");
+    script.variable::<i32>("local", 0);
+    script.action(action::set(counter, counter.get() + expr::int(1)));
+    script.finish();
+    other.nova_scripts[0].script_id = 5;
+    other.nova_scripts[0].variables[0].variable_id = 2;
+
+    let map = level.merge(&other, &MergeOptions::default());
+    let variable = map.variables[&counter.id()];
+    assert_ne!(variable, 2);
+
+    let mut interpreter = Interpreter::new(&level, Recorder::default()).unwrap();
+    interpreter.start_script(map.scripts[&5]).unwrap();
+    assert!(interpreter.run_until_idle(1.0).unwrap());
+    assert_eq!(interpreter.global(variable), Some(&Value::Int(1)));
+}
+
+#[test]
+fn level_diff_metadata() {
+    use crate::diff::LevelDiff;
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let old = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap();
+
+    assert!(LevelDiff::new(&old, &old).is_empty());
+
+    let mut new = old.clone();
+    new.local_level.level_name = String::from("renamed");
+    new.level_data.gold_medal_time = 12_000;
+
+    let diff = LevelDiff::new(&old, &new);
+
+    assert_eq!(diff.metadata.level_name.as_ref().unwrap().new, "renamed");
+    assert_eq!(
+        diff.medal_times.gold_medal_time.as_ref().unwrap().new,
+        12_000
+    );
+    assert!(diff.settings.is_empty());
+    assert!(diff.layer_order.is_none());
+    assert!(diff.objects.is_empty());
+
+    let text = diff.to_string();
+    assert!(text.contains("level_name: \"New level\" -> \"renamed\"\n"));
+}
+
+#[test]
+fn level_diff_layer_order() {
+    use crate::diff::LevelDiff;
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let old = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap();
+
+    let mut new = old.clone();
+    new.level_data.layers.swap(0, 1);
+
+    let diff = LevelDiff::new(&old, &new);
+
+    let order = diff.layer_order.as_ref().unwrap();
+    assert_eq!(order.new[0], old.level_data.layers[1].layer_id);
+    assert!(diff.layers.is_empty());
+    assert!(diff.to_string().contains("~ layer order: "));
+}
+
+#[test]
+fn level_diff_objects() {
+    use crate::{
+        diff::{Entry, LevelDiff},
+        types::{object_property::PropertyKey, vec2::Vec2},
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let old = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap();
+
+    let mut new = old.clone();
+    let moved = new.level_data.object_mut(1).unwrap();
+    moved.position = moved.position + Vec2::new(1.0, 0.0);
+    new.level_data.object_mut(2).unwrap().properties.clear();
+    new.level_data.remove_object(92);
+    let added = new
+        .level_data
+        .add_object(old.level_data.objects[0].clone())
+        .unwrap();
+
+    let diff = LevelDiff::new(&old, &new);
+
+    assert!(matches!(diff.objects[&92], Entry::Removed(_)));
+    assert!(matches!(diff.objects[&added], Entry::Added(_)));
+
+    let Entry::Modified(changes) = &diff.objects[&1] else {
+        panic!("object 1 should be modified");
+    };
+    assert!(changes.fields.position.is_some());
+    assert!(changes.properties.is_empty());
+
+    let Entry::Modified(changes) = &diff.objects[&2] else {
+        panic!("object 2 should be modified");
+    };
+    assert!(changes.fields.is_empty());
+    assert!(matches!(
+        changes.properties[&PropertyKey::TerrainCorners],
+        Entry::Removed(_)
+    ));
+
+    let text = diff.to_string();
+    assert!(text.contains("- object 92\n"));
+    assert!(text.contains(&format!("+ object {added}\n")));
+    assert!(text.contains("    - TerrainCorners: "));
+}
+
+#[test]
+fn level_diff_actions() {
+    use crate::diff::{ActionChange, Entry, LevelDiff};
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let old = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap();
+
+    let mut new = old.clone();
+    let script = &mut new.level_data.nova_scripts[1];
+    script.actions[0].wait = !script.actions[0].wait;
+    script.actions.push(script.actions[0].clone());
+    let script_id = script.script_id;
+
+    let diff = LevelDiff::new(&old, &new);
+
+    let Entry::Modified(changes) = &diff.scripts[&script_id] else {
+        panic!("the script should be modified");
+    };
+    assert!(changes.fields.is_empty());
+    assert!(matches!(
+        changes.actions.as_slice(),
+        [
+            ActionChange::Modified {
+                old_index: 0,
+                new_index: 0,
+                ..
+            },
+            ActionChange::Inserted { .. }
+        ]
+    ));
+
+    // the start of the action that didn't change is skipped
+    let text = diff.to_string();
+    assert!(text.contains("~ action 0: ..."));
+    assert!(text.contains("wait: false, action_type: Move"));
+    assert!(text.contains("wait: true, action_type: Move"));
+}
+
+#[test]
+fn level_diff_nested_actions() {
+    use crate::{
+        diff::{ActionChange, Entry, LevelDiff, Patch},
+        types::{
+            action_type::ActionType,
+            dynamic_type::DynamicType,
+            novascript::{action::Action, nova_value::NovaValue},
+        },
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let mut old = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap();
+
+    // changes inside a nested action are listed by their place in the nested actions
+    let script = &mut old.level_data.nova_scripts[1];
+    let script_id = script.script_id;
+    let actions = core::mem::take(&mut script.actions);
+    script.actions.push(Action {
+        closed: false,
+        wait: false,
+        action_type: ActionType::Repeat {
+            actions,
+            count: NovaValue::new_int(DynamicType::IntConstant, 2),
+        },
+    });
+
+    let mut new = old.clone();
+    let nested = new.level_data.nova_scripts[1].actions[0]
+        .action_type
+        .actions_mut()
+        .remove(0);
+    nested[0].wait ^= true;
+    nested.push(nested[0].clone());
+
+    let diff = LevelDiff::new(&old, &new);
+
+    let Entry::Modified(changes) = &diff.scripts[&script_id] else {
+        panic!("the script should be modified");
+    };
+    let [ActionChange::Nested {
+        old_index: 0,
+        new_index: 0,
+        list: 0,
+        changes,
+    }] = changes.actions.as_slice()
+    else {
+        panic!("only the nested actions should be changed");
+    };
+    assert!(matches!(
+        changes.as_slice(),
+        [
+            ActionChange::Modified {
+                old_index: 0,
+                new_index: 0,
+                ..
+            },
+            ActionChange::Inserted { .. }
+        ]
+    ));
+
+    let text = diff.to_string();
+    assert!(text.contains("    ~ action 0, list 0:\n        ~ action 0: ..."));
+
+    let mut patched = old;
+    Patch::from_diff(&diff).apply(&mut patched).unwrap();
+    assert_eq!(patched, new);
+}
+
+#[test]
+fn three_way_merge() {
+    use crate::{
+        diff::{three_way_merge, ConflictKind, Location, MergeConflict, Side},
+        types::vec2::Vec2,
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let base = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap();
+
+    let mut ours = base.clone();
+    ours.local_level.level_name = String::from("renamed");
+    let moved = ours.level_data.object_mut(1).unwrap();
+    moved.position = moved.position + Vec2::new(1.0, 0.0);
+    let moved_to = moved.position;
+    ours.level_data.object_mut(2).unwrap().tag = String::from("ours");
+    let our_object = ours
+        .level_data
+        .add_object(base.level_data.objects[0].clone())
+        .unwrap();
+    ours.level_data.nova_scripts[1].actions[0].wait ^= true;
+
+    let mut theirs = base.clone();
+    theirs.level_data.gold_medal_time = 12_000;
+    let their_object = theirs
+        .level_data
+        .add_object(base.level_data.objects[1].clone())
+        .unwrap();
+    theirs.level_data.remove_object(92);
+    theirs.level_data.remove_object(1);
+    theirs.level_data.object_mut(2).unwrap().tag = String::from("theirs");
+    theirs.level_data.nova_scripts[1].actions.pop();
+
+    // both sides added an object with the same id
+    assert_eq!(our_object, their_object);
+
+    let merge = three_way_merge(&base, &ours, &theirs);
+    let level = &merge.level.level_data;
+
+    assert_eq!(merge.level.local_level.level_name, "renamed");
+    assert_eq!(level.gold_medal_time, 12_000);
+    assert!(level.object(92).is_none());
+    assert_eq!(level.object(1).unwrap().position, moved_to);
+    assert_eq!(level.object(2).unwrap().tag, "ours");
+    assert_eq!(level.objects.len(), base.level_data.objects.len() + 1);
+
+    // their object got a new id
+    let renumbered = level.objects.last().unwrap();
+    assert_ne!(renumbered.entity_id, our_object);
+    assert_eq!(renumbered.tile_id, base.level_data.objects[1].tile_id);
+
+    let children = &level.layers[0].children;
+    assert!(!children.contains(&92));
+    assert!(children.contains(&1));
+    assert!(children.contains(&our_object));
+    assert!(children.contains(&renumbered.entity_id));
+    assert!(level
+        .objects
+        .iter()
+        .filter(|object| object.in_group == 0)
+        .all(|object| level
+            .layers
+            .iter()
+            .any(|layer| layer.children.contains(&object.entity_id))));
+
+    let script_id = base.level_data.nova_scripts[1].script_id;
+    assert_eq!(
+        merge.conflicts,
+        [
+            MergeConflict {
+                location: Location::Object(1),
+                kind: ConflictKind::RemovedAndModified {
+                    removed_by: Side::Theirs
+                },
+            },
+            MergeConflict {
+                location: Location::Object(2),
+                kind: ConflictKind::Field(String::from("tag")),
+            },
+            MergeConflict {
+                location: Location::Script(script_id),
+                kind: ConflictKind::Actions,
+            },
+        ]
+    );
+    assert_eq!(
+        merge.conflicts[0].to_string(),
+        "object 1: removed by theirs but changed by the other side"
+    );
+}
+
+#[test]
+fn patch_from_diff() {
+    use crate::{
+        diff::{LevelDiff, Operation, Patch},
+        error::Error,
+        types::vec2::Vec2,
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let old = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap();
+
+    let mut new = old.clone();
+    new.local_level.level_name = String::from("renamed");
+    new.level_data.gravity = Vec2::new(0.0, -50.0);
+    new.level_data.layers.swap(0, 2);
+    let moved = new.level_data.object_mut(1).unwrap();
+    moved.position = moved.position + Vec2::new(1.0, 0.0);
+    new.level_data.object_mut(2).unwrap().properties.clear();
+    new.level_data.remove_object(92);
+    new.level_data
+        .add_object(old.level_data.objects[0].clone())
+        .unwrap();
+    new.level_data
+        .add_object(old.level_data.objects[1].clone())
+        .unwrap();
+
+    let actions = &mut new.level_data.nova_scripts[1].actions;
+    let first = actions.remove(0);
+    actions.push(first.clone());
+    actions.insert(1, first);
+    actions[0].wait ^= true;
+
+    let patch = Patch::from_diff(&LevelDiff::new(&old, &new));
+    assert!(patch
+        .operations
+        .iter()
+        .any(|operation| matches!(operation, Operation::ReorderLayers(_))));
+
+    let mut patched = old.clone();
+    patch.apply(&mut patched).unwrap();
+    assert_eq!(patched, new);
+
+    // the removed object doesn't exist anymore, and nothing is changed
+    let result = patch.apply(&mut patched);
+    assert!(matches!(result, Err(Error::PatchFailed { .. })));
+    assert_eq!(patched, new);
+
+    let mut forced = old;
+    forced.local_level.level_name = String::from("other name");
+    patch.apply_ignoring_preconditions(&mut forced).unwrap();
+    assert_eq!(forced, new);
+}
+
+#[test]
+fn level_stats() {
+    use crate::types::{image::Image, object_property::ObjectProperty, prefab::Prefab};
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let stats = level.stats();
+    assert_eq!(stats.objects, level.objects.len());
+    assert_eq!(stats.objects_by_tile.values().sum::<usize>(), stats.objects);
+    assert_eq!(
+        stats.objects_by_layer.values().sum::<usize>(),
+        stats.objects
+    );
+    assert_eq!(stats.scripts, 2);
+    assert_eq!(stats.global_variables, level.global_variables.len());
+
+    let terrain_vertices = level
+        .objects
+        .iter()
+        .flat_map(|object| &object.properties)
+        .map(|property| match property {
+            ObjectProperty::TerrainCorners(rings) => rings.iter().map(Vec::len).sum(),
+            _ => 0,
+        })
+        .sum::<usize>();
+    assert!(terrain_vertices > 0);
+    assert_eq!(stats.terrain_vertices, terrain_vertices);
+
+    let top_level = level
+        .nova_scripts
+        .iter()
+        .map(|script| script.actions.len())
+        .sum::<usize>();
+    assert!(stats.actions >= top_level);
+    assert!(stats.max_action_depth >= 1);
+
+    assert!(stats.to_string().contains("scripts: 2"));
+
+    // the images of prefabs are counted, both decoded and encoded
+    #[cfg(feature = "image")]
+    let image = Image(image::RgbaImage::new(2, 3));
+    #[cfg(not(feature = "image"))]
+    let image = Image(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\x02\0\0\0\x03".to_vec());
+    let encoded = u64::try_from(image.encoded_len().unwrap()).unwrap();
+
+    let mut level = level;
+    level.prefabs.push(Prefab {
+        prefab_id: 1,
+        prefab_image_data: image,
+        items: Vec::new(),
+    });
+
+    let stats = level.stats();
+    assert_eq!(stats.image_bytes, 2 * 3 * 4);
+    assert!(encoded > 0);
+    assert_eq!(stats.encoded_image_bytes, encoded);
+    assert!(stats
+        .to_string()
+        .contains(&format!("image bytes: 24 ({encoded} encoded)")));
+}
+
+#[test]
+fn reference_index() {
+    use crate::{
+        diff::Location,
+        references::{Step, Target},
+        types::action_type::ActionType,
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+    let index = level.reference_index();
+
+    let bullets = level.global_variables[0].variable_id;
+    let uses = index.uses(Target::Object(92));
+    assert_eq!(uses.len(), 4);
+    assert_eq!(uses[0].location, Location::GlobalVariable(bullets));
+    assert_eq!(uses[0].path, [Step::InitialValue, Step::Element(10)]);
+
+    // the variable is set by the first action of the first nested list of the first action
+    let set = &index.uses(Target::GlobalVariable(bullets))[0];
+    let script = level.nova_scripts[0].script_id;
+    assert_eq!(set.location, Location::Script(script));
+    assert_eq!(
+        set.path,
+        [
+            Step::Action(0),
+            Step::Nested(0),
+            Step::Action(0),
+            Step::Variable
+        ]
+    );
+    assert!(matches!(
+        level.nova_scripts[0].actions[0].action_type.actions()[0][0].action_type,
+        ActionType::SetVariable { .. }
+    ));
+    assert_eq!(
+        set.to_string(),
+        format!("script {script} > action 0 > nested actions 0 > action 0 > variable")
+    );
+
+    assert!(!index.is_used(Target::Object(level.next_entity_id())));
+}
+
+#[test]
+fn refactorings() {
+    use crate::{
+        builder::{action, expr},
+        error::Error,
+        references::Target,
+        types::{
+            action_type::ActionType, dynamic_type::DynamicType, level_data::LevelData,
+            novascript::static_type::StaticType,
+        },
+    };
+
+    let mut level = LevelData::default();
+    let speed = level.add_global::<f32>("speed", 2.0);
+    let other = level.add_global::<i32>("other", 0);
+
+    let mut function = level.new_script("accelerate");
+    let amount = function.parameter::<f32>("amount", 1.0);
+    function.action(action::set(speed, speed.get() + expr::param(amount)));
+    let function = function.finish();
+
+    let mut script = level.new_script("main");
+    let counter = script.variable::<i32>("counter", 0);
+    script.action(action::run_function(function).arg(amount, expr::float(3.0)));
+    script.action(action::set(counter, counter.get() + expr::int(1)));
+    script.action(action::set(speed, expr::float(0.0)));
+    let main = script.finish();
+
+    let result = level.rename_variable(Target::GlobalVariable(other.id()), "speed");
+    assert!(matches!(result, Err(Error::DuplicateName(_))));
+    let result = level.rename_script(main, "accelerate");
+    assert!(matches!(result, Err(Error::DuplicateName(_))));
+    level.rename_script(main, "start").unwrap();
+    assert_eq!(level.nova_scripts[1].script_name, "start");
+
+    // a local variable of the caller would hide the global variable the function uses
+    let mut hidden = level.clone();
+    let mut local = hidden.nova_scripts[1].variables[0].clone();
+    local.variable_id = speed.id();
+    hidden.nova_scripts[1].variables.push(local);
+    let before = hidden.clone();
+    let result = hidden.inline_function(function);
+    assert!(matches!(result, Err(Error::InvalidEdit(_))));
+    assert_eq!(hidden, before);
+
+    // the argument replaces the parameter
+    let mut inlined = level.clone();
+    assert_eq!(inlined.inline_function(function).unwrap(), 1);
+    assert_eq!(
+        inlined.nova_scripts[1].actions[0],
+        action::set(speed, speed.get() + expr::float(3.0))
+    );
+
+    // the local variable has to be made global before the action using it can be extracted
+    let result = inlined.extract_function(main, 1..2, "count");
+    assert!(matches!(result, Err(Error::InvalidEdit(_))));
+    let global = inlined.make_variable_global(main, counter.id()).unwrap();
+    assert!(inlined.nova_scripts[1].variables.is_empty());
+    assert_eq!(inlined.global_variables[2].variable_id, global);
+
+    let count = inlined.extract_function(main, 1..2, "count").unwrap();
+    let extracted = &inlined.nova_scripts[2];
+    assert_eq!(extracted.script_id, count);
+    assert!(extracted.is_function);
+    assert!(matches!(
+        extracted.actions[0].action_type,
+        ActionType::SetVariable { variable, .. } if variable == global
+    ));
+    assert!(matches!(
+        &inlined.nova_scripts[1].actions[1].action_type,
+        ActionType::RunFunction { function } if function.id == count
+    ));
+
+    // setting the variable to itself is still fine, but passing it as a float argument isn't
+    let actions = &mut level.nova_scripts[1].actions;
+    actions.push(action::set(speed, speed.get()));
+    actions.push(
+        action::run_function(function)
+            .arg(amount, speed.get())
+            .into(),
+    );
+
+    // the reads where floats are expected and both assignments of floats are errors now
+    let errors = level
+        .change_variable_type(Target::GlobalVariable(speed.id()), StaticType::Int)
+        .unwrap();
+    assert_eq!(errors.len(), 4);
+    let reads = errors
+        .iter()
+        .filter(|error| error.expected == StaticType::Float && error.found == StaticType::Int)
+        .count();
+    assert_eq!(reads, 2);
+    assert_eq!(
+        level.global_variables[0].initial_value.dynamic_type,
+        DynamicType::IntConstant
+    );
+    assert_eq!(
+        level
+            .reference_index()
+            .uses(Target::GlobalVariable(speed.id()))
+            .len(),
+        6
+    );
+}
+
+#[test]
+fn prune_unused() {
+    use crate::{
+        catalogue,
+        types::{
+            brush::{Brush, BrushGrid},
+            color::Color,
+            image::Image,
+            object_property::ObjectProperty,
+            pattern::Pattern,
+            prefab::Prefab,
+            vec2::Vec2,
+        },
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let mut level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    let square = catalogue::lookup(catalogue::SQUARE).unwrap();
+
+    for pattern_id in 1..=3 {
+        level.patterns.push(Pattern {
+            pattern_id,
+            pattern_frames: Vec::new(),
+        });
+    }
+
+    level.objects[0].properties.push(ObjectProperty::Pattern(1));
+
+    // pattern 2 is only used by a prefab without instances
+    let mut item = square.new_object(10, Vec2::new(0.0, 0.0), 0);
+    item.properties.push(ObjectProperty::Pattern(2));
+
+    // the type of the image depends on the image feature
+    #[allow(clippy::default_trait_access)]
+    for (prefab_id, item) in [
+        (5, item),
+        (6, square.new_object(10, Vec2::new(0.0, 0.0), 0)),
+    ] {
+        level.prefabs.push(Prefab {
+            prefab_id,
+            prefab_image_data: Image(Default::default()),
+            items: vec![item],
+        });
+    }
+
+    level.instantiate_prefab(6, Vec2::new(0.0, 0.0), 1).unwrap();
+
+    level.brushes.push(Brush {
+        brush_id: 7,
+        spread: Vec2::new(1.0, 1.0),
+        frequency: 1.0.into(),
+        grid: BrushGrid { x: 1, y: 1 },
+        objects: Vec::new(),
+    });
+
+    let used = level.custom_background_color;
+    let unused_color = Color::new(0.1, 0.2, 0.3, 1.0);
+    level.color_palette = Some(vec![used, used, unused_color]);
+
+    let before = level.clone();
+    let unused = level.unused_assets();
+    assert_eq!(unused.patterns, [2, 3]);
+    assert_eq!(unused.prefabs, [5]);
+    assert_eq!(unused.brushes, [7]);
+    assert_eq!(unused.colors, [used, unused_color]);
+    assert!(unused.bytes > 0);
+    assert_eq!(level, before);
+
+    assert_eq!(level.prune_unused(), unused);
+    assert_eq!(level.patterns.len(), 1);
+    assert_eq!(level.prefabs[0].prefab_id, 6);
+    assert!(level.brushes.is_empty());
+    assert_eq!(level.color_palette, Some(vec![used]));
+    assert!(level.unused_assets().is_empty());
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn pattern_frames() {
+    use crate::types::{image::Image, pattern::Pattern};
+    use core::time::Duration;
+    use image::{imageops::FilterType, Rgba, RgbaImage};
+
+    let red = RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]));
+    let blue = RgbaImage::from_pixel(4, 4, Rgba([0, 0, 255, 255]));
+
+    let mut pattern = Pattern {
+        pattern_id: 1,
+        pattern_frames: Vec::new(),
+    };
+
+    pattern.insert_frame(0, red.clone());
+    pattern.insert_frame(1, red);
+    pattern.insert_frame(0, blue);
+
+    // the blue frame is resized to the size of the first frame
+    assert_eq!(pattern.pattern_frames[0].dimensions(), Some((2, 2)));
+    assert_eq!(pattern.dedup_frames(), 1);
+
+    pattern.move_frame(0, 1);
+    assert_eq!(
+        pattern.pattern_frames[1].0.get_pixel(0, 0).0,
+        [0, 0, 255, 255]
+    );
+
+    let sheet = pattern.sprite_sheet().unwrap();
+    assert_eq!(sheet.dimensions(), (4, 2));
+    assert_eq!(sheet.get_pixel(3, 1).0, [0, 0, 255, 255]);
+
+    let mut gif = Vec::new();
+    pattern
+        .write_gif(&mut gif, Duration::from_millis(100))
+        .unwrap();
+    assert_eq!(image::guess_format(&gif).unwrap(), image::ImageFormat::Gif);
+
+    let mut apng = Vec::new();
+    pattern
+        .write_apng(&mut apng, Duration::from_millis(100))
+        .unwrap();
+    assert!(apng.windows(4).any(|chunk| chunk == b"acTL"));
+    assert_eq!(image::load_from_memory(&apng).unwrap().width(), 2);
+
+    pattern.resize_frames(8, 8, FilterType::Nearest);
+    assert_eq!(pattern.sprite_sheet().unwrap().dimensions(), (16, 8));
+    assert!(pattern.remove_frame(2).is_none());
+    assert!(pattern.remove_frame(0).is_some());
+
+    // frames that are too wide to fit next to each other
+    pattern.pattern_frames = vec![Image(RgbaImage::new(u32::MAX, 0)); 2];
+    assert!(pattern.sprite_sheet().is_none());
+}
+
+#[test]
+fn duplicate_images() {
+    use crate::{
+        edit::ImageLocation,
+        types::{image::Image, object_property::ObjectProperty, pattern::Pattern, prefab::Prefab},
+    };
+
+    let in_bytes = extract(include_bytes!("test_files/1.exolvl")).unwrap();
+    let mut level = Exolvl::read(&mut Cursor::new(in_bytes)).unwrap().level_data;
+
+    // the type of the image depends on the image feature
+    #[allow(clippy::default_trait_access)]
+    let image = Image(Default::default());
+
+    level.prefabs.push(Prefab {
+        prefab_id: 5,
+        prefab_image_data: image.clone(),
+        items: Vec::new(),
+    });
+
+    for (pattern_id, frames) in [(1, 1), (2, 1), (3, 0)] {
+        level.patterns.push(Pattern {
+            pattern_id,
+            pattern_frames: vec![image.clone(); frames],
+        });
+    }
+
+    level.objects[0].properties.push(ObjectProperty::Pattern(2));
+
+    assert_eq!(
+        level.duplicate_images(),
+        [vec![
+            ImageLocation::Prefab(5),
+            ImageLocation::PatternFrame {
+                pattern: 1,
+                frame: 0
+            },
+            ImageLocation::PatternFrame {
+                pattern: 2,
+                frame: 0
+            },
+        ]]
+    );
+
+    assert_eq!(level.dedup_patterns(), [(2, 1)].into());
+    assert_eq!(level.patterns.len(), 2);
+    assert!(level.objects[0]
+        .properties
+        .contains(&ObjectProperty::Pattern(1)));
+    assert!(level.dedup_patterns().is_empty());
+}
+
+#[test]
+fn recompress_png() {
+    use crate::types::image::recompress_png;
+    use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression, Crc};
+    use std::io::Read as _;
+
+    fn chunk(png: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+        let mut crc = Crc::new();
+        crc.update(kind);
+        crc.update(data);
+
+        png.extend(u32::try_from(data.len()).unwrap().to_be_bytes());
+        png.extend(kind);
+        png.extend(data);
+        png.extend(crc.sum().to_be_bytes());
+    }
+
+    // an uncompressed 16x16 RGBA image with a comment
+    let pixels = [0, 255, 0, 0, 255].repeat(16 * 16);
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::none());
+    std::io::Write::write_all(&mut encoder, &pixels).unwrap();
+    let data = encoder.finish().unwrap();
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(
+        &mut png,
+        b"IHDR",
+        &[0, 0, 0, 16, 0, 0, 0, 16, 8, 6, 0, 0, 0],
+    );
+    chunk(&mut png, b"tEXt", b"Comment\0made by hand");
+    chunk(&mut png, b"IDAT", &data[..100]);
+    chunk(&mut png, b"IDAT", &data[100..]);
+    chunk(&mut png, b"IEND", &[]);
+
+    let recompressed = recompress_png(&png).unwrap();
+    assert!(recompressed.len() < png.len());
+    assert_eq!(&recompressed[..24], &png[..24]);
+    assert!(!recompressed.windows(4).any(|kind| kind == b"tEXt"));
+
+    // the image data is the same
+    let start = recompressed
+        .windows(4)
+        .position(|kind| kind == b"IDAT")
+        .unwrap()
+        + 4;
+    let mut decompressed = Vec::new();
+    ZlibDecoder::new(&recompressed[start..])
+        .read_to_end(&mut decompressed)
+        .unwrap();
+    assert_eq!(decompressed, pixels);
+
+    // it's already as small as it gets
+    assert_eq!(recompress_png(&recompressed), None);
+    assert_eq!(recompress_png(b"not a png"), None);
+
+    // a chunk length that doesn't fit in the image
+    let mut broken = png.clone();
+    broken[8..12].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert_eq!(recompress_png(&broken), None);
+
+    #[cfg(not(feature = "image"))]
+    {
+        use crate::types::image::Image;
+
+        let mut image = Image(png);
+        assert!(image.recompress());
+        assert_eq!(image.0, recompressed);
+        assert_eq!(image.dimensions(), Some((16, 16)));
+        assert!(!image.recompress());
+    }
+}
+
+#[test]
+fn brush_scatter() {
+    use crate::types::{
+        brush::{Brush, BrushGrid, BrushObject},
+        object_property::ObjectProperty,
+        vec2::Vec2,
+    };
+    use ordered_float::OrderedFloat;
+
+    let brush_object = |entity_id, weight, flip_x| BrushObject {
+        entity_id,
+        properties: Vec::new(),
+        weight: OrderedFloat(weight),
+        scale: OrderedFloat(1.0),
+        rotation: OrderedFloat(0.0),
+        flip_x,
+        flip_y: false,
+    };
+
+    let mut brush = Brush {
+        brush_id: 0,
+        spread: Vec2::new(0.5, 0.5),
+        frequency: OrderedFloat(1.0),
+        grid: BrushGrid { x: 0, y: 0 },
+        objects: vec![brush_object(1, 1.0, false), brush_object(2, 3.0, true)],
+    };
+
+    let stroke = [
+        Vec2::new(0.0, 0.0),
+        Vec2::new(4.0, 0.0),
+        Vec2::new(4.0, 6.0),
+    ];
+    let objects = brush.scatter(&stroke, 42);
+
+    // one object at the start and one every unit along the stroke
+    assert_eq!(objects.len(), 11);
+    assert_eq!(objects, brush.scatter(&stroke, 42));
+    assert_ne!(objects, brush.scatter(&stroke, 43));
+
+    for object in &objects {
+        assert!(object.position.x.0.abs() <= 4.5 && object.position.y.0.abs() <= 6.5);
+        assert_eq!(
+            object.properties.contains(&ObjectProperty::FlipX(true)),
+            object.tile_id == 2
+        );
+    }
+
+    // snapped to the grid, with at most one object per cell
+    brush.grid = BrushGrid { x: 2, y: 2 };
+    let objects = brush.scatter(&stroke, 42);
+    assert!(objects.len() < 11);
+
+    for (i, object) in objects.iter().enumerate() {
+        assert!(object.position.x.0 % 2.0 == 0.0 && object.position.y.0 % 2.0 == 0.0);
+        assert!(objects[..i]
+            .iter()
+            .all(|other| other.position != object.position));
+    }
+
+    // a huge frequency places a limited number of objects instead of running forever
+    brush.grid = BrushGrid { x: 0, y: 0 };
+    brush.frequency = OrderedFloat(1e7);
+    let long = [Vec2::new(0.0, 0.0), Vec2::new(1000.0, 0.0)];
+    assert_eq!(brush.scatter(&long, 42).len(), Brush::MAX_STAMPS);
+    brush.frequency = OrderedFloat(f32::MIN_POSITIVE);
+    assert_eq!(brush.scatter(&long, 42).len(), 1);
+
+    // nothing to place
+    brush.objects.clear();
+    assert!(brush.scatter(&stroke, 42).is_empty());
+}
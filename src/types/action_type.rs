@@ -246,6 +246,465 @@ pub enum ActionType {
     },
 }
 
+impl ActionType {
+    /// Returns the expressions used as arguments by this action, in the order they are stored in the file.
+    ///
+    /// This doesn't include the expressions of nested actions (e.g. the actions inside a [`ActionType::Repeat`]).
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn values(&self) -> Vec<&NovaValue> {
+        match self {
+            Self::Repeat { count, .. } => vec![count],
+            Self::RepeatWhile { condition, .. } | Self::ConditionBlock { condition, .. } => {
+                vec![condition]
+            }
+            Self::Wait { duration } => vec![duration],
+            Self::WaitFrames { frames } => vec![frames],
+            Self::Move {
+                target_objects,
+                position,
+                global,
+                duration,
+                easing,
+            } => vec![target_objects, position, global, duration, easing],
+            Self::Scale {
+                target_objects,
+                scale,
+                duration,
+                easing,
+            } => vec![target_objects, scale, duration, easing],
+            Self::Rotate {
+                target_objects,
+                rotation,
+                shortest_path,
+                global,
+                duration,
+                easing,
+            } => vec![
+                target_objects,
+                rotation,
+                shortest_path,
+                global,
+                duration,
+                easing,
+            ],
+            Self::RotateAround {
+                target_objects,
+                pivot,
+                rotation,
+                rotate_target,
+                duration,
+                easing,
+            } => vec![
+                target_objects,
+                pivot,
+                rotation,
+                rotate_target,
+                duration,
+                easing,
+            ],
+            Self::SetVariable { value, .. } => value.iter().collect(),
+            Self::ResetVariable { .. } | Self::GameFinish | Self::CameraFollowPlayer => Vec::new(),
+            Self::ResetObject { target_objects }
+            | Self::Activate { target_objects }
+            | Self::Deactivate { target_objects }
+            | Self::Kill { target_objects }
+            | Self::RepeatForEachObject { target_objects, .. }
+            | Self::PlayParticleSystem { target_objects } => vec![target_objects],
+            Self::SetColor {
+                target_objects,
+                color,
+                channel,
+                duration,
+                easing,
+            } => vec![target_objects, color, channel, duration, easing],
+            Self::SetTransparency {
+                target_objects,
+                transparency,
+                channel,
+                duration,
+                easing,
+            } => vec![target_objects, transparency, channel, duration, easing],
+            Self::SetSecondaryColor {
+                target_objects,
+                color,
+                duration,
+                easing,
+            }
+            | Self::SetBorderColor {
+                target_objects,
+                color,
+                duration,
+                easing,
+            } => vec![target_objects, color, duration, easing],
+            Self::SetSecondaryTransparency {
+                target_objects,
+                transparency,
+                duration,
+                easing,
+            }
+            | Self::SetBorderTransparency {
+                target_objects,
+                transparency,
+                duration,
+                easing,
+            } => vec![target_objects, transparency, duration, easing],
+            Self::SetSprite {
+                target_objects,
+                sprite,
+            } => vec![target_objects, sprite],
+            Self::SetText {
+                target_objects,
+                text,
+            } => vec![target_objects, text],
+            Self::SetEnabled {
+                target_objects,
+                enabled,
+            } => vec![target_objects, enabled],
+            Self::Damage {
+                target_objects,
+                damage,
+            } => vec![target_objects, damage],
+            Self::CameraPan {
+                position,
+                duration,
+                easing,
+            } => vec![position, duration, easing],
+            Self::CameraZoom {
+                viewport_size,
+                duration,
+                easing,
+            } => vec![viewport_size, duration, easing],
+            Self::CameraZoomReset { duration, easing }
+            | Self::CameraOffsetReset { duration, easing } => vec![duration, easing],
+            Self::CameraOffset {
+                offset,
+                duration,
+                easing,
+            } => vec![offset, duration, easing],
+            Self::CameraShake {
+                strength,
+                roughness,
+                fade_in,
+                fade_out,
+                duration,
+            } => vec![strength, roughness, fade_in, fade_out, duration],
+            Self::PlaySound {
+                sound,
+                volume,
+                pitch,
+            } => vec![sound, volume, pitch],
+            Self::PlayMusic {
+                music,
+                volume,
+                pitch,
+            } => vec![music, volume, pitch],
+            Self::SetDirection {
+                target_objects,
+                direction,
+            } => vec![target_objects, direction],
+            Self::SetGravity {
+                target_objects,
+                gravity,
+            } => vec![target_objects, gravity],
+            Self::SetVelocity {
+                target_objects,
+                velocity,
+            } => vec![target_objects, velocity],
+            Self::SetCinematic { enabled }
+            | Self::SetInputEnabled { enabled }
+            | Self::SetTimerEnabled { enabled } => vec![enabled],
+            Self::GameTextShow { text, duration } => vec![text, duration],
+            Self::DialogueShow {
+                text,
+                position,
+                reverse_direction,
+            } => vec![text, position, reverse_direction],
+            Self::StopScript { script } => vec![script],
+            Self::TransitionIn {
+                type_,
+                color,
+                duration,
+                easing,
+            }
+            | Self::TransitionOut {
+                type_,
+                color,
+                duration,
+                easing,
+            } => vec![type_, color, duration, easing],
+            Self::TimeScale {
+                time_scale,
+                duration,
+                easing,
+            } => vec![time_scale, duration, easing],
+            Self::RunFunction { function } => function
+                .parameters
+                .iter()
+                .map(|parameter| &parameter.value)
+                .collect(),
+            Self::SetVariableOverTime {
+                value,
+                duration,
+                easing,
+                ..
+            } => value.iter().chain([duration, easing]).collect(),
+            Self::StopSound {
+                sound_instance,
+                fade_out,
+            } => vec![sound_instance, fade_out],
+            Self::StopParticleSystem {
+                target_objects,
+                clear,
+            } => vec![target_objects, clear],
+        }
+    }
+
+    /// Returns mutable references to the expressions used as arguments by this action, see [`ActionType::values`].
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn values_mut(&mut self) -> Vec<&mut NovaValue> {
+        match self {
+            Self::Repeat { count, .. } => vec![count],
+            Self::RepeatWhile { condition, .. } | Self::ConditionBlock { condition, .. } => {
+                vec![condition]
+            }
+            Self::Wait { duration } => vec![duration],
+            Self::WaitFrames { frames } => vec![frames],
+            Self::Move {
+                target_objects,
+                position,
+                global,
+                duration,
+                easing,
+            } => vec![target_objects, position, global, duration, easing],
+            Self::Scale {
+                target_objects,
+                scale,
+                duration,
+                easing,
+            } => vec![target_objects, scale, duration, easing],
+            Self::Rotate {
+                target_objects,
+                rotation,
+                shortest_path,
+                global,
+                duration,
+                easing,
+            } => vec![
+                target_objects,
+                rotation,
+                shortest_path,
+                global,
+                duration,
+                easing,
+            ],
+            Self::RotateAround {
+                target_objects,
+                pivot,
+                rotation,
+                rotate_target,
+                duration,
+                easing,
+            } => vec![
+                target_objects,
+                pivot,
+                rotation,
+                rotate_target,
+                duration,
+                easing,
+            ],
+            Self::SetVariable { value, .. } => value.iter_mut().collect(),
+            Self::ResetVariable { .. } | Self::GameFinish | Self::CameraFollowPlayer => Vec::new(),
+            Self::ResetObject { target_objects }
+            | Self::Activate { target_objects }
+            | Self::Deactivate { target_objects }
+            | Self::Kill { target_objects }
+            | Self::RepeatForEachObject { target_objects, .. }
+            | Self::PlayParticleSystem { target_objects } => vec![target_objects],
+            Self::SetColor {
+                target_objects,
+                color,
+                channel,
+                duration,
+                easing,
+            } => vec![target_objects, color, channel, duration, easing],
+            Self::SetTransparency {
+                target_objects,
+                transparency,
+                channel,
+                duration,
+                easing,
+            } => vec![target_objects, transparency, channel, duration, easing],
+            Self::SetSecondaryColor {
+                target_objects,
+                color,
+                duration,
+                easing,
+            }
+            | Self::SetBorderColor {
+                target_objects,
+                color,
+                duration,
+                easing,
+            } => vec![target_objects, color, duration, easing],
+            Self::SetSecondaryTransparency {
+                target_objects,
+                transparency,
+                duration,
+                easing,
+            }
+            | Self::SetBorderTransparency {
+                target_objects,
+                transparency,
+                duration,
+                easing,
+            } => vec![target_objects, transparency, duration, easing],
+            Self::SetSprite {
+                target_objects,
+                sprite,
+            } => vec![target_objects, sprite],
+            Self::SetText {
+                target_objects,
+                text,
+            } => vec![target_objects, text],
+            Self::SetEnabled {
+                target_objects,
+                enabled,
+            } => vec![target_objects, enabled],
+            Self::Damage {
+                target_objects,
+                damage,
+            } => vec![target_objects, damage],
+            Self::CameraPan {
+                position,
+                duration,
+                easing,
+            } => vec![position, duration, easing],
+            Self::CameraZoom {
+                viewport_size,
+                duration,
+                easing,
+            } => vec![viewport_size, duration, easing],
+            Self::CameraZoomReset { duration, easing }
+            | Self::CameraOffsetReset { duration, easing } => vec![duration, easing],
+            Self::CameraOffset {
+                offset,
+                duration,
+                easing,
+            } => vec![offset, duration, easing],
+            Self::CameraShake {
+                strength,
+                roughness,
+                fade_in,
+                fade_out,
+                duration,
+            } => vec![strength, roughness, fade_in, fade_out, duration],
+            Self::PlaySound {
+                sound,
+                volume,
+                pitch,
+            } => vec![sound, volume, pitch],
+            Self::PlayMusic {
+                music,
+                volume,
+                pitch,
+            } => vec![music, volume, pitch],
+            Self::SetDirection {
+                target_objects,
+                direction,
+            } => vec![target_objects, direction],
+            Self::SetGravity {
+                target_objects,
+                gravity,
+            } => vec![target_objects, gravity],
+            Self::SetVelocity {
+                target_objects,
+                velocity,
+            } => vec![target_objects, velocity],
+            Self::SetCinematic { enabled }
+            | Self::SetInputEnabled { enabled }
+            | Self::SetTimerEnabled { enabled } => vec![enabled],
+            Self::GameTextShow { text, duration } => vec![text, duration],
+            Self::DialogueShow {
+                text,
+                position,
+                reverse_direction,
+            } => vec![text, position, reverse_direction],
+            Self::StopScript { script } => vec![script],
+            Self::TransitionIn {
+                type_,
+                color,
+                duration,
+                easing,
+            }
+            | Self::TransitionOut {
+                type_,
+                color,
+                duration,
+                easing,
+            } => vec![type_, color, duration, easing],
+            Self::TimeScale {
+                time_scale,
+                duration,
+                easing,
+            } => vec![time_scale, duration, easing],
+            Self::RunFunction { function } => function
+                .parameters
+                .iter_mut()
+                .map(|parameter| &mut parameter.value)
+                .collect(),
+            Self::SetVariableOverTime {
+                value,
+                duration,
+                easing,
+                ..
+            } => value.iter_mut().chain([duration, easing]).collect(),
+            Self::StopSound {
+                sound_instance,
+                fade_out,
+            } => vec![sound_instance, fade_out],
+            Self::StopParticleSystem {
+                target_objects,
+                clear,
+            } => vec![target_objects, clear],
+        }
+    }
+
+    /// Returns the lists of nested actions of this action, e.g. the `if_actions` and `else_actions` of a
+    /// [`ActionType::ConditionBlock`].
+    #[must_use]
+    pub fn actions(&self) -> Vec<&Vec<Action>> {
+        match self {
+            Self::Repeat { actions, .. }
+            | Self::RepeatWhile { actions, .. }
+            | Self::RepeatForEachObject { actions, .. } => vec![actions],
+            Self::ConditionBlock {
+                if_actions,
+                else_actions,
+                ..
+            } => vec![if_actions, else_actions],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns mutable references to the lists of nested actions of this action, see [`ActionType::actions`].
+    #[must_use]
+    pub fn actions_mut(&mut self) -> Vec<&mut Vec<Action>> {
+        match self {
+            Self::Repeat { actions, .. }
+            | Self::RepeatWhile { actions, .. }
+            | Self::RepeatForEachObject { actions, .. } => vec![actions],
+            Self::ConditionBlock {
+                if_actions,
+                else_actions,
+                ..
+            } => vec![if_actions, else_actions],
+            _ => Vec::new(),
+        }
+    }
+}
+
 impl From<&ActionType> for i32 {
     fn from(action_type: &ActionType) -> Self {
         match action_type {
@@ -0,0 +1,8 @@
+define_enum!(
+    /// How an object is blended with the objects behind it.
+    Blending {
+        Normal = 0,
+        Additive = 1,
+        Multiply = 2,
+    }
+);
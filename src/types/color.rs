@@ -10,6 +10,18 @@ pub struct Color {
     pub a: OrderedFloat<f32>,
 }
 
+impl Color {
+    #[must_use]
+    pub const fn new(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self {
+            r: OrderedFloat(r),
+            g: OrderedFloat(g),
+            b: OrderedFloat(b),
+            a: OrderedFloat(a),
+        }
+    }
+}
+
 impl Read for Color {
     fn read(input: &mut impl std::io::Read) -> Result<Self, Error> {
         Ok(Self {
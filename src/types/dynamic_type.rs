@@ -1,4 +1,4 @@
-use crate::{error::Error, Read, Write};
+use crate::{error::Error, types::novascript::static_type::StaticType, Read, Write};
 
 macro_rules! define_dynamic_type {
     ($($name:ident = $number:expr),*) => {
@@ -229,6 +229,297 @@ define_dynamic_type!(
     IntStringLength = 196
 );
 
+impl DynamicType {
+    /// Returns whether values of this type read a variable, whose id is stored as the int value.
+    #[must_use]
+    pub const fn is_variable(self) -> bool {
+        matches!(
+            self,
+            Self::BoolVariable
+                | Self::IntVariable
+                | Self::FloatVariable
+                | Self::StringVariable
+                | Self::ColorVariable
+                | Self::VectorVariable
+                | Self::SoundVariable
+                | Self::MusicVariable
+                | Self::ObjectVariable
+                | Self::ObjectSetVariable
+                | Self::TransitionVariable
+                | Self::EasingVariable
+                | Self::SpriteVariable
+                | Self::ScriptVariable
+                | Self::LayerVariable
+        )
+    }
+
+    /// Returns whether values of this type read a parameter of their function, whose id is stored as the int value.
+    #[must_use]
+    pub const fn is_parameter(self) -> bool {
+        matches!(
+            self,
+            Self::BoolParameter
+                | Self::IntParameter
+                | Self::FloatParameter
+                | Self::StringParameter
+                | Self::ColorParameter
+                | Self::VectorParameter
+                | Self::SoundParameter
+                | Self::MusicParameter
+                | Self::ObjectParameter
+                | Self::ObjectSetParameter
+                | Self::TransitionParameter
+                | Self::EasingParameter
+                | Self::SpriteParameter
+                | Self::ScriptParameter
+                | Self::LayerParameter
+        )
+    }
+
+    /// Returns the type of the values of this type.
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub const fn static_type(self) -> StaticType {
+        match self {
+            Self::BoolConstant
+            | Self::BoolVariable
+            | Self::BoolNot
+            | Self::BoolAnd
+            | Self::BoolOr
+            | Self::BoolEqualBool
+            | Self::BoolEqualNumber
+            | Self::BoolEqualString
+            | Self::BoolEqualColor
+            | Self::BoolEqualVector
+            | Self::BoolEqualObject
+            | Self::BoolNotEqualBool
+            | Self::BoolNotEqualNumber
+            | Self::BoolNotEqualString
+            | Self::BoolNotEqualColor
+            | Self::BoolNotEqualVector
+            | Self::BoolNotEqualObject
+            | Self::BoolLess
+            | Self::BoolLessOrEqual
+            | Self::BoolGreater
+            | Self::BoolGreaterOrEqual
+            | Self::BoolObjectDead
+            | Self::BoolPlayerOnGround
+            | Self::BoolPlayerOnWalljump
+            | Self::BoolPlayerOnBooster
+            | Self::BoolPlayerOnSwing
+            | Self::BoolPlayerInFloatingZone
+            | Self::BoolPlayerUsingGlider
+            | Self::BoolObjectsColliding
+            | Self::BoolInputPressed
+            | Self::BoolInputPressedLeft
+            | Self::BoolInputPressedRight
+            | Self::BoolInputHeld
+            | Self::BoolInputHeldLeft
+            | Self::BoolInputHeldRight
+            | Self::BoolInputReleased
+            | Self::BoolInputReleasedLeft
+            | Self::BoolInputReleasedRight
+            | Self::BoolObjectActivated
+            | Self::BoolPlayerJumpLocked
+            | Self::BoolParameter
+            | Self::BoolObjectsCollidingWithPoint
+            | Self::BoolPointerDown
+            | Self::BoolPointerHeld
+            | Self::BoolPointerReleased => StaticType::Bool,
+            Self::IntConstant
+            | Self::IntVariable
+            | Self::IntAdd
+            | Self::IntSubtract
+            | Self::IntMultiply
+            | Self::IntDivide
+            | Self::IntModulo
+            | Self::IntMin
+            | Self::IntMax
+            | Self::IntAbs
+            | Self::IntSign
+            | Self::IntRound
+            | Self::IntCeil
+            | Self::IntFloor
+            | Self::IntRandom
+            | Self::IntRepeatCount
+            | Self::IntObjectDirection
+            | Self::IntObjectSetCount
+            | Self::IntParameter
+            | Self::IntLastSoundInstance
+            | Self::IntStringLength => StaticType::Int,
+            Self::FloatConstant
+            | Self::FloatVariable
+            | Self::FloatAdd
+            | Self::FloatSubtract
+            | Self::FloatMultiply
+            | Self::FloatDivide
+            | Self::FloatModulo
+            | Self::FloatMin
+            | Self::FloatMax
+            | Self::FloatAbs
+            | Self::FloatSign
+            | Self::FloatRound
+            | Self::FloatCeil
+            | Self::FloatFloor
+            | Self::FloatCos
+            | Self::FloatSin
+            | Self::FloatTan
+            | Self::FloatAcos
+            | Self::FloatAsin
+            | Self::FloatAtan
+            | Self::FloatSqrt
+            | Self::FloatPow
+            | Self::FloatRandom
+            | Self::FloatTime
+            | Self::FloatSemitones
+            | Self::FloatVectorX
+            | Self::FloatVectorY
+            | Self::FloatVectorLength
+            | Self::FloatVectorLengthSqr
+            | Self::FloatVectorDistance
+            | Self::FloatVectorDistanceSqr
+            | Self::FloatVectorDot
+            | Self::FloatVectorAngle
+            | Self::FloatVectorAngleBetween
+            | Self::FloatObjectRotation
+            | Self::FloatObjectGlobalRotation
+            | Self::FloatCameraViewportSize
+            | Self::FloatDamageAmount
+            | Self::FloatLevelTime
+            | Self::FloatParameter
+            | Self::FloatRoundDecimals
+            | Self::FloatColorR
+            | Self::FloatColorG
+            | Self::FloatColorB
+            | Self::FloatColorA => StaticType::Float,
+            Self::StringConstant
+            | Self::StringVariable
+            | Self::StringFromInt
+            | Self::StringFromFloat
+            | Self::StringConcat
+            | Self::StringObjectTag
+            | Self::StringParameter
+            | Self::StringSubstring => StaticType::String,
+            Self::ColorConstant
+            | Self::ColorValues
+            | Self::ColorVariable
+            | Self::ColorObjectColor
+            | Self::ColorParameter => StaticType::Color,
+            Self::VectorConstant
+            | Self::VectorValues
+            | Self::VectorVariable
+            | Self::VectorAdd
+            | Self::VectorSubtract
+            | Self::VectorMultiply
+            | Self::VectorDivide
+            | Self::VectorNormalize
+            | Self::VectorPerpendicular
+            | Self::VectorReflect
+            | Self::VectorObjectPos
+            | Self::VectorObjectGlobalPos
+            | Self::VectorObjectScale
+            | Self::VectorObjectGlobalScale
+            | Self::VectorObjectVelocity
+            | Self::VectorCameraPos
+            | Self::VectorParameter
+            | Self::VectorPointerPositionDeprecated
+            | Self::VectorPointerWorldPositionDeprecated
+            | Self::VectorCollisionPoint
+            | Self::VectorCollisionNormal
+            | Self::VectorClosestFromPoint
+            | Self::VectorRotate
+            | Self::VectorPointerPosition
+            | Self::VectorPointerWorldPosition => StaticType::Vector,
+            Self::SoundConstant | Self::SoundVariable | Self::SoundParameter => StaticType::Sound,
+            Self::MusicConstant | Self::MusicVariable | Self::MusicParameter => StaticType::Music,
+            Self::ObjectConstant
+            | Self::ObjectVariable
+            | Self::ObjectAnyObject
+            | Self::ObjectFirstFromSet
+            | Self::ObjectRandomFromSet
+            | Self::ObjectElementFromSet
+            | Self::ObjectSourceObject
+            | Self::ObjectCollidedObject
+            | Self::ObjectTargetObject
+            | Self::ObjectPlayer
+            | Self::ObjectParent
+            | Self::ObjectParameter
+            | Self::ObjectRepeatObject => StaticType::Object,
+            Self::ObjectSetConstant
+            | Self::ObjectSetVariable
+            | Self::ObjectSetConcat
+            | Self::ObjectSetPlayers
+            | Self::ObjectSetObjectsWithTag
+            | Self::ObjectSetChildren
+            | Self::ObjectSetParameter
+            | Self::ObjectSetAllObjects
+            | Self::ObjectSetObjectsInLayer
+            | Self::ObjectSetObjectsInCircle
+            | Self::ObjectSetUnion
+            | Self::ObjectSetIntersection
+            | Self::ObjectSetDifference
+            | Self::ObjectSetRemoveAtIndex => StaticType::ObjectSet,
+            Self::TransitionConstant | Self::TransitionVariable | Self::TransitionParameter => {
+                StaticType::Transition
+            }
+            Self::EasingConstant | Self::EasingVariable | Self::EasingParameter => {
+                StaticType::Easing
+            }
+            Self::SpriteConstant | Self::SpriteVariable | Self::SpriteParameter => {
+                StaticType::Sprite
+            }
+            Self::ScriptConstant | Self::ScriptVariable | Self::ScriptParameter => {
+                StaticType::Script
+            }
+            Self::LayerConstant | Self::LayerVariable | Self::LayerParameter => StaticType::Layer,
+        }
+    }
+
+    /// Returns the dynamic type of values that read a variable of a type.
+    #[must_use]
+    pub const fn variable(static_type: StaticType) -> Self {
+        match static_type {
+            StaticType::Bool => Self::BoolVariable,
+            StaticType::Int => Self::IntVariable,
+            StaticType::Float => Self::FloatVariable,
+            StaticType::String => Self::StringVariable,
+            StaticType::Color => Self::ColorVariable,
+            StaticType::Vector => Self::VectorVariable,
+            StaticType::Sound => Self::SoundVariable,
+            StaticType::Music => Self::MusicVariable,
+            StaticType::Object => Self::ObjectVariable,
+            StaticType::ObjectSet => Self::ObjectSetVariable,
+            StaticType::Transition => Self::TransitionVariable,
+            StaticType::Easing => Self::EasingVariable,
+            StaticType::Sprite => Self::SpriteVariable,
+            StaticType::Script => Self::ScriptVariable,
+            StaticType::Layer => Self::LayerVariable,
+        }
+    }
+
+    /// Returns the dynamic type of values that read a parameter of a type.
+    #[must_use]
+    pub const fn parameter(static_type: StaticType) -> Self {
+        match static_type {
+            StaticType::Bool => Self::BoolParameter,
+            StaticType::Int => Self::IntParameter,
+            StaticType::Float => Self::FloatParameter,
+            StaticType::String => Self::StringParameter,
+            StaticType::Color => Self::ColorParameter,
+            StaticType::Vector => Self::VectorParameter,
+            StaticType::Sound => Self::SoundParameter,
+            StaticType::Music => Self::MusicParameter,
+            StaticType::Object => Self::ObjectParameter,
+            StaticType::ObjectSet => Self::ObjectSetParameter,
+            StaticType::Transition => Self::TransitionParameter,
+            StaticType::Easing => Self::EasingParameter,
+            StaticType::Sprite => Self::SpriteParameter,
+            StaticType::Script => Self::ScriptParameter,
+            StaticType::Layer => Self::LayerParameter,
+        }
+    }
+}
+
 impl Read for DynamicType {
     fn read(input: &mut impl std::io::Read) -> Result<Self, Error> {
         let value = i32::read(input)?;
@@ -0,0 +1,7 @@
+define_enum!(
+    /// The shape of the area particles are emitted from.
+    EmitterShape {
+        Rectangle = 0,
+        Circle = 1,
+    }
+);
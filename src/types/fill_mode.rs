@@ -0,0 +1,7 @@
+define_enum!(
+    /// How the inside of a shape or terrain object is filled.
+    FillMode {
+        Solid = 0,
+        Pattern = 1,
+    }
+);
@@ -0,0 +1,7 @@
+define_enum!(
+    /// Whether a layer is drawn behind or in front of the player.
+    ForegroundType {
+        Background = 0,
+        Foreground = 1,
+    }
+);
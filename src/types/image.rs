@@ -37,6 +37,151 @@ impl<'de> serde::Deserialize<'de> for Image {
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Image(pub Vec<u8>);
 
+impl Image {
+    /// Returns the width and height of the image in pixels.
+    #[cfg(feature = "image")]
+    #[must_use]
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        Some(self.0.dimensions())
+    }
+
+    /// Returns the number of bytes of the image when it's encoded as a PNG image, like it's written to a level.
+    ///
+    /// The image is encoded to count the bytes, so this returns `None` if it can't be encoded.
+    #[cfg(feature = "image")]
+    #[must_use]
+    pub fn encoded_len(&self) -> Option<usize> {
+        let mut output = std::io::Cursor::new(Vec::new());
+        self.0.write_to(&mut output, ImageFormat::Png).ok()?;
+
+        Some(output.into_inner().len())
+    }
+
+    /// Returns the number of bytes of the encoded image, as it's stored in a level.
+    #[cfg(not(feature = "image"))]
+    #[must_use]
+    pub const fn encoded_len(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+
+    /// Returns the width and height of the image in pixels.
+    ///
+    /// The size is read from the header of the encoded image, so this returns `None` if the image isn't a PNG image.
+    #[cfg(not(feature = "image"))]
+    #[must_use]
+    pub fn dimensions(&self) -> Option<(u32, u32)> {
+        const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+        let header = self.0.get(..24)?;
+
+        if !header.starts_with(SIGNATURE) || &header[12..16] != b"IHDR" {
+            return None;
+        }
+
+        let number = |bytes: &[u8]| bytes.try_into().ok().map(u32::from_be_bytes);
+
+        Some((number(&header[16..20])?, number(&header[20..24])?))
+    }
+
+    /// Recompresses a PNG image losslessly, and returns whether it got smaller, see [`recompress_png`].
+    ///
+    /// With the `image` feature, images are decoded when they're read and encoded again when they're written, so
+    /// this isn't available. Use [`recompress_png`] on the encoded image instead.
+    #[cfg(not(feature = "image"))]
+    pub fn recompress(&mut self) -> bool {
+        match recompress_png(&self.0) {
+            Some(output) => {
+                self.0 = output;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Recompresses a PNG image losslessly, and returns the smaller image.
+///
+/// Text, time, physical size and EXIF chunks are removed and the image data is compressed again with the best
+/// zlib compression. The decompressed image data isn't changed, so the pixels stay exactly the same. Images that
+/// aren't PNG images, animated PNG images and images that wouldn't get smaller give `None`.
+#[must_use]
+pub fn recompress_png(png: &[u8]) -> Option<Vec<u8>> {
+    use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression, Crc};
+    use std::io::{Read as _, Write as _};
+
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    const METADATA: [&[u8; 4]; 6] = [b"tEXt", b"zTXt", b"iTXt", b"tIME", b"pHYs", b"eXIf"];
+
+    let mut rest = png.strip_prefix(SIGNATURE)?;
+
+    let mut chunks = Vec::new();
+
+    while !rest.is_empty() {
+        let length = rest
+            .get(..4)
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(u32::from_be_bytes)
+            .and_then(|length| usize::try_from(length).ok())?;
+
+        // the length, kind and data of the chunk, and its checksum
+        let end = length.checked_add(12)?;
+        let chunk = rest.get(4..end)?;
+
+        chunks.push((&chunk[..4], &chunk[4..length + 4]));
+        rest = &rest[end..];
+    }
+
+    if chunks.iter().any(|(kind, _)| *kind == b"acTL") {
+        return None;
+    }
+
+    let compressed = chunks
+        .iter()
+        .filter(|(kind, _)| *kind == b"IDAT")
+        .flat_map(|(_, data)| data.iter().copied())
+        .collect::<Vec<_>>();
+
+    let mut data = Vec::new();
+
+    ZlibDecoder::new(compressed.as_slice())
+        .read_to_end(&mut data)
+        .ok()?;
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+    let recompressed = encoder
+        .write_all(&data)
+        .and_then(|()| encoder.finish())
+        .ok()?;
+
+    let mut output = SIGNATURE.to_vec();
+    let mut wrote_data = false;
+
+    for (kind, chunk) in chunks {
+        let chunk = match kind {
+            b"IDAT" if wrote_data => continue,
+            b"IDAT" => {
+                wrote_data = true;
+                recompressed.as_slice()
+            }
+            _ if METADATA.iter().any(|metadata| *metadata == kind) => continue,
+            _ => chunk,
+        };
+
+        let length = u32::try_from(chunk.len()).ok()?;
+
+        let mut crc = Crc::new();
+        crc.update(kind);
+        crc.update(chunk);
+
+        output.extend(length.to_be_bytes());
+        output.extend(kind);
+        output.extend(chunk);
+        output.extend(crc.sum().to_be_bytes());
+    }
+
+    (output.len() < png.len()).then_some(output)
+}
+
 impl Read for Image {
     fn read(input: &mut impl std::io::Read) -> Result<Self, Error> {
         let data = Read::read(input)?;
@@ -1,4 +1,4 @@
-use super::vec2::Vec2;
+use super::{foreground_type::ForegroundType, vec2::Vec2};
 use crate::{error::Error, Read, Write};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -10,7 +10,7 @@ pub struct Layer {
     pub selected: bool,
     pub invisible: bool,
     pub locked: bool,
-    pub foreground_type: i32,
+    pub foreground_type: ForegroundType,
     pub parallax: Vec2,
     pub fixed_size: bool,
     pub children: Vec<i32>,
@@ -1,4 +1,5 @@
 use super::{
+    blending::Blending,
     brush::Brush,
     color::Color,
     layer::Layer,
@@ -99,7 +100,7 @@ pub struct LevelData {
     /// Used when copying and pasting properties of terrain.
     pub custom_terrain_secondary_color: Color,
     /// The blend mode of the copied terrain.
-    pub custom_terrain_blend_mode: i32,
+    pub custom_terrain_blend_mode: Blending,
     /// The custom terrain border color of the level.
     pub custom_terrain_border_color: Color,
     /// The thickness of the terrain border.
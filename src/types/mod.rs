@@ -1,9 +1,70 @@
+macro_rules! define_enum {
+    (
+        $(#[$meta:meta])*
+        $name:ident {
+            $($(#[$variant_meta:meta])* $variant:ident = $number:literal),*$(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        ///
+        /// The variants only cover the values that are known to this crate, which aren't necessarily all the values
+        /// the game uses. Values that aren't known are kept as `Other`, so they are written back unchanged.
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+        pub enum $name {
+            $($(#[$variant_meta])* $variant,)*
+            /// A value that isn't known to this crate.
+            Other(i32),
+        }
+
+        impl From<i32> for $name {
+            fn from(value: i32) -> Self {
+                match value {
+                    $($number => Self::$variant,)*
+                    other => Self::Other(other),
+                }
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $number,)*
+                    $name::Other(other) => other,
+                }
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::from(0)
+            }
+        }
+
+        impl $crate::Read for $name {
+            fn read(input: &mut impl std::io::Read) -> Result<Self, $crate::error::Error> {
+                i32::read(input).map(Self::from)
+            }
+        }
+
+        impl $crate::Write for $name {
+            fn write(&self, output: &mut impl std::io::Write) -> Result<(), $crate::error::Error> {
+                i32::from(*self).write(output)
+            }
+        }
+    };
+}
+
 pub mod action_type;
 pub mod author_replay;
+pub mod blending;
 pub mod brush;
 pub mod color;
 pub mod dynamic_type;
+pub mod emitter_shape;
 pub mod exolvl;
+pub mod fill_mode;
+pub mod foreground_type;
 pub mod function_call;
 pub mod image;
 pub mod layer;
@@ -14,7 +75,11 @@ pub mod object;
 pub mod object_property;
 pub mod old_script;
 pub mod pattern;
+pub mod physics_type;
 pub mod prefab;
+pub mod properties;
+pub mod simulation_space;
 pub mod theme;
+pub mod tile_layer;
 pub(crate) mod varint;
 pub mod vec2;
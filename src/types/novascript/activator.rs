@@ -1,10 +1,11 @@
-use super::nova_value::NovaValue;
+use super::{nova_value::NovaValue, static_type::StaticType};
 use crate::{error::Error, Read, Write};
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Activator {
-    pub activator_type: i32,
+    pub activator_type: ActivatorType,
+    /// The parameters of the activator, see [`ActivatorType::parameter_types`] for their types.
     pub parameters: Vec<NovaValue>,
 }
 
@@ -23,3 +24,124 @@ impl Write for Activator {
         self.parameters.write(output)
     }
 }
+
+/// The event that starts a script.
+///
+/// This isn't a complete list of activators, only the ones whose values are known. The test levels also use the
+/// values 38 and 39, whose meaning isn't known. Values that aren't known to this crate are kept as `Other`, so they
+/// are written back unchanged.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ActivatorType {
+    /// The level starts.
+    LevelStart,
+    /// Every frame.
+    EveryFrame,
+    /// An object starts colliding with one of the objects in a set.
+    CollisionEnter,
+    /// The player uses an input.
+    Input(Input),
+    /// A value that isn't known to this crate.
+    Other(i32),
+}
+
+impl ActivatorType {
+    /// Returns the types of the parameters of this activator, in the order they are stored in
+    /// [`Activator::parameters`].
+    ///
+    /// Only the parameters of [`ActivatorType::CollisionEnter`] are known, this returns no types for other activators
+    /// even if they have parameters.
+    #[must_use]
+    pub const fn parameter_types(self) -> &'static [StaticType] {
+        match self {
+            Self::CollisionEnter => &[StaticType::Object, StaticType::ObjectSet],
+            Self::LevelStart | Self::EveryFrame | Self::Input(_) | Self::Other(_) => &[],
+        }
+    }
+}
+
+const INPUT_OFFSET: i32 = 28;
+
+impl From<i32> for ActivatorType {
+    fn from(value: i32) -> Self {
+        match value {
+            0 => Self::LevelStart,
+            2 => Self::EveryFrame,
+            4 => Self::CollisionEnter,
+            other => other
+                .checked_sub(INPUT_OFFSET)
+                .and_then(Input::from_index)
+                .map_or(Self::Other(other), Self::Input),
+        }
+    }
+}
+
+impl From<ActivatorType> for i32 {
+    fn from(value: ActivatorType) -> Self {
+        match value {
+            ActivatorType::LevelStart => 0,
+            ActivatorType::EveryFrame => 2,
+            ActivatorType::CollisionEnter => 4,
+            ActivatorType::Input(input) => INPUT_OFFSET + input as Self,
+            ActivatorType::Other(other) => other,
+        }
+    }
+}
+
+impl From<Input> for ActivatorType {
+    fn from(input: Input) -> Self {
+        Self::Input(input)
+    }
+}
+
+impl Read for ActivatorType {
+    fn read(input: &mut impl std::io::Read) -> Result<Self, Error> {
+        i32::read(input).map(Self::from)
+    }
+}
+
+impl Write for ActivatorType {
+    fn write(&self, output: &mut impl std::io::Write) -> Result<(), Error> {
+        i32::from(*self).write(output)
+    }
+}
+
+/// A player input, used by [`ActivatorType::Input`].
+///
+/// The variants are in the same order as the matching input expressions, e.g. [`DynamicType::BoolInputPressed`].
+///
+/// [`DynamicType::BoolInputPressed`]: crate::types::dynamic_type::DynamicType::BoolInputPressed
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Input {
+    Pressed,
+    PressedLeft,
+    PressedRight,
+    Held,
+    HeldLeft,
+    HeldRight,
+    Released,
+    ReleasedLeft,
+    ReleasedRight,
+}
+
+impl Input {
+    const ALL: [Self; 9] = [
+        Self::Pressed,
+        Self::PressedLeft,
+        Self::PressedRight,
+        Self::Held,
+        Self::HeldLeft,
+        Self::HeldRight,
+        Self::Released,
+        Self::ReleasedLeft,
+        Self::ReleasedRight,
+    ];
+
+    fn from_index(index: i32) -> Option<Self> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| Self::ALL.get(index))
+            .copied()
+    }
+}
@@ -0,0 +1,11 @@
+define_enum!(
+    /// Which color of an object is changed by [`ActionType::SetColor`] and [`ActionType::SetTransparency`].
+    ///
+    /// [`ActionType::SetColor`]: crate::types::action_type::ActionType::SetColor
+    /// [`ActionType::SetTransparency`]: crate::types::action_type::ActionType::SetTransparency
+    ColorChannel {
+        Primary = 0,
+        Secondary = 1,
+        Border = 2,
+    }
+);
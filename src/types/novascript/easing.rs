@@ -0,0 +1,11 @@
+define_enum!(
+    /// The easing of an action that happens over time.
+    ///
+    /// This is the value of [`DynamicType::EasingConstant`](crate::types::dynamic_type::DynamicType) expressions.
+    Easing {
+        Linear = 0,
+        In = 1,
+        Out = 2,
+        InOut = 3,
+    }
+);
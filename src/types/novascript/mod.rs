@@ -7,9 +7,12 @@ use variable::Variable;
 
 pub mod action;
 pub mod activator;
+pub mod color_channel;
+pub mod easing;
 pub mod nova_value;
 pub mod parameter;
 pub mod static_type;
+pub mod transition;
 pub mod variable;
 
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -0,0 +1,10 @@
+define_enum!(
+    /// The kind of a screen transition.
+    ///
+    /// Only the fade transition is known.
+    ///
+    /// This is the value of [`DynamicType::TransitionConstant`](crate::types::dynamic_type::DynamicType) expressions.
+    Transition {
+        Fade = 0,
+    }
+);
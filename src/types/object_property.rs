@@ -1,4 +1,7 @@
-use super::{color::Color, vec2::Vec2};
+use super::{
+    blending::Blending, color::Color, emitter_shape::EmitterShape, fill_mode::FillMode,
+    physics_type::PhysicsType, simulation_space::SimulationSpace, vec2::Vec2,
+};
 use crate::{error::Error, Read, Write};
 use ordered_float::OrderedFloat;
 
@@ -7,19 +10,19 @@ use ordered_float::OrderedFloat;
 pub enum ObjectProperty {
     Color(Color),
     Resolution(i32),
-    FillMode(i32),
+    FillMode(FillMode),
     SecondaryColor(Color),
     Thickness(OrderedFloat<f32>),
     TotalAngle(i32),
     Corners(i32),
-    Blending(i32),
+    Blending(Blending),
     GridOffset(Vec2),
     CornerRadius(OrderedFloat<f32>),
     Width(OrderedFloat<f32>),
     Height(OrderedFloat<f32>),
     BorderColor(Color),
     BorderThickness(OrderedFloat<f32>),
-    PhysicsType(i32),
+    PhysicsType(PhysicsType),
     Friction(OrderedFloat<f32>),
     TerrainCorners(Vec<Vec<Vec2>>),
     Direction(i32),
@@ -59,10 +62,10 @@ pub enum ObjectProperty {
     AutoPlay(bool),
     LifetimeMin(OrderedFloat<f32>),
     LifetimeMax(OrderedFloat<f32>),
-    SimulationSpace(i32),
+    SimulationSpace(SimulationSpace),
     Rate(OrderedFloat<f32>),
     Burst(i32),
-    EmitterShape(i32),
+    EmitterShape(EmitterShape),
     EmitterWidth(OrderedFloat<f32>),
     EmitterHeight(OrderedFloat<f32>),
     EmitterTotalAngle(OrderedFloat<f32>),
@@ -87,6 +90,199 @@ pub enum ObjectProperty {
     MoonOffset(OrderedFloat<f32>),
 }
 
+/// The kind of an [`ObjectProperty`], without its value.
+///
+/// Keys are ordered by their id in the file format, which is the canonical order of properties.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PropertyKey {
+    Color,
+    Resolution,
+    FillMode,
+    SecondaryColor,
+    Thickness,
+    TotalAngle,
+    Corners,
+    Blending,
+    GridOffset,
+    CornerRadius,
+    Width,
+    Height,
+    BorderColor,
+    BorderThickness,
+    PhysicsType,
+    Friction,
+    TerrainCorners,
+    Direction,
+    Impulse,
+    Killer,
+    RoundReflexAngles,
+    RoundCollider,
+    Radius,
+    Size,
+    ReverseDirection,
+    CollisionDetector,
+    Pattern,
+    PatternTiling,
+    PatternOffset,
+    Bounce,
+    RestoreVelocity,
+    Sprite,
+    Trigger,
+    Health,
+    DamageFromJump,
+    DamageFromDash,
+    ReverseDirOnDamage,
+    Floating,
+    LinkedObjects,
+    FlipX,
+    FlipY,
+    Text,
+    FontSize,
+    EditorColor,
+    Color2,
+    Color3,
+    Color4,
+    ParticleTexture,
+    Duration,
+    Delay,
+    Loop,
+    AutoPlay,
+    LifetimeMin,
+    LifetimeMax,
+    SimulationSpace,
+    Rate,
+    Burst,
+    EmitterShape,
+    EmitterWidth,
+    EmitterHeight,
+    EmitterTotalAngle,
+    SizeMin,
+    SizeMax,
+    SizeOverLifetime,
+    StartSizeMultiplier,
+    EndSizeMultiplier,
+    SpeedMin,
+    SpeedMax,
+    SpeeLimit,
+    SpeedDampen,
+    RotationMin,
+    RotationMax,
+    Rotationspeed,
+    ColorOverLifetime,
+    StartColorMultiplier,
+    EndColorMultiplier,
+    GravityMultiplier,
+    AnchorPos,
+    MoonInnerRadius,
+    MoonOffset,
+}
+
+macro_rules! impl_keys {
+    ($($variant:ident = $id:literal),*$(,)?) => {
+        impl ObjectProperty {
+            /// Returns the kind of this property.
+            #[must_use]
+            pub const fn key(&self) -> PropertyKey {
+                match self {
+                    $(Self::$variant(_) => PropertyKey::$variant,)*
+                }
+            }
+        }
+
+        impl From<PropertyKey> for i32 {
+            fn from(key: PropertyKey) -> Self {
+                match key {
+                    $(PropertyKey::$variant => $id,)*
+                }
+            }
+        }
+    };
+}
+
+impl_keys!(
+    Color = 0,
+    Resolution = 1,
+    FillMode = 2,
+    SecondaryColor = 3,
+    Thickness = 4,
+    TotalAngle = 5,
+    Corners = 6,
+    Blending = 7,
+    GridOffset = 8,
+    CornerRadius = 9,
+    Width = 10,
+    Height = 11,
+    BorderColor = 12,
+    BorderThickness = 13,
+    PhysicsType = 14,
+    Friction = 15,
+    TerrainCorners = 16,
+    Direction = 17,
+    Impulse = 18,
+    Killer = 19,
+    RoundReflexAngles = 20,
+    RoundCollider = 21,
+    Radius = 22,
+    Size = 23,
+    ReverseDirection = 24,
+    CollisionDetector = 25,
+    Pattern = 26,
+    PatternTiling = 27,
+    PatternOffset = 28,
+    Bounce = 32,
+    RestoreVelocity = 34,
+    Sprite = 35,
+    Trigger = 36,
+    Health = 37,
+    DamageFromJump = 38,
+    DamageFromDash = 39,
+    ReverseDirOnDamage = 40,
+    Floating = 41,
+    LinkedObjects = 42,
+    FlipX = 43,
+    FlipY = 44,
+    Text = 45,
+    FontSize = 46,
+    EditorColor = 47,
+    Color2 = 48,
+    Color3 = 49,
+    Color4 = 50,
+    ParticleTexture = 51,
+    Duration = 52,
+    Delay = 53,
+    Loop = 54,
+    AutoPlay = 55,
+    LifetimeMin = 56,
+    LifetimeMax = 57,
+    SimulationSpace = 58,
+    Rate = 59,
+    Burst = 60,
+    EmitterShape = 61,
+    EmitterWidth = 62,
+    EmitterHeight = 63,
+    EmitterTotalAngle = 64,
+    SizeMin = 65,
+    SizeMax = 66,
+    SizeOverLifetime = 67,
+    StartSizeMultiplier = 68,
+    EndSizeMultiplier = 69,
+    SpeedMin = 71,
+    SpeedMax = 72,
+    SpeeLimit = 73,
+    SpeedDampen = 74,
+    RotationMin = 75,
+    RotationMax = 76,
+    Rotationspeed = 77,
+    ColorOverLifetime = 78,
+    StartColorMultiplier = 79,
+    EndColorMultiplier = 80,
+    GravityMultiplier = 81,
+    AnchorPos = 82,
+    MoonInnerRadius = 83,
+    MoonOffset = 84,
+);
+
 impl Read for ObjectProperty {
     fn read(input: &mut impl std::io::Read) -> Result<Self, Error> {
         let property_type = Read::read(input)?;
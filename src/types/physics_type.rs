@@ -0,0 +1,10 @@
+define_enum!(
+    /// How an object interacts with the player.
+    ///
+    /// Only two physics types are known, the test levels only use [`PhysicsType::Decoration`].
+    PhysicsType {
+        Static = 0,
+        /// The object has no collision. Collision detectors still work.
+        Decoration = 4,
+    }
+);
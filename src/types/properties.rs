@@ -0,0 +1,276 @@
+//! Typed access to the properties of objects and brush objects.
+//!
+//! Properties are stored as a list in the order they were read, so that writing a level gives back the same bytes.
+//! The [`Properties`] trait looks properties up by their [`PropertyKey`] and keeps that order intact: setting a
+//! property that already exists replaces it in place, and new properties are inserted in canonical (id) order.
+
+use super::{
+    blending::Blending,
+    brush::BrushObject,
+    color::Color,
+    emitter_shape::EmitterShape,
+    fill_mode::FillMode,
+    object::Object,
+    object_property::{ObjectProperty, PropertyKey},
+    physics_type::PhysicsType,
+    simulation_space::SimulationSpace,
+    vec2::Vec2,
+};
+use crate::private::Sealed;
+use ordered_float::OrderedFloat;
+
+macro_rules! accessors {
+    ($($kind:ident $variant:ident: $get:ident, $set:ident $(, $ty:ty)?;)*) => {
+        $(accessors!(@$kind $variant, $get, $set $(, $ty)?);)*
+    };
+    (@copy $variant:ident, $get:ident, $set:ident, $ty:ty) => {
+        #[doc = concat!("Returns the value of the [`", stringify!($variant), "`](ObjectProperty::", stringify!($variant), ") property, if it is set.")]
+        #[must_use]
+        fn $get(&self) -> Option<$ty> {
+            match self.property(PropertyKey::$variant)? {
+                ObjectProperty::$variant(value) => Some(*value),
+                _ => None,
+            }
+        }
+
+        #[doc = concat!("Sets the value of the [`", stringify!($variant), "`](ObjectProperty::", stringify!($variant), ") property.")]
+        fn $set(&mut self, value: $ty) {
+            self.set_property(ObjectProperty::$variant(value));
+        }
+    };
+    (@float $variant:ident, $get:ident, $set:ident) => {
+        #[doc = concat!("Returns the value of the [`", stringify!($variant), "`](ObjectProperty::", stringify!($variant), ") property, if it is set.")]
+        #[must_use]
+        fn $get(&self) -> Option<f32> {
+            match self.property(PropertyKey::$variant)? {
+                ObjectProperty::$variant(value) => Some(value.0),
+                _ => None,
+            }
+        }
+
+        #[doc = concat!("Sets the value of the [`", stringify!($variant), "`](ObjectProperty::", stringify!($variant), ") property.")]
+        fn $set(&mut self, value: f32) {
+            self.set_property(ObjectProperty::$variant(OrderedFloat(value)));
+        }
+    };
+    (@string $variant:ident, $get:ident, $set:ident) => {
+        #[doc = concat!("Returns the value of the [`", stringify!($variant), "`](ObjectProperty::", stringify!($variant), ") property, if it is set.")]
+        #[must_use]
+        fn $get(&self) -> Option<&str> {
+            match self.property(PropertyKey::$variant)? {
+                ObjectProperty::$variant(value) => Some(value),
+                _ => None,
+            }
+        }
+
+        #[doc = concat!("Sets the value of the [`", stringify!($variant), "`](ObjectProperty::", stringify!($variant), ") property.")]
+        fn $set(&mut self, value: impl Into<String>) {
+            self.set_property(ObjectProperty::$variant(value.into()));
+        }
+    };
+    (@list $variant:ident, $get:ident, $set:ident, $ty:ty) => {
+        #[doc = concat!("Returns the value of the [`", stringify!($variant), "`](ObjectProperty::", stringify!($variant), ") property, if it is set.")]
+        #[must_use]
+        fn $get(&self) -> Option<&[$ty]> {
+            match self.property(PropertyKey::$variant)? {
+                ObjectProperty::$variant(value) => Some(value),
+                _ => None,
+            }
+        }
+
+        #[doc = concat!("Sets the value of the [`", stringify!($variant), "`](ObjectProperty::", stringify!($variant), ") property.")]
+        fn $set(&mut self, value: Vec<$ty>) {
+            self.set_property(ObjectProperty::$variant(value));
+        }
+    };
+}
+
+/// Typed access to a list of [`ObjectProperty`]s, implemented by [`Object`] and [`BrushObject`].
+///
+/// If a property appears more than once (see [`Properties::duplicate_properties`]), the last occurrence is the one
+/// that is read and replaced.
+pub trait Properties: Sealed {
+    /// Returns the properties in their stored order.
+    fn properties(&self) -> &[ObjectProperty];
+
+    /// Returns the list of properties for direct modification.
+    fn properties_mut(&mut self) -> &mut Vec<ObjectProperty>;
+
+    /// Returns the property with the given key, if it is set.
+    #[must_use]
+    fn property(&self, key: PropertyKey) -> Option<&ObjectProperty> {
+        self.properties()
+            .iter()
+            .rev()
+            .find(|property| property.key() == key)
+    }
+
+    /// Returns a mutable reference to the property with the given key, if it is set.
+    fn property_mut(&mut self, key: PropertyKey) -> Option<&mut ObjectProperty> {
+        self.properties_mut()
+            .iter_mut()
+            .rev()
+            .find(|property| property.key() == key)
+    }
+
+    /// Sets a property and returns its previous value.
+    ///
+    /// An existing property is replaced in place. A new property is inserted before the first property with a
+    /// higher key, so a list in canonical order stays in canonical order.
+    fn set_property(&mut self, property: ObjectProperty) -> Option<ObjectProperty> {
+        let key = property.key();
+
+        if let Some(existing) = self.property_mut(key) {
+            return Some(core::mem::replace(existing, property));
+        }
+
+        let properties = self.properties_mut();
+        let index = properties
+            .iter()
+            .position(|existing| existing.key() > key)
+            .unwrap_or(properties.len());
+        properties.insert(index, property);
+
+        None
+    }
+
+    /// Removes every occurrence of the property with the given key and returns the one that was in effect.
+    fn remove_property(&mut self, key: PropertyKey) -> Option<ObjectProperty> {
+        let properties = self.properties_mut();
+        let index = properties
+            .iter()
+            .rposition(|property| property.key() == key)?;
+        let removed = properties.remove(index);
+        properties.retain(|property| property.key() != key);
+
+        Some(removed)
+    }
+
+    /// Returns the keys of all properties that appear more than once, in canonical order.
+    #[must_use]
+    fn duplicate_properties(&self) -> Vec<PropertyKey> {
+        let mut keys = self
+            .properties()
+            .iter()
+            .map(ObjectProperty::key)
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+
+        let mut duplicates = keys
+            .windows(2)
+            .filter(|pair| pair[0] == pair[1])
+            .map(|pair| pair[0])
+            .collect::<Vec<_>>();
+        duplicates.dedup();
+
+        duplicates
+    }
+
+    /// Sorts the properties into canonical order, by key. Duplicates keep their relative order.
+    fn sort_properties(&mut self) {
+        self.properties_mut().sort_by_key(ObjectProperty::key);
+    }
+
+    accessors! {
+        copy Color: color, set_color, Color;
+        copy Resolution: resolution, set_resolution, i32;
+        copy FillMode: fill_mode, set_fill_mode, FillMode;
+        copy SecondaryColor: secondary_color, set_secondary_color, Color;
+        float Thickness: thickness, set_thickness;
+        copy TotalAngle: total_angle, set_total_angle, i32;
+        copy Corners: corners, set_corners, i32;
+        copy Blending: blending, set_blending, Blending;
+        copy GridOffset: grid_offset, set_grid_offset, Vec2;
+        float CornerRadius: corner_radius, set_corner_radius;
+        float Width: width, set_width;
+        float Height: height, set_height;
+        copy BorderColor: border_color, set_border_color, Color;
+        float BorderThickness: border_thickness, set_border_thickness;
+        copy PhysicsType: physics_type, set_physics_type, PhysicsType;
+        float Friction: friction, set_friction;
+        list TerrainCorners: terrain_corners, set_terrain_corners, Vec<Vec2>;
+        copy Direction: direction, set_direction, i32;
+        copy Impulse: impulse, set_impulse, i32;
+        copy Killer: killer, set_killer, bool;
+        copy RoundReflexAngles: round_reflex_angles, set_round_reflex_angles, bool;
+        copy RoundCollider: round_collider, set_round_collider, bool;
+        float Radius: radius, set_radius;
+        float Size: size, set_size;
+        copy ReverseDirection: reverse_direction, set_reverse_direction, bool;
+        copy CollisionDetector: collision_detector, set_collision_detector, bool;
+        copy Pattern: pattern, set_pattern, i32;
+        copy PatternTiling: pattern_tiling, set_pattern_tiling, Vec2;
+        copy PatternOffset: pattern_offset, set_pattern_offset, Vec2;
+        copy Bounce: bounce, set_bounce, bool;
+        copy RestoreVelocity: restore_velocity, set_restore_velocity, bool;
+        string Sprite: sprite, set_sprite;
+        copy Trigger: trigger, set_trigger, bool;
+        float Health: health, set_health;
+        copy DamageFromJump: damage_from_jump, set_damage_from_jump, bool;
+        copy DamageFromDash: damage_from_dash, set_damage_from_dash, bool;
+        copy ReverseDirOnDamage: reverse_dir_on_damage, set_reverse_dir_on_damage, bool;
+        copy Floating: floating, set_floating, bool;
+        list LinkedObjects: linked_objects, set_linked_objects, i32;
+        copy FlipX: flip_x, set_flip_x, bool;
+        copy FlipY: flip_y, set_flip_y, bool;
+        string Text: text, set_text;
+        float FontSize: font_size, set_font_size;
+        copy EditorColor: editor_color, set_editor_color, Color;
+        copy Color2: color_2, set_color_2, Color;
+        copy Color3: color_3, set_color_3, Color;
+        copy Color4: color_4, set_color_4, Color;
+        string ParticleTexture: particle_texture, set_particle_texture;
+        float Duration: duration, set_duration;
+        float Delay: delay, set_delay;
+        copy Loop: loop_, set_loop, bool;
+        copy AutoPlay: auto_play, set_auto_play, bool;
+        float LifetimeMin: lifetime_min, set_lifetime_min;
+        float LifetimeMax: lifetime_max, set_lifetime_max;
+        copy SimulationSpace: simulation_space, set_simulation_space, SimulationSpace;
+        float Rate: rate, set_rate;
+        copy Burst: burst, set_burst, i32;
+        copy EmitterShape: emitter_shape, set_emitter_shape, EmitterShape;
+        float EmitterWidth: emitter_width, set_emitter_width;
+        float EmitterHeight: emitter_height, set_emitter_height;
+        float EmitterTotalAngle: emitter_total_angle, set_emitter_total_angle;
+        float SizeMin: size_min, set_size_min;
+        float SizeMax: size_max, set_size_max;
+        copy SizeOverLifetime: size_over_lifetime, set_size_over_lifetime, bool;
+        float StartSizeMultiplier: start_size_multiplier, set_start_size_multiplier;
+        float EndSizeMultiplier: end_size_multiplier, set_end_size_multiplier;
+        float SpeedMin: speed_min, set_speed_min;
+        float SpeedMax: speed_max, set_speed_max;
+        float SpeeLimit: speed_limit, set_speed_limit;
+        float SpeedDampen: speed_dampen, set_speed_dampen;
+        float RotationMin: rotation_min, set_rotation_min;
+        float RotationMax: rotation_max, set_rotation_max;
+        float Rotationspeed: rotation_speed, set_rotation_speed;
+        copy ColorOverLifetime: color_over_lifetime, set_color_over_lifetime, bool;
+        copy StartColorMultiplier: start_color_multiplier, set_start_color_multiplier, Color;
+        copy EndColorMultiplier: end_color_multiplier, set_end_color_multiplier, Color;
+        float GravityMultiplier: gravity_multiplier, set_gravity_multiplier;
+        copy AnchorPos: anchor_pos, set_anchor_pos, Vec2;
+        float MoonInnerRadius: moon_inner_radius, set_moon_inner_radius;
+        float MoonOffset: moon_offset, set_moon_offset;
+    }
+}
+
+impl Properties for Object {
+    fn properties(&self) -> &[ObjectProperty] {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Vec<ObjectProperty> {
+        &mut self.properties
+    }
+}
+
+impl Properties for BrushObject {
+    fn properties(&self) -> &[ObjectProperty] {
+        &self.properties
+    }
+
+    fn properties_mut(&mut self) -> &mut Vec<ObjectProperty> {
+        &mut self.properties
+    }
+}
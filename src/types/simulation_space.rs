@@ -0,0 +1,9 @@
+define_enum!(
+    /// The space particles are simulated in.
+    SimulationSpace {
+        /// Particles move along with the emitter.
+        Local = 0,
+        /// Particles stay where they were emitted.
+        World = 1,
+    }
+);
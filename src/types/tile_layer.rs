@@ -0,0 +1,378 @@
+//! Tile maps of levels made in the legacy editor.
+//!
+//! [`LevelData`] stores its six tile maps as flat arrays of `i32`s. This module assumes that a non-empty array
+//! starts with a header of four values, the width, the height and the x and y position of the first cell, followed
+//! by `width * height` tile ids row by row, starting at the bottom left, and that cells without a tile contain
+//! [`EMPTY`]. A level without tiles in a layer has an empty array for it.
+//!
+//! **This layout is a guess.** All levels available to this crate have empty tile arrays, so only the empty case
+//! has been seen in real levels. Arrays that don't fit the layout are rejected by [`TileLayer::decode`], and every
+//! array that is accepted is [encoded](TileLayer::encode) back unchanged, so reading and writing a level never
+//! changes its tiles, but the meaning of the values may be wrong.
+//!
+//! ```
+//! use exolvl::types::level_data::LevelData;
+//! use exolvl::types::tile_layer::{TileLayer, TileLayerKind};
+//!
+//! let mut level = LevelData::default();
+//!
+//! let mut terrain = TileLayer::default();
+//! terrain.set(3, -2, 7).unwrap();
+//! level.set_tile_layer(TileLayerKind::Terrain, &terrain);
+//!
+//! assert_eq!(level.terrain_tiles, [1, 1, 3, -2, 7]);
+//! assert_eq!(level.tile_layer(TileLayerKind::Terrain).unwrap().get(3, -2), Some(7));
+//! ```
+
+use super::level_data::LevelData;
+use crate::error::Error;
+
+/// The tile id of cells without a tile.
+pub const EMPTY: i32 = 0;
+
+/// The number of values before the tiles in an encoded tile layer.
+const HEADER_LEN: usize = 4;
+
+/// One of the tile maps of a level.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TileLayerKind {
+    /// [`LevelData::under_decoration_tiles`].
+    UnderDecoration,
+    /// [`LevelData::background_decoration_tiles`].
+    BackgroundDecoration,
+    /// [`LevelData::terrain_tiles`].
+    Terrain,
+    /// [`LevelData::floating_zone_tiles`].
+    FloatingZone,
+    /// [`LevelData::object_tiles`].
+    Object,
+    /// [`LevelData::foreground_decoration_tiles`].
+    ForegroundDecoration,
+}
+
+impl TileLayerKind {
+    /// All tile layers, in the order they are stored in.
+    pub const ALL: [Self; 6] = [
+        Self::UnderDecoration,
+        Self::BackgroundDecoration,
+        Self::Terrain,
+        Self::FloatingZone,
+        Self::Object,
+        Self::ForegroundDecoration,
+    ];
+}
+
+/// A rectangular grid of tile ids.
+///
+/// The grid grows when a tile is set outside of it, and cells are addressed by their position in the level, so
+/// growing it doesn't move any tiles.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub struct TileLayer {
+    /// The position of the bottom left cell.
+    origin: (i32, i32),
+    width: u32,
+    height: u32,
+    /// The tile ids row by row, starting at the bottom left.
+    tiles: Vec<i32>,
+    /// Whether the layer has a header, which layers stored as an empty array don't have.
+    has_header: bool,
+}
+
+impl TileLayer {
+    /// Returns a layer of the given size where every cell is empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the layer would have cells outside of the `i32` range, or too many cells to store.
+    pub fn new(origin: (i32, i32), width: u32, height: u32) -> Result<Self, Error> {
+        let last = |origin: i32, size: u32| i64::from(origin) + i64::from(size) - 1;
+
+        if i32::try_from(last(origin.0, width)).is_err()
+            || i32::try_from(last(origin.1, height)).is_err()
+        {
+            return Err(Error::InvalidTileLayer(format!(
+                "a {width}x{height} layer at {origin:?} goes past the largest position"
+            )));
+        }
+
+        let cells = usize::try_from(u64::from(width) * u64::from(height))
+            .ok()
+            .filter(|&cells| cells <= isize::MAX as usize / size_of::<i32>())
+            .ok_or_else(|| {
+                Error::InvalidTileLayer(format!("a {width}x{height} layer is too large"))
+            })?;
+
+        Ok(Self {
+            origin,
+            width,
+            height,
+            tiles: vec![EMPTY; cells],
+            has_header: true,
+        })
+    }
+
+    /// Decodes a tile layer from one of the tile arrays of a [`LevelData`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the array is too short for its header, has a negative size, has cells outside of the
+    /// `i32` range, or doesn't have one tile per cell.
+    pub fn decode(values: &[i32]) -> Result<Self, Error> {
+        let Some((header, tiles)) = values.split_first_chunk::<HEADER_LEN>() else {
+            return if values.is_empty() {
+                Ok(Self::default())
+            } else {
+                Err(Error::InvalidTileLayer(format!(
+                    "expected a header of {HEADER_LEN} values, found {} values",
+                    values.len()
+                )))
+            };
+        };
+
+        let [width, height, x, y] = *header;
+
+        let (Ok(width), Ok(height)) = (u32::try_from(width), u32::try_from(height)) else {
+            return Err(Error::InvalidTileLayer(format!(
+                "invalid size {width}x{height}"
+            )));
+        };
+
+        let cells = u64::from(width) * u64::from(height);
+
+        if tiles.len() as u64 != cells {
+            return Err(Error::InvalidTileLayer(format!(
+                "expected {cells} tiles for a {width}x{height} layer, found {}",
+                tiles.len()
+            )));
+        }
+
+        let mut layer = Self::new((x, y), width, height)?;
+        layer.tiles.copy_from_slice(tiles);
+
+        Ok(layer)
+    }
+
+    /// Encodes the layer as it is stored in a [`LevelData`].
+    ///
+    /// Layers are encoded exactly as they were [decoded](Self::decode). The [default](Self::default) layer, and
+    /// layers that were decoded from an empty array or [shrunk](Self::shrink_to_fit) to nothing, are encoded as an
+    /// empty array.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the width or height doesn't fit in an `i32`.
+    #[must_use]
+    pub fn encode(&self) -> Vec<i32> {
+        if !self.has_header {
+            return Vec::new();
+        }
+
+        let size = |value: u32| i32::try_from(value).expect("tile layer is too large");
+
+        let mut values = Vec::with_capacity(HEADER_LEN + self.tiles.len());
+        values.extend([
+            size(self.width),
+            size(self.height),
+            self.origin.0,
+            self.origin.1,
+        ]);
+        values.extend(&self.tiles);
+
+        values
+    }
+
+    /// Returns the position of the bottom left cell.
+    #[must_use]
+    pub const fn origin(&self) -> (i32, i32) {
+        self.origin
+    }
+
+    /// Returns the number of columns.
+    #[must_use]
+    pub const fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the number of rows.
+    #[must_use]
+    pub const fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns whether no cell has a tile.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tiles.iter().all(|&tile| tile == EMPTY)
+    }
+
+    /// Returns the tile at a position, or `None` if the cell is empty or outside of the layer.
+    #[must_use]
+    pub fn get(&self, x: i32, y: i32) -> Option<i32> {
+        self.index(x, y)
+            .map(|index| self.tiles[index])
+            .filter(|&tile| tile != EMPTY)
+    }
+
+    /// Sets the tile at a position and returns the tile that was there, growing the layer if the position is outside
+    /// of it. Setting a tile to [`EMPTY`] is the same as [clearing](Self::clear) it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the layer would grow too large, see [`TileLayer::new`]. The layer isn't changed then.
+    pub fn set(&mut self, x: i32, y: i32, tile: i32) -> Result<Option<i32>, Error> {
+        if tile == EMPTY {
+            return Ok(self.clear(x, y));
+        }
+
+        let index = self.grow_to(x, y)?;
+        let old = core::mem::replace(&mut self.tiles[index], tile);
+
+        Ok((old != EMPTY).then_some(old))
+    }
+
+    /// Empties the cell at a position and returns the tile that was there. The layer doesn't shrink.
+    pub fn clear(&mut self, x: i32, y: i32) -> Option<i32> {
+        let index = self.index(x, y)?;
+        let old = core::mem::replace(&mut self.tiles[index], EMPTY);
+
+        (old != EMPTY).then_some(old)
+    }
+
+    /// Returns the positions and ids of all tiles, row by row from the bottom left.
+    pub fn iter(&self) -> impl Iterator<Item = ((i32, i32), i32)> + '_ {
+        let width = self.width as usize;
+
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter(|&(_, &tile)| tile != EMPTY)
+            .map(move |(index, &tile)| {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+                let (column, row) = ((index % width) as i32, (index / width) as i32);
+
+                ((self.origin.0 + column, self.origin.1 + row), tile)
+            })
+    }
+
+    /// Shrinks the layer to the smallest rectangle that contains all tiles.
+    pub fn shrink_to_fit(&mut self) {
+        let extent = self.iter().fold(None, |extent, ((x, y), _)| {
+            Some(
+                extent.map_or(((x, y), (x, y)), |(min, max): ((i32, i32), (i32, i32))| {
+                    ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+                }),
+            )
+        });
+
+        match extent {
+            Some((min, max)) => {
+                // the tiles are already inside the layer, so the smaller layer fits as well
+                if let Ok(resized) = self.resized(min, max) {
+                    *self = resized;
+                }
+            }
+            None => *self = Self::default(),
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> Option<usize> {
+        let column = u32::try_from(i64::from(x) - i64::from(self.origin.0)).ok()?;
+        let row = u32::try_from(i64::from(y) - i64::from(self.origin.1)).ok()?;
+
+        (column < self.width && row < self.height)
+            .then(|| row as usize * self.width as usize + column as usize)
+    }
+
+    /// Grows the layer to contain a cell, and returns the index of the cell.
+    fn grow_to(&mut self, x: i32, y: i32) -> Result<usize, Error> {
+        if let Some(index) = self.index(x, y) {
+            return Ok(index);
+        }
+
+        if self.tiles.is_empty() {
+            *self = Self::new((x, y), 1, 1)?;
+        } else {
+            let max = self.max();
+
+            *self = self.resized(
+                (self.origin.0.min(x), self.origin.1.min(y)),
+                (max.0.max(x), max.1.max(y)),
+            )?;
+        }
+
+        Ok(self
+            .index(x, y)
+            .expect("the layer was grown to contain the cell"))
+    }
+
+    /// The position of the top right cell. [`TileLayer::new`] makes sure it fits in an `i32`.
+    #[allow(clippy::cast_possible_wrap)]
+    const fn max(&self) -> (i32, i32) {
+        (
+            self.origin.0 + self.width as i32 - 1,
+            self.origin.1 + self.height as i32 - 1,
+        )
+    }
+
+    /// Returns a layer with the cells in the rectangle from `min` to `max`, and the tiles of this layer inside it.
+    fn resized(&self, min: (i32, i32), max: (i32, i32)) -> Result<Self, Error> {
+        let size = |min: i32, max: i32| {
+            u32::try_from(i64::from(max) - i64::from(min) + 1).map_err(|_| {
+                Error::InvalidTileLayer(format!("a layer from {min} to {max} is too large"))
+            })
+        };
+
+        let mut resized = Self::new(min, size(min.0, max.0)?, size(min.1, max.1)?)?;
+
+        for ((x, y), tile) in self.iter() {
+            if let Some(index) = resized.index(x, y) {
+                resized.tiles[index] = tile;
+            }
+        }
+
+        Ok(resized)
+    }
+}
+
+impl LevelData {
+    /// Returns the encoded tiles of one of the tile layers.
+    #[must_use]
+    pub const fn tiles(&self, kind: TileLayerKind) -> &Vec<i32> {
+        match kind {
+            TileLayerKind::UnderDecoration => &self.under_decoration_tiles,
+            TileLayerKind::BackgroundDecoration => &self.background_decoration_tiles,
+            TileLayerKind::Terrain => &self.terrain_tiles,
+            TileLayerKind::FloatingZone => &self.floating_zone_tiles,
+            TileLayerKind::Object => &self.object_tiles,
+            TileLayerKind::ForegroundDecoration => &self.foreground_decoration_tiles,
+        }
+    }
+
+    /// Returns the encoded tiles of one of the tile layers mutably.
+    pub const fn tiles_mut(&mut self, kind: TileLayerKind) -> &mut Vec<i32> {
+        match kind {
+            TileLayerKind::UnderDecoration => &mut self.under_decoration_tiles,
+            TileLayerKind::BackgroundDecoration => &mut self.background_decoration_tiles,
+            TileLayerKind::Terrain => &mut self.terrain_tiles,
+            TileLayerKind::FloatingZone => &mut self.floating_zone_tiles,
+            TileLayerKind::Object => &mut self.object_tiles,
+            TileLayerKind::ForegroundDecoration => &mut self.foreground_decoration_tiles,
+        }
+    }
+
+    /// Decodes one of the tile layers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tiles of the layer aren't [encoded correctly](TileLayer::decode).
+    pub fn tile_layer(&self, kind: TileLayerKind) -> Result<TileLayer, Error> {
+        TileLayer::decode(self.tiles(kind))
+    }
+
+    /// Replaces one of the tile layers.
+    pub fn set_tile_layer(&mut self, kind: TileLayerKind, layer: &TileLayer) {
+        *self.tiles_mut(kind) = layer.encode();
+    }
+}
@@ -12,6 +12,107 @@ pub struct Vec2 {
     pub y: OrderedFloat<f32>,
 }
 
+impl Vec2 {
+    /// The zero vector.
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+
+    /// Creates a new vector from its coordinates.
+    #[must_use]
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self {
+            x: OrderedFloat(x),
+            y: OrderedFloat(y),
+        }
+    }
+
+    /// Returns the dot product of `self` and `other`.
+    #[must_use]
+    pub fn dot(self, other: Self) -> f32 {
+        self.x.0.mul_add(other.x.0, self.y.0 * other.y.0)
+    }
+
+    /// Returns the 2D cross product (the z component of the 3D cross product) of `self` and `other`.
+    #[must_use]
+    pub fn cross(self, other: Self) -> f32 {
+        self.x.0.mul_add(other.y.0, -(self.y.0 * other.x.0))
+    }
+
+    /// Returns the length of the vector.
+    #[must_use]
+    pub fn length(self) -> f32 {
+        self.length_squared().sqrt()
+    }
+
+    /// Returns the squared length of the vector.
+    #[must_use]
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+
+    /// Returns the vector scaled to a length of 1, or the zero vector if its length is 0.
+    #[must_use]
+    pub fn normalize(self) -> Self {
+        let length = self.length();
+
+        if length == 0.0 {
+            Self::ZERO
+        } else {
+            self / length
+        }
+    }
+
+    /// Returns the vector rotated counter-clockwise by `degrees`.
+    #[must_use]
+    pub fn rotate(self, degrees: f32) -> Self {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+
+        Self::new(
+            self.x.0.mul_add(cos, -(self.y.0 * sin)),
+            self.x.0.mul_add(sin, self.y.0 * cos),
+        )
+    }
+}
+
+impl core::ops::Add for Vec2 {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.x.0 + rhs.x.0, self.y.0 + rhs.y.0)
+    }
+}
+
+impl core::ops::Sub for Vec2 {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x.0 - rhs.x.0, self.y.0 - rhs.y.0)
+    }
+}
+
+impl core::ops::Mul<f32> for Vec2 {
+    type Output = Self;
+
+    fn mul(self, rhs: f32) -> Self {
+        Self::new(self.x.0 * rhs, self.y.0 * rhs)
+    }
+}
+
+impl core::ops::Div<f32> for Vec2 {
+    type Output = Self;
+
+    fn div(self, rhs: f32) -> Self {
+        Self::new(self.x.0 / rhs, self.y.0 / rhs)
+    }
+}
+
+impl core::ops::Neg for Vec2 {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x.0, -self.y.0)
+    }
+}
+
 impl Read for Vec2 {
     fn read(input: &mut impl std::io::Read) -> Result<Self, Error> {
         Ok(Self {
@@ -0,0 +1,97 @@
+//! Walking the values and actions of scripts.
+
+use crate::types::{
+    action_type::ActionType,
+    novascript::{action::Action, nova_value::NovaValue, NovaScript},
+};
+
+/// Calls `f` on a value and every value nested in it, parents before their children.
+pub fn value(value: &NovaValue, f: &mut impl FnMut(&NovaValue)) {
+    f(value);
+
+    for sub_value in value.inner.sub_values.iter().flatten() {
+        self::value(sub_value, f);
+    }
+}
+
+/// Calls `f` on a value and every value nested in it, see [`value`].
+pub fn value_mut(value: &mut NovaValue, f: &mut impl FnMut(&mut NovaValue)) {
+    f(value);
+
+    for sub_value in value.inner.sub_values.iter_mut().flatten() {
+        value_mut(sub_value, f);
+    }
+}
+
+/// Calls `f` on every action in a list and every action nested in them, parents before their children.
+pub fn actions(actions: &[Action], f: &mut impl FnMut(&ActionType)) {
+    for action in actions {
+        f(&action.action_type);
+
+        for nested in action.action_type.actions() {
+            self::actions(nested, f);
+        }
+    }
+}
+
+/// Calls `f` on every action in a list and every action nested in them, see [`actions`].
+pub fn actions_mut(actions: &mut [Action], f: &mut impl FnMut(&mut ActionType)) {
+    for action in actions {
+        f(&mut action.action_type);
+
+        for nested in action.action_type.actions_mut() {
+            actions_mut(nested, f);
+        }
+    }
+}
+
+/// Calls `f` on every value in a script, see [`script_values_mut`].
+pub fn script_values(script: &NovaScript, f: &mut impl FnMut(&NovaValue)) {
+    value(&script.condition, f);
+
+    for activator in &script.activation_list {
+        for parameter in &activator.parameters {
+            value(parameter, f);
+        }
+    }
+
+    for parameter in &script.parameters {
+        value(&parameter.default_value, f);
+    }
+
+    for variable in &script.variables {
+        value(&variable.initial_value, f);
+    }
+
+    actions(&script.actions, &mut |action| {
+        for action_value in action.values() {
+            value(action_value, f);
+        }
+    });
+}
+
+/// Calls `f` on every value in a script: its condition, the parameters of its activators, the default values of its
+/// parameters, the initial values of its variables and the values of all of its actions, including nested values.
+pub fn script_values_mut(script: &mut NovaScript, f: &mut impl FnMut(&mut NovaValue)) {
+    value_mut(&mut script.condition, f);
+
+    for activator in &mut script.activation_list {
+        for parameter in &mut activator.parameters {
+            value_mut(parameter, f);
+        }
+    }
+
+    for parameter in &mut script.parameters {
+        value_mut(&mut parameter.default_value, f);
+    }
+
+    for variable in &mut script.variables {
+        value_mut(&mut variable.initial_value, f);
+    }
+
+    actions_mut(&mut script.actions, &mut |action| {
+        for value in action.values_mut() {
+            value_mut(value, f);
+        }
+    });
+}